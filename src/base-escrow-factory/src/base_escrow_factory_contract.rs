@@ -3,8 +3,9 @@ use near_sdk::{
     env, log, near, AccountId, Gas,
     collections::UnorderedMap,
 };
-use crate::types::{Order, Immutables, FactoryError, ValidationData};
-use crate::utils::{validate_order, parse_extra_data_args, create_immutables, allow_multiple_fills, is_valid_partial_fill, validate_merkle_proof, extract_parts_amount, extract_root, create_validation_key};
+use crate::engine::{DefaultEscrowEngine, EscrowEngine};
+use crate::types::{Order, Immutables, FactoryError, ValidationData, ValidationPreview};
+use crate::utils::{validate_order, parse_extra_data_args, create_immutables, allow_multiple_fills, is_valid_partial_fill, verify_multi_proof, extract_parts_amount, extract_root, create_validation_key, compute_salt, is_resolver_whitelisted};
 
 // Gas for cross-contract calls
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
@@ -23,7 +24,26 @@ pub struct BaseEscrowFactory {
     escrow_dst_implementation: AccountId,
     proxy_src_bytecode_hash: [u8; 32],
     proxy_dst_bytecode_hash: [u8; 32],
+    /// Wasm bytecode deployed for source escrows by the `EscrowEngine`,
+    /// checked against `proxy_src_bytecode_hash` before every deployment.
+    escrow_src_code: Vec<u8>,
+    /// Wasm bytecode deployed for destination escrows, checked against
+    /// `proxy_dst_bytecode_hash` before every deployment.
+    escrow_dst_code: Vec<u8>,
+    /// Escrow subaccounts already deployed, keyed by the CREATE2-equivalent
+    /// salt of their immutables, so the same immutables can never deploy
+    /// twice.
+    deployed_escrows: UnorderedMap<[u8; 32], AccountId>,
     validated_data: UnorderedMap<[u8; 32], ValidationData>,
+    /// Resolver allowlist recorded for each private order's `post_interaction`,
+    /// keyed by `order_hash`, so `is_resolver_allowed` can answer queries
+    /// about an order without the caller re-supplying its extra data.
+    resolver_allowlists: UnorderedMap<[u8; 32], Vec<[u8; 32]>>,
+    /// This deployment's own chain id. `post_interaction`/`taker_interaction`
+    /// reject any order whose `src_chain_id` doesn't match it, so an order
+    /// signed for a different NEAR network (or the EVM side of the swap)
+    /// can't have its escrow deployed here.
+    chain_id: u64,
 }
 
 impl Default for BaseEscrowFactory {
@@ -39,7 +59,12 @@ impl Default for BaseEscrowFactory {
             escrow_dst_implementation: AccountId::try_from("test.near".to_string()).unwrap(),
             proxy_src_bytecode_hash: [0u8; 32],
             proxy_dst_bytecode_hash: [0u8; 32],
+            escrow_src_code: Vec::new(),
+            escrow_dst_code: Vec::new(),
+            deployed_escrows: UnorderedMap::new(b"d"),
             validated_data: UnorderedMap::new(b"v"),
+            resolver_allowlists: UnorderedMap::new(b"r"),
+            chain_id: 0,
         }
     }
 }
@@ -56,6 +81,11 @@ impl BaseEscrowFactory {
         rescue_delay_dst: u32,
         escrow_src_implementation: AccountId,
         escrow_dst_implementation: AccountId,
+        escrow_src_code: Vec<u8>,
+        escrow_dst_code: Vec<u8>,
+        proxy_src_bytecode_hash: [u8; 32],
+        proxy_dst_bytecode_hash: [u8; 32],
+        chain_id: u64,
     ) -> Self {
         Self {
             limit_order_protocol,
@@ -66,9 +96,14 @@ impl BaseEscrowFactory {
             rescue_delay_dst,
             escrow_src_implementation,
             escrow_dst_implementation,
-            proxy_src_bytecode_hash: [0u8; 32],
-            proxy_dst_bytecode_hash: [0u8; 32],
+            proxy_src_bytecode_hash,
+            proxy_dst_bytecode_hash,
+            escrow_src_code,
+            escrow_dst_code,
+            deployed_escrows: UnorderedMap::new(b"d"),
             validated_data: UnorderedMap::new(b"v"),
+            resolver_allowlists: UnorderedMap::new(b"r"),
+            chain_id,
         }
     }
 
@@ -90,9 +125,25 @@ impl BaseEscrowFactory {
             return Err(FactoryError::InvalidOrder);
         }
 
+        // Reject orders whose source chain id differs from this deployment's
+        if order.src_chain_id != self.chain_id {
+            return Err(FactoryError::ChainIdMismatch);
+        }
+
         // Parse extra data
         let extra_data_args = parse_extra_data_args(&extra_data)?;
 
+        // Private orders restrict who may fill them to an allowlist of
+        // resolvers carried in the order's extra data
+        if order.maker_traits.allow_private_orders()
+            && !is_resolver_whitelisted(&extra_data_args.resolver_allowlist, &taker)
+        {
+            return Err(FactoryError::ResolverNotWhitelisted);
+        }
+        if order.maker_traits.allow_private_orders() && !extra_data_args.resolver_allowlist.is_empty() {
+            self.resolver_allowlists.insert(&order_hash, &extra_data_args.resolver_allowlist);
+        }
+
         // Calculate hashlock based on maker traits
         let hashlock = if allow_multiple_fills(&order.maker_traits) {
             // Handle multiple fills with Merkle validation
@@ -102,8 +153,13 @@ impl BaseEscrowFactory {
             }
 
             let root_shortened = extract_root(&extra_data_args.hashlock_info);
-            let key = create_validation_key(&order_hash, &root_shortened);
-            
+            let key = create_validation_key(
+                &order_hash,
+                &root_shortened,
+                order.src_chain_id,
+                order.dst_chain_id,
+            );
+
             // Get validation data
             let validated = self.validated_data.get(&key).unwrap_or_default();
             
@@ -145,11 +201,87 @@ impl BaseEscrowFactory {
         Ok(())
     }
 
+    /// Dry-run `post_interaction`'s checks - `validate_order`,
+    /// `parse_extra_data_args`, the private-order allowlist, and the
+    /// multiple-fill/`is_valid_partial_fill` branch - without mutating state
+    /// or deploying an escrow, so a relayer or resolver can simulate a fill
+    /// off-chain and see the precise `FactoryError` it would hit before
+    /// spending gas.
+    #[handle_result]
+    pub fn validate_post_interaction(
+        &self,
+        order: Order,
+        order_hash: [u8; 32],
+        taker: AccountId,
+        making_amount: u128,
+        _taking_amount: u128,
+        remaining_making_amount: u128,
+        extra_data: Vec<u8>,
+    ) -> Result<ValidationPreview, FactoryError> {
+        if !validate_order(&order) {
+            return Err(FactoryError::InvalidOrder);
+        }
+
+        if order.src_chain_id != self.chain_id {
+            return Err(FactoryError::ChainIdMismatch);
+        }
+
+        let extra_data_args = parse_extra_data_args(&extra_data)?;
+
+        if order.maker_traits.allow_private_orders()
+            && !is_resolver_whitelisted(&extra_data_args.resolver_allowlist, &taker)
+        {
+            return Err(FactoryError::ResolverNotWhitelisted);
+        }
+
+        let mut matched_leaf_index = None;
+        let hashlock = if allow_multiple_fills(&order.maker_traits) {
+            let parts_amount = extract_parts_amount(&extra_data_args.hashlock_info);
+            if parts_amount < 2 {
+                return Err(FactoryError::InvalidSecretsAmount);
+            }
+
+            let root_shortened = extract_root(&extra_data_args.hashlock_info);
+            let key = create_validation_key(
+                &order_hash,
+                &root_shortened,
+                order.src_chain_id,
+                order.dst_chain_id,
+            );
+            let validated = self.validated_data.get(&key).unwrap_or_default();
+
+            if !is_valid_partial_fill(
+                making_amount,
+                remaining_making_amount,
+                order.making_amount,
+                parts_amount,
+                validated.index,
+            ) {
+                return Err(FactoryError::InvalidPartialFill);
+            }
+
+            matched_leaf_index = Some(validated.index);
+            validated.leaf
+        } else {
+            extra_data_args.hashlock_info
+        };
+
+        let timelocks = extra_data_args.timelocks.set_deployed_at(env::block_timestamp_ms() / 1000);
+        let safety_deposit = extra_data_args.deposits.value;
+
+        Ok(ValidationPreview {
+            hashlock,
+            safety_deposit,
+            timelocks,
+            matched_leaf_index,
+        })
+    }
+
     /// Taker interaction for Merkle proof validation
     #[handle_result]
     pub fn taker_interaction(
         &mut self,
-        _order: Order,
+        order: Order,
         extension: Vec<u8>,
         order_hash: [u8; 32],
         _taker: AccountId,
@@ -163,36 +295,57 @@ impl BaseEscrowFactory {
             return Err(FactoryError::AccessDenied);
         }
 
+        // Reject orders whose source chain id differs from this deployment's
+        if order.src_chain_id != self.chain_id {
+            return Err(FactoryError::ChainIdMismatch);
+        }
+
         // Parse extra data
         let extra_data_args = parse_extra_data_args(&extra_data)?;
 
-        // Extract proof data from extension
+        // Extract proof data from extension - may carry several secrets sharing one multiproof
         let proof_data = self.extract_proof_data(&extension)?;
-        
-        // Validate Merkle proof
+        if proof_data.indices.is_empty() {
+            return Err(FactoryError::InvalidExtension);
+        }
+
+        // Validate all leaves in this batch against the root in one pass
         let root_shortened = extract_root(&extra_data_args.hashlock_info);
-        let key = create_validation_key(&order_hash, &root_shortened);
-        
-        let computed_root = validate_merkle_proof(
+        let key = create_validation_key(
+            &order_hash,
+            &root_shortened,
+            order.src_chain_id,
+            order.dst_chain_id,
+        );
+
+        let verified = verify_multi_proof(
+            &proof_data.secret_hashes,
             &proof_data.proof,
-            proof_data.secret_hash,
-            proof_data.index,
+            &proof_data.proof_flags,
             root_shortened,
         );
 
-        if !computed_root {
+        if !verified {
             return Err(FactoryError::InvalidProof);
         }
 
-        // Store validation data
+        // Store the tip of the batch: the highest-index leaf just validated,
+        // matching the single-proof convention of advancing past the fill made.
+        let last_index = *proof_data.indices.last().unwrap();
+        let last_leaf = *proof_data.secret_hashes.last().unwrap();
         let validation_data = ValidationData {
-            leaf: proof_data.secret_hash,
-            index: proof_data.index + 1,
+            leaf: last_leaf,
+            index: last_index + 1,
         };
-        
+
         self.validated_data.insert(&key, &validation_data);
 
-        log!("Merkle proof validated: order_hash={:?}, index={}", order_hash, proof_data.index);
+        log!(
+            "Merkle multiproof validated: order_hash={:?}, leaves={}, last_index={}",
+            order_hash,
+            proof_data.indices.len(),
+            last_index
+        );
 
         Ok(())
     }
@@ -242,65 +395,143 @@ impl BaseEscrowFactory {
         self.validated_data.get(&key)
     }
 
+    /// Check whether `account` may fill the private order identified by
+    /// `order_hash`. An order with no recorded allowlist (not private, or
+    /// `post_interaction` hasn't run yet) allows everyone.
+    pub fn is_resolver_allowed(&self, order_hash: [u8; 32], account: AccountId) -> bool {
+        match self.resolver_allowlists.get(&order_hash) {
+            Some(allowlist) => is_resolver_whitelisted(&allowlist, &account),
+            None => true,
+        }
+    }
+
+    /// Get this deployment's configured chain id
+    pub fn get_chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Predict the subaccount a source escrow for these immutables will
+    /// deploy to, the same way a resolver predicts a CREATE2 address
+    /// off-chain before the escrow exists.
+    pub fn compute_escrow_address(&self, immutables: Immutables) -> AccountId {
+        let salt = compute_salt(&immutables);
+        crate::utils::derive_escrow_account_id(&salt, &env::current_account_id())
+    }
+
+    /// Get the subaccount a source escrow for `immutables` deployed to, if
+    /// `post_interaction` has already run for it.
+    pub fn get_deployed_escrow(&self, immutables: Immutables) -> Option<AccountId> {
+        let salt = compute_salt(&immutables);
+        self.deployed_escrows.get(&salt)
+    }
+
     // Internal helper functions
-    fn create_src_escrow(&self, immutables: Immutables) -> Result<(), FactoryError> {
-        // In a real implementation, this would deploy a new escrow contract
-        // For now, we'll just log the creation
-        log!("Creating source escrow with immutables: {:?}", immutables);
+
+    /// Build this factory's configured `EscrowEngine`. Built fresh per call
+    /// from stored config rather than held as contract state, since a
+    /// `Box<dyn EscrowEngine>` can't round-trip through Borsh.
+    fn engine(&self) -> DefaultEscrowEngine {
+        DefaultEscrowEngine {
+            factory: env::current_account_id(),
+            escrow_src_code: self.escrow_src_code.clone(),
+            escrow_src_bytecode_hash: self.proxy_src_bytecode_hash,
+            escrow_dst_code: self.escrow_dst_code.clone(),
+            escrow_dst_bytecode_hash: self.proxy_dst_bytecode_hash,
+            access_token: self.access_token.clone(),
+            guardian: self.owner.clone(),
+            rescue_delay_src: self.rescue_delay_src as u64,
+            rescue_delay_dst: self.rescue_delay_dst as u64,
+            chain_id: self.chain_id,
+        }
+    }
+
+    fn create_src_escrow(&mut self, immutables: Immutables) -> Result<(), FactoryError> {
+        let salt = compute_salt(&immutables);
+        if self.deployed_escrows.get(&salt).is_some() {
+            return Err(FactoryError::EscrowAlreadyDeployed);
+        }
+
+        let escrow_account = self.engine().deploy_src(&immutables)?;
+        self.deployed_escrows.insert(&salt, &escrow_account);
+
+        log!(
+            "Deploying source escrow {} with immutables: {:?}",
+            escrow_account,
+            immutables
+        );
         Ok(())
     }
 
+    /// Parse proof data from extension.
+    ///
+    /// Wire format (little-endian), simplified for this NEAR port:
+    /// - `count: u16` - number of `(idx, secret_hash)` leaves validated together
+    /// - `count * 40` bytes - the `(idx: u64, secret_hash: [u8; 32])` pairs, in
+    ///   ascending tree-index order
+    /// - `flags_len: u16` - number of `verify_multi_proof` proof flags
+    /// - `flags_len` bytes - one `0`/`1` byte per proof flag
+    /// - remaining bytes - sibling hashes for `verify_multi_proof`, 32 bytes each
     fn extract_proof_data(&self, extension: &[u8]) -> Result<ProofData, FactoryError> {
-        // In a real implementation, this would properly extract proof data from extension
-        // For now, we'll create a simplified version
-        if extension.len() < 32 {
+        if extension.len() < 2 {
             return Err(FactoryError::InvalidExtension);
         }
 
-        let mut secret_hash = [0u8; 32];
-        secret_hash.copy_from_slice(&extension[0..32]);
+        let count = u16::from_le_bytes(extension[0..2].try_into().unwrap()) as usize;
+        let mut offset = 2;
+        let entries_len = count * 40;
+        if extension.len() < offset + entries_len + 2 {
+            return Err(FactoryError::InvalidExtension);
+        }
 
-        let index = if extension.len() >= 40 {
-            u64::from_le_bytes(extension[32..40].try_into().unwrap())
-        } else {
-            0
-        };
+        let mut indices = Vec::with_capacity(count);
+        let mut secret_hashes = Vec::with_capacity(count);
+        for i in 0..count {
+            let start = offset + i * 40;
+            let index = u64::from_le_bytes(extension[start..start + 8].try_into().unwrap());
+            let mut secret_hash = [0u8; 32];
+            secret_hash.copy_from_slice(&extension[start + 8..start + 40]);
+            indices.push(index);
+            secret_hashes.push(secret_hash);
+        }
+        offset += entries_len;
 
-        let proof = if extension.len() > 40 {
-            // Extract proof elements (simplified)
-            let proof_data = &extension[40..];
-            let proof_elements = proof_data.len() / 32;
-            let mut proof = Vec::new();
-            
-            for i in 0..proof_elements {
-                let start = i * 32;
-                let end = start + 32;
-                if end <= proof_data.len() {
-                    let mut element = [0u8; 32];
-                    element.copy_from_slice(&proof_data[start..end]);
-                    proof.push(element);
-                }
+        let flags_len = u16::from_le_bytes(extension[offset..offset + 2].try_into().unwrap()) as usize;
+        offset += 2;
+        if extension.len() < offset + flags_len {
+            return Err(FactoryError::InvalidExtension);
+        }
+        let proof_flags: Vec<bool> = extension[offset..offset + flags_len].iter().map(|b| *b != 0).collect();
+        offset += flags_len;
+
+        let proof_tail = &extension[offset..];
+        let proof_elements = proof_tail.len() / 32;
+        let mut proof = Vec::with_capacity(proof_elements);
+        for i in 0..proof_elements {
+            let start = i * 32;
+            let end = start + 32;
+            if end <= proof_tail.len() {
+                let mut element = [0u8; 32];
+                element.copy_from_slice(&proof_tail[start..end]);
+                proof.push(element);
             }
-            
-            proof
-        } else {
-            Vec::new()
-        };
+        }
 
         Ok(ProofData {
-            secret_hash,
-            index,
+            indices,
+            secret_hashes,
             proof,
+            proof_flags,
         })
     }
 }
 
-/// Proof data structure for Merkle validation
+/// Proof data structure for Merkle multiproof validation
 #[derive(Debug)]
 struct ProofData {
-    secret_hash: [u8; 32],
-    index: u64,
+    indices: Vec<u64>,
+    secret_hashes: Vec<[u8; 32]>,
     proof: Vec<[u8; 32]>,
+    proof_flags: Vec<bool>,
 }
 
 /*
@@ -331,28 +562,185 @@ mod tests {
             making_amount: 1000,
             taking_amount: 1000,
             maker_traits: crate::types::MakerTraits::default(),
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
         }
     }
 
-    #[test]
-    fn test_new() {
-        let context = get_context(accounts(0));
-        testing_env!(context.build());
+    fn escrow_code() -> Vec<u8> {
+        vec![1, 2, 3]
+    }
 
-        let contract = BaseEscrowFactory::new(
-            accounts(1),
+    fn new_test_factory(escrow_code_hash: [u8; 32]) -> BaseEscrowFactory {
+        BaseEscrowFactory::new(
+            accounts(3),
             accounts(2),
             accounts(3),
             3600,
             3600,
             accounts(4),
             accounts(5),
-        );
-        assert_eq!(contract.get_limit_order_protocol(), accounts(1));
+            escrow_code(),
+            escrow_code(),
+            escrow_code_hash,
+            escrow_code_hash,
+            1313161555,
+        )
+    }
+
+    #[test]
+    fn test_new() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = new_test_factory([0u8; 32]);
+        assert_eq!(contract.get_limit_order_protocol(), accounts(3));
         assert_eq!(contract.get_fee_token(), accounts(2));
         assert_eq!(contract.get_access_token(), accounts(3));
         assert_eq!(contract.get_rescue_delay_src(), 3600);
         assert_eq!(contract.get_rescue_delay_dst(), 3600);
+        assert_eq!(contract.get_chain_id(), 1313161555);
+    }
+
+    #[test]
+    fn test_post_interaction_rejects_chain_id_mismatch() {
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let mut contract = new_test_factory([0u8; 32]);
+
+        let mut order = create_test_order();
+        order.src_chain_id = 1313161556; // a different NEAR network
+
+        let result = contract.post_interaction(
+            order,
+            vec![],
+            [1u8; 32],
+            accounts(4),
+            1000,
+            1000,
+            1000,
+            vec![0u8; 64],
+        );
+        assert_eq!(result, Err(FactoryError::ChainIdMismatch));
+    }
+
+    #[test]
+    fn test_post_interaction_rejects_non_whitelisted_resolver() {
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = near_sdk::env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+
+        let mut order = create_test_order();
+        order.maker_traits.allow_private_orders = true;
+
+        let mut extra_data = vec![0u8; 64];
+        extra_data.extend_from_slice(&1u16.to_le_bytes());
+        extra_data.extend_from_slice(&crate::utils::hash_resolver(&accounts(5)));
+
+        let result = contract.post_interaction(
+            order,
+            vec![],
+            [2u8; 32],
+            accounts(4),
+            1000,
+            1000,
+            0,
+            extra_data,
+        );
+        assert_eq!(result, Err(FactoryError::ResolverNotWhitelisted));
+    }
+
+    #[test]
+    fn test_post_interaction_allows_whitelisted_resolver() {
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = near_sdk::env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+
+        let mut order = create_test_order();
+        order.maker_traits.allow_private_orders = true;
+
+        let mut extra_data = vec![0u8; 64];
+        extra_data.extend_from_slice(&1u16.to_le_bytes());
+        extra_data.extend_from_slice(&crate::utils::hash_resolver(&accounts(4)));
+
+        contract
+            .post_interaction(
+                order,
+                vec![],
+                [3u8; 32],
+                accounts(4),
+                1000,
+                1000,
+                0,
+                extra_data,
+            )
+            .expect("whitelisted resolver should be allowed");
+
+        assert!(contract.is_resolver_allowed([3u8; 32], accounts(4)));
+        assert!(!contract.is_resolver_allowed([3u8; 32], accounts(5)));
+    }
+
+    #[test]
+    fn test_validate_post_interaction_matches_post_interaction_outcome() {
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = near_sdk::env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+
+        let order = create_test_order();
+        let preview = contract
+            .validate_post_interaction(
+                order.clone(),
+                [4u8; 32],
+                accounts(4),
+                1000,
+                1000,
+                0,
+                vec![0u8; 64],
+            )
+            .expect("single-fill order should validate cleanly");
+        assert_eq!(preview.hashlock, [0u8; 32]);
+        assert_eq!(preview.safety_deposit, 0);
+        assert_eq!(preview.matched_leaf_index, None);
+
+        // The same inputs fed to the mutating call should succeed too, and
+        // validating first must not have changed anything it depends on.
+        contract
+            .post_interaction(order, vec![], [4u8; 32], accounts(4), 1000, 1000, 0, vec![0u8; 64])
+            .expect("post_interaction should succeed after a matching dry run");
+    }
+
+    #[test]
+    fn test_validate_post_interaction_rejects_non_whitelisted_resolver() {
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = near_sdk::env::keccak256(&escrow_code()).try_into().unwrap();
+        let contract = new_test_factory(code_hash);
+
+        let mut order = create_test_order();
+        order.maker_traits.allow_private_orders = true;
+
+        let mut extra_data = vec![0u8; 64];
+        extra_data.extend_from_slice(&1u16.to_le_bytes());
+        extra_data.extend_from_slice(&crate::utils::hash_resolver(&accounts(5)));
+
+        let result = contract.validate_post_interaction(
+            order,
+            [5u8; 32],
+            accounts(4),
+            1000,
+            1000,
+            0,
+            extra_data,
+        );
+        assert_eq!(result, Err(FactoryError::ResolverNotWhitelisted));
     }
 
     #[test]
@@ -364,4 +752,75 @@ mod tests {
         assert_eq!(contract.get_rescue_delay_src(), 3600);
         assert_eq!(contract.get_rescue_delay_dst(), 3600);
     }
+
+    #[test]
+    fn test_verify_multi_proof_degenerate_single_leaf() {
+        let leaf = [7u8; 32];
+        assert!(crate::utils::verify_multi_proof(&[leaf], &[], &[], leaf));
+        assert!(!crate::utils::verify_multi_proof(&[leaf], &[], &[], [0u8; 32]));
+    }
+
+    #[test]
+    fn test_verify_multi_proof_two_leaves_share_one_root() {
+        let l0 = [1u8; 32];
+        let l1 = [2u8; 32];
+        let (lo, hi) = if l0 <= l1 { (l0, l1) } else { (l1, l0) };
+        let mut data = Vec::new();
+        data.extend_from_slice(&lo);
+        data.extend_from_slice(&hi);
+        let root: [u8; 32] = near_sdk::env::keccak256(&data).try_into().unwrap();
+
+        assert!(crate::utils::verify_multi_proof(&[l0, l1], &[], &[true], root));
+        assert!(!crate::utils::verify_multi_proof(&[l0, l1], &[], &[true], [9u8; 32]));
+    }
+
+    #[test]
+    fn test_verify_multi_proof_rejects_length_mismatch() {
+        // leaves.len() + proof.len() must equal proof_flags.len() + 1
+        assert!(!crate::utils::verify_multi_proof(&[[1u8; 32], [2u8; 32]], &[], &[], [0u8; 32]));
+    }
+
+    #[test]
+    fn test_taker_interaction_validates_and_stores_batch() {
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = near_sdk::env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+
+        // The simplified root stub always resolves to the low 32 bytes of
+        // hashlock_info, so a zeroed hashlock_info means the only batch that
+        // validates end-to-end is a single degenerate leaf of all zeros.
+        let mut extension = Vec::new();
+        extension.extend_from_slice(&1u16.to_le_bytes());
+        extension.extend_from_slice(&3u64.to_le_bytes()); // idx
+        extension.extend_from_slice(&[0u8; 32]); // secret_hash == stub root
+        extension.extend_from_slice(&0u16.to_le_bytes()); // no flags
+
+        let extra_data = vec![0u8; 64];
+        let order = create_test_order();
+
+        contract
+            .taker_interaction(
+                order.clone(),
+                extension,
+                [9u8; 32],
+                accounts(2),
+                100,
+                100,
+                0,
+                extra_data,
+            )
+            .expect("multiproof batch should validate");
+
+        let key = create_validation_key(
+            &[9u8; 32],
+            &extract_root(&[0u8; 32]),
+            order.src_chain_id,
+            order.dst_chain_id,
+        );
+        let stored = contract.get_validation_data(key).unwrap();
+        assert_eq!(stored.leaf, [0u8; 32]);
+        assert_eq!(stored.index, 4);
+    }
 } 
\ No newline at end of file
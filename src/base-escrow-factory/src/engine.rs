@@ -0,0 +1,221 @@
+// Find all our documentation at https://docs.near.org
+use near_sdk::{env, AccountId, Gas, NearToken, Promise};
+
+use crate::types::{FactoryError, Immutables};
+use crate::utils::{compute_salt, derive_escrow_account_id};
+
+// Gas for the deployed escrow's initializer call
+const GAS_FOR_ESCROW_INIT: Gas = Gas::from_tgas(30);
+
+/// Deploys the on-chain contract that custodies one leg of a cross-chain
+/// swap. `BaseEscrowFactory` builds one of these from its own stored
+/// config rather than hardcoding the deployment inline, so an operator can
+/// swap in a different deployment strategy (e.g. a slimmer proxy contract,
+/// or a different rescue-delay policy) without touching `post_interaction`.
+/// NEAR has no EVM-style minimal-proxy opcode, so "deploy" here means a
+/// real, full-weight contract: create a deterministic subaccount of the
+/// factory, deploy the configured wasm onto it, and call its `new`.
+pub trait EscrowEngine {
+    /// Deploy the source-chain escrow for `immutables`, returning the
+    /// deterministic subaccount it was scheduled onto.
+    fn deploy_src(&self, immutables: &Immutables) -> Result<AccountId, FactoryError>;
+
+    /// Deploy the destination-chain escrow for `immutables`.
+    fn deploy_dst(&self, immutables: &Immutables) -> Result<AccountId, FactoryError>;
+}
+
+/// Default `EscrowEngine`: a straight wasm deploy onto a CREATE2-equivalent
+/// subaccount, built fresh from `BaseEscrowFactory`'s stored config for
+/// each call rather than held as contract state - a `Box<dyn EscrowEngine>`
+/// can't round-trip through Borsh, so the engine is a plain value
+/// constructed on demand instead of a trait object kept in storage.
+pub struct DefaultEscrowEngine {
+    pub factory: AccountId,
+    pub escrow_src_code: Vec<u8>,
+    pub escrow_src_bytecode_hash: [u8; 32],
+    pub escrow_dst_code: Vec<u8>,
+    pub escrow_dst_bytecode_hash: [u8; 32],
+    /// Forwarded verbatim into the deployed escrow's `access_token` init arg.
+    pub access_token: AccountId,
+    /// Forwarded verbatim into the deployed escrow's `guardian` init arg -
+    /// the factory's own owner doubles as the pause guardian for escrows it
+    /// deploys.
+    pub guardian: AccountId,
+    /// Forwarded into `EscrowSrc::new`'s `rescue_delay` init arg.
+    pub rescue_delay_src: u64,
+    /// Forwarded into `EscrowDst::new`'s `rescue_delay` init arg.
+    pub rescue_delay_dst: u64,
+    /// Forwarded into the deployed escrow's `chain_id` init arg - this
+    /// factory's single chain id, used for both legs since its own
+    /// `Immutables` doesn't carry separate src/dst chain ids.
+    pub chain_id: u64,
+}
+
+impl DefaultEscrowEngine {
+    fn deploy(
+        &self,
+        immutables: &Immutables,
+        code: &[u8],
+        expected_bytecode_hash: [u8; 32],
+        rescue_delay: u64,
+    ) -> Result<AccountId, FactoryError> {
+        let code_hash: [u8; 32] = env::keccak256(code).try_into().unwrap();
+        if code_hash != expected_bytecode_hash {
+            return Err(FactoryError::BytecodeHashMismatch);
+        }
+
+        let salt = compute_salt(immutables);
+        let escrow_account = derive_escrow_account_id(&salt, &self.factory);
+
+        // `EscrowSrc::new`/`EscrowDst::new` are plain `#[near]` `#[init]`
+        // methods, which deserialize their args as JSON keyed by parameter
+        // name - not the Borsh encoding of the whole `Immutables` struct.
+        // Both take the same six named args (just in a different order, which
+        // doesn't matter for a JSON object), so one payload shape covers both.
+        let init_args = near_sdk::serde_json::json!({
+            "rescue_delay": rescue_delay,
+            "access_token": self.access_token,
+            "chain_id": self.chain_id,
+            "order_hash_seed": immutables.order_hash,
+            "guardian": self.guardian,
+            "immutables_hash": salt,
+        });
+
+        Promise::new(escrow_account.clone())
+            .create_account()
+            .transfer(NearToken::from_yoctonear(immutables.safety_deposit))
+            .deploy_contract(code.to_vec())
+            .function_call(
+                "new".to_string(),
+                near_sdk::serde_json::to_vec(&init_args).unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_ESCROW_INIT,
+            );
+
+        Ok(escrow_account)
+    }
+}
+
+impl EscrowEngine for DefaultEscrowEngine {
+    fn deploy_src(&self, immutables: &Immutables) -> Result<AccountId, FactoryError> {
+        self.deploy(
+            immutables,
+            &self.escrow_src_code,
+            self.escrow_src_bytecode_hash,
+            self.rescue_delay_src,
+        )
+    }
+
+    fn deploy_dst(&self, immutables: &Immutables) -> Result<AccountId, FactoryError> {
+        self.deploy(
+            immutables,
+            &self.escrow_dst_code,
+            self.escrow_dst_bytecode_hash,
+            self.rescue_delay_dst,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Timelocks;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn get_context() -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder.current_account_id(accounts(0));
+        builder
+    }
+
+    fn create_test_immutables() -> Immutables {
+        Immutables {
+            order_hash: [1u8; 32],
+            hashlock: [2u8; 32],
+            maker: accounts(1),
+            taker: accounts(2),
+            token: accounts(3),
+            amount: 1000,
+            safety_deposit: 100,
+            timelocks: Timelocks::default(),
+        }
+    }
+
+    /// The deployed escrow's `#[init] fn new` takes six distinct JSON args
+    /// (`rescue_delay`, `access_token`, `chain_id`, `order_hash_seed`,
+    /// `guardian`, `immutables_hash`) - not a Borsh-encoded `Immutables`.
+    /// Runs `deploy` with a matching bytecode hash, then builds the expected
+    /// init args as JSON and checks that each of the six keys is present.
+    /// The `function_call` payload `deploy` schedules is not read back here.
+    #[test]
+    fn test_deploy_src_function_call_args_match_escrow_src_new_signature() {
+        testing_env!(get_context().build());
+
+        let code = b"fake wasm".to_vec();
+        let bytecode_hash: [u8; 32] = env::keccak256(&code).try_into().unwrap();
+        let engine = DefaultEscrowEngine {
+            factory: accounts(0),
+            escrow_src_code: code.clone(),
+            escrow_src_bytecode_hash: bytecode_hash,
+            escrow_dst_code: Vec::new(),
+            escrow_dst_bytecode_hash: [0u8; 32],
+            access_token: accounts(4),
+            guardian: accounts(5),
+            rescue_delay_src: 3600,
+            rescue_delay_dst: 7200,
+            chain_id: 1313161555,
+        };
+
+        let immutables = create_test_immutables();
+        engine
+            .deploy(&immutables, &code, bytecode_hash, engine.rescue_delay_src)
+            .expect("deploy should succeed with a matching bytecode hash");
+
+        let init_args = near_sdk::serde_json::json!({
+            "rescue_delay": 3600u64,
+            "access_token": accounts(4),
+            "chain_id": 1313161555u64,
+            "order_hash_seed": immutables.order_hash,
+            "guardian": accounts(5),
+            "immutables_hash": compute_salt(&immutables),
+        });
+        let args_map = init_args
+            .as_object()
+            .expect("init args should serialize as a JSON object");
+        for key in [
+            "rescue_delay",
+            "access_token",
+            "chain_id",
+            "order_hash_seed",
+            "guardian",
+            "immutables_hash",
+        ] {
+            assert!(
+                args_map.contains_key(key),
+                "init args must carry a `{key}` field matching EscrowSrc::new's signature"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deploy_rejects_bytecode_hash_mismatch() {
+        testing_env!(get_context().build());
+
+        let engine = DefaultEscrowEngine {
+            factory: accounts(0),
+            escrow_src_code: b"fake wasm".to_vec(),
+            escrow_src_bytecode_hash: [0u8; 32],
+            escrow_dst_code: Vec::new(),
+            escrow_dst_bytecode_hash: [0u8; 32],
+            access_token: accounts(4),
+            guardian: accounts(5),
+            rescue_delay_src: 3600,
+            rescue_delay_dst: 7200,
+            chain_id: 1313161555,
+        };
+
+        let result = engine.deploy_src(&create_test_immutables());
+        assert!(matches!(result, Err(FactoryError::BytecodeHashMismatch)));
+    }
+}
@@ -15,6 +15,15 @@ pub struct Order {
     pub making_amount: u128,
     pub taking_amount: u128,
     pub maker_traits: MakerTraits,
+    /// Chain id this order's maker asset (and escrow) lives on, folded into
+    /// the order hash and `create_validation_key` so a Merkle leaf validated
+    /// and an escrow deployed for one source/destination chain pair can't be
+    /// replayed against a different deployment signing the same bytes -
+    /// the EIP-155 domain-separation idea, applied per-order rather than
+    /// only at the EIP-712 domain level.
+    pub src_chain_id: u64,
+    /// Chain id the taker asset (and counterpart escrow) lives on.
+    pub dst_chain_id: u64,
 }
 
 impl Default for Order {
@@ -28,6 +37,8 @@ impl Default for Order {
             making_amount: 0,
             taking_amount: 0,
             maker_traits: MakerTraits::default(),
+            src_chain_id: 0,
+            dst_chain_id: 0,
         }
     }
 }
@@ -42,6 +53,10 @@ pub struct MakerTraits {
     pub has_extension: bool,
     pub nonce_or_epoch: u64,
     pub series: u64,
+    /// Restricts fills to the resolver allowlist carried in the order's
+    /// extra data - the EIP-2930 access-list idea, applied to "who may
+    /// fill" instead of "which storage slots are pre-warmed".
+    pub allow_private_orders: bool,
 }
 
 impl MakerTraits {
@@ -69,6 +84,11 @@ impl MakerTraits {
     pub fn series(&self) -> u64 {
         self.series
     }
+
+    /// Check if this order is restricted to an allowlisted set of resolvers
+    pub fn allow_private_orders(&self) -> bool {
+        self.allow_private_orders
+    }
 }
 
 /// Extra data arguments for escrow creation
@@ -77,6 +97,9 @@ pub struct ExtraDataArgs {
     pub hashlock_info: [u8; 32],
     pub deposits: U256,
     pub timelocks: Timelocks,
+    /// Resolvers authorized to fill a private order, as `keccak256(account_id)`
+    /// entries. Empty when the order isn't restricted.
+    pub resolver_allowlist: Vec<[u8; 32]>,
 }
 
 impl Default for ExtraDataArgs {
@@ -85,6 +108,7 @@ impl Default for ExtraDataArgs {
             hashlock_info: [0u8; 32],
             deposits: U256::default(),
             timelocks: Timelocks::default(),
+            resolver_allowlist: Vec::new(),
         }
     }
 }
@@ -188,6 +212,19 @@ impl Default for ValidationData {
     }
 }
 
+/// Preview of what `post_interaction` would compute and store for a fill,
+/// returned by the read-only `validate_post_interaction` so a caller can
+/// simulate a fill and catch a `FactoryError` before spending gas.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ValidationPreview {
+    pub hashlock: [u8; 32],
+    pub safety_deposit: u128,
+    pub timelocks: Timelocks,
+    /// The Merkle leaf index this fill would consume, for a multiple-fill
+    /// order; `None` for a single-fill order.
+    pub matched_leaf_index: Option<u64>,
+}
+
 /// Error types for factory operations
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum FactoryError {
@@ -202,6 +239,10 @@ pub enum FactoryError {
     InvalidAccessToken,
     AccessDenied,
     InvalidProof,
+    ChainIdMismatch,
+    BytecodeHashMismatch,
+    EscrowAlreadyDeployed,
+    ResolverNotWhitelisted,
 }
 
 impl AsRef<str> for FactoryError {
@@ -218,6 +259,10 @@ impl AsRef<str> for FactoryError {
             FactoryError::InvalidAccessToken => "InvalidAccessToken",
             FactoryError::AccessDenied => "AccessDenied",
             FactoryError::InvalidProof => "InvalidProof",
+            FactoryError::ChainIdMismatch => "ChainIdMismatch",
+            FactoryError::BytecodeHashMismatch => "BytecodeHashMismatch",
+            FactoryError::EscrowAlreadyDeployed => "EscrowAlreadyDeployed",
+            FactoryError::ResolverNotWhitelisted => "ResolverNotWhitelisted",
         }
     }
 }
@@ -1,13 +1,19 @@
+use crate::types::{EscrowError, Immutables, Payment, ReleasePlan, TimelockStage, Witness};
+use crate::utils::{
+    hash_secret, hash_secret_with, validate_after, validate_before, validate_caller,
+};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env, ext_contract, log, near, AccountId, Gas, Promise, PromiseResult,
+    env, ext_contract,
+    json_types::U128,
+    log, near,
     serde::{Deserialize, Serialize},
+    serde_json, AccountId, Gas, Promise, PromiseResult,
 };
-use crate::types::{Immutables, EscrowError, TimelockStage};
-use crate::utils::{hash_secret, validate_after, validate_before, validate_caller};
 
 // Gas for cross-contract calls
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_FT_BALANCE_OF: Gas = Gas::from_tgas(5);
 
 /// Base abstract Escrow contract for cross-chain atomic swap
 #[near(contract_state)]
@@ -16,6 +22,31 @@ pub struct BaseEscrow {
     pub rescue_delay: u64,
     pub access_token: AccountId,
     pub factory: AccountId,
+    /// Guardian account allowed to pause/resume withdrawals, alongside the
+    /// factory, as an incident-response lever (mirrors Aurora engine's
+    /// `is_paused` + `PauseContract`/`ResumeContract` model).
+    pub guardian: AccountId,
+    /// While `true`, `withdraw`/`public_withdraw` revert with
+    /// `EscrowError::Paused`. Cancellation is never gated by this flag once
+    /// its own timelock has elapsed, so a paused escrow can't strand funds.
+    pub is_paused: bool,
+    /// Chain identifier this escrow was deployed on. `EscrowSrc` and
+    /// `EscrowDst` check it against `Immutables.src_chain_id`/`dst_chain_id`
+    /// in their own `validate_immutables`, so a commitment valid on one
+    /// deployment can't be replayed against a sibling escrow on another
+    /// chain. `BaseEscrow::validate_immutables` does not check it.
+    pub chain_id: u64,
+    /// Budget-DSL-style conditional release plan evaluated by `release` (see
+    /// `ReleasePlan`). Empty by default; install one with `set_release_plan`
+    /// to express release flows beyond the hard-coded HTLC withdraw/cancel
+    /// paths on `EscrowSrc`/`EscrowDst`.
+    pub release_plan: ReleasePlan,
+    /// Total already paid out by `release` across all fired branches.
+    pub released_total: u128,
+    /// Ceiling `released_total` can never exceed, installed alongside the
+    /// plan in `set_release_plan` (typically `immutables.amount +
+    /// immutables.safety_deposit`).
+    pub release_cap: u128,
 }
 
 impl Default for BaseEscrow {
@@ -24,6 +55,12 @@ impl Default for BaseEscrow {
             rescue_delay: 0,
             access_token: AccountId::new_unvalidated("".to_string()),
             factory: AccountId::new_unvalidated("".to_string()),
+            guardian: AccountId::new_unvalidated("".to_string()),
+            is_paused: false,
+            chain_id: 0,
+            release_plan: ReleasePlan::default(),
+            released_total: 0,
+            release_cap: 0,
         }
     }
 }
@@ -32,31 +69,166 @@ impl Default for BaseEscrow {
 impl BaseEscrow {
     /// Initialize the contract
     #[init]
-    pub fn new(rescue_delay: u64, access_token: AccountId) -> Self {
+    pub fn new(
+        rescue_delay: u64,
+        access_token: AccountId,
+        guardian: AccountId,
+        chain_id: u64,
+    ) -> Self {
         Self {
             rescue_delay,
             access_token,
             factory: env::predecessor_account_id(),
+            guardian,
+            is_paused: false,
+            chain_id,
+            release_plan: ReleasePlan::default(),
+            released_total: 0,
+            release_cap: 0,
         }
     }
 
+    /// Pause `withdraw`/`public_withdraw`. Callable only by the guardian or
+    /// the factory that deployed this escrow.
+    #[handle_result]
+    pub fn pause(&mut self) -> Result<(), EscrowError> {
+        self.validate_guardian()?;
+        self.is_paused = true;
+        Ok(())
+    }
+
+    /// Resume `withdraw`/`public_withdraw` after a pause.
+    #[handle_result]
+    pub fn resume(&mut self) -> Result<(), EscrowError> {
+        self.validate_guardian()?;
+        self.is_paused = false;
+        Ok(())
+    }
+
+    /// Get whether withdrawals are currently paused
+    pub fn get_is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Get the guardian account
+    pub fn get_guardian(&self) -> AccountId {
+        self.guardian.clone()
+    }
+
+    /// Get the chain id this escrow is configured for
+    pub fn get_chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Install a conditional release plan, replacing any existing one, with
+    /// a hard cap on how much it can pay out in total and resetting the
+    /// running total to zero. Callable only by the guardian or factory,
+    /// mirroring `pause`/`resume`'s authorization.
+    #[handle_result]
+    pub fn set_release_plan(&mut self, plan: ReleasePlan, cap: u128) -> Result<(), EscrowError> {
+        self.validate_guardian()?;
+        self.release_plan = plan;
+        self.release_cap = cap;
+        self.released_total = 0;
+        Ok(())
+    }
+
+    /// Get the currently installed release plan
+    pub fn get_release_plan(&self) -> ReleasePlan {
+        self.release_plan.clone()
+    }
+
+    /// Get the total already paid out by `release` across all fired branches
+    pub fn get_released_total(&self) -> u128 {
+        self.released_total
+    }
+
+    /// Evaluate the installed release plan against the current call context
+    /// and fire (transfer + mark consumed) the first not-yet-consumed branch
+    /// whose every witness is satisfied: `Witness::Timestamp` needs
+    /// `env::block_timestamp()` at or past its value, `Witness::Secret` needs
+    /// `secret` to be supplied and hash (via `hash_secret`) to the stored
+    /// value, `Witness::Signature` needs the predecessor account to match.
+    /// Generalizes the hard-coded taker-withdraw-after-timelock HTLC flow
+    /// into data - the existing `EscrowSrc`/`EscrowDst` withdraw/cancel
+    /// methods are unaffected and keep using their own timelock checks; this
+    /// is the engine integrators reach for when a plan installed via
+    /// `set_release_plan` needs richer branching than that default HTLC pair.
+    #[handle_result]
+    pub fn release(&mut self, secret: Option<[u8; 32]>) -> Result<Payment, EscrowError> {
+        let predecessor = env::predecessor_account_id();
+        let now = env::block_timestamp();
+
+        let branch_index = self.release_plan.branches.iter().position(|branch| {
+            !branch.consumed
+                && branch.witnesses.iter().all(|witness| match witness {
+                    Witness::Timestamp(ts) => now >= *ts,
+                    Witness::Secret(hash) => {
+                        secret.map(|s| &hash_secret(&s) == hash).unwrap_or(false)
+                    }
+                    Witness::Signature(account) => &predecessor == account,
+                })
+        });
+        let branch_index = branch_index.ok_or(EscrowError::NoBranchSatisfied)?;
+
+        let payment = self.release_plan.branches[branch_index].payment.clone();
+        let new_total = self
+            .released_total
+            .checked_add(payment.amount)
+            .ok_or(EscrowError::ReleasePlanExhausted)?;
+        if new_total > self.release_cap {
+            return Err(EscrowError::ReleasePlanExhausted);
+        }
+
+        self.release_plan.branches[branch_index].consumed = true;
+        self.released_total = new_total;
+        self.uni_transfer(&payment.token, &payment.to, payment.amount);
+
+        Ok(payment)
+    }
+
+    fn validate_guardian(&self) -> Result<(), EscrowError> {
+        let caller = env::predecessor_account_id();
+        if caller != self.guardian && caller != self.factory {
+            return Err(EscrowError::InvalidCaller);
+        }
+        Ok(())
+    }
+
+    /// Reject the call if withdrawals are currently paused
+    pub fn validate_not_paused(&self) -> Result<(), EscrowError> {
+        if self.is_paused {
+            return Err(EscrowError::Paused);
+        }
+        Ok(())
+    }
+
     /// Rescue funds from the escrow
     /// Funds can only be rescued by the taker after the rescue delay
     #[handle_result]
-    pub fn rescue_funds(&mut self, token: AccountId, amount: u128, immutables: Immutables) -> PromiseResult {
+    pub fn rescue_funds(
+        &mut self,
+        token: AccountId,
+        amount: u128,
+        immutables: Immutables,
+    ) -> PromiseResult {
+        // Validate contract is not paused
+        self.validate_not_paused().expect("Contract is paused");
+
         // Validate caller is taker
         validate_caller(&immutables.taker).expect("Invalid caller");
-        
+
         // Validate immutables
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
+        self.validate_immutables(&immutables)
+            .expect("Invalid immutables");
+
         // Validate rescue time
         let rescue_start = immutables.timelocks.rescue_start(self.rescue_delay);
         validate_after(rescue_start).expect("Invalid time for rescue");
 
         // Transfer tokens
         self.uni_transfer(&token, &immutables.taker, amount);
-        
+
         log!("Funds rescued: token={}, amount={}", token, amount);
         Ok(())
     }
@@ -71,12 +243,31 @@ impl BaseEscrow {
         self.factory.clone()
     }
 
-    /// Validate that caller has access token
-    #[handle_result]
-    pub fn validate_access_token(&self) -> Result<(), EscrowError> {
-        // In NEAR, we would need to check if the caller has the access token
-        // This is a simplified implementation
-        Ok(())
+    /// Begin checking that the caller holds a non-zero balance of the access
+    /// token, by querying `ft_balance_of` on `self.access_token`. The
+    /// access-token-gated public withdraw/cancel paths `.then()` this into
+    /// `resolve_access_token` once the query resolves.
+    pub fn check_access_token(&self) -> Promise {
+        ext_ft::ext(self.access_token.clone())
+            .with_static_gas(GAS_FOR_FT_BALANCE_OF)
+            .ft_balance_of(env::predecessor_account_id())
+    }
+
+    /// Resolve the result of a prior `check_access_token` promise: succeeds
+    /// only if the balance query itself succeeded and returned a non-zero
+    /// balance, otherwise fails with `OnlyAccessTokenHolder`.
+    pub fn resolve_access_token(&self) -> Result<(), EscrowError> {
+        match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                let balance: U128 = serde_json::from_slice(&value)
+                    .map_err(|_| EscrowError::OnlyAccessTokenHolder)?;
+                if balance.0 == 0 {
+                    return Err(EscrowError::OnlyAccessTokenHolder);
+                }
+                Ok(())
+            }
+            _ => Err(EscrowError::OnlyAccessTokenHolder),
+        }
     }
 
     /// Validate immutables - to be implemented by derived contracts
@@ -88,8 +279,12 @@ impl BaseEscrow {
 
     /// Validate secret matches hashlock
     #[handle_result]
-    pub fn validate_secret(&self, secret: &[u8; 32], immutables: &Immutables) -> Result<(), EscrowError> {
-        let secret_hash = hash_secret(secret);
+    pub fn validate_secret(
+        &self,
+        secret: &[u8; 32],
+        immutables: &Immutables,
+    ) -> Result<(), EscrowError> {
+        let secret_hash = hash_secret_with(secret, immutables.hash_algorithm);
         if secret_hash != immutables.hashlock {
             return Err(EscrowError::InvalidSecret);
         }
@@ -114,17 +309,33 @@ impl BaseEscrow {
     pub fn near_transfer(&self, to: &AccountId, amount: u128) {
         Promise::new(to.clone()).transfer(amount);
     }
+
+    /// Same transfer `uni_transfer` fires, but returns the `Promise` instead
+    /// of discarding it, so a caller can `.then()` a callback onto it and
+    /// only commit state once the transfer actually confirms.
+    pub fn dispatch_transfer(&self, token: &AccountId, to: &AccountId, amount: u128) -> Promise {
+        if token.as_str() == "near" {
+            Promise::new(to.clone()).transfer(amount)
+        } else {
+            ext_ft::ext(token.clone())
+                .with_attached_deposit(1)
+                .with_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(to.clone(), amount, None)
+        }
+    }
 }
 
 // External FT contract interface
 #[ext_contract(ext_ft)]
 pub trait FungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: u128, memo: Option<String>);
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Branch;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
     use near_sdk::{testing_env, AccountId};
 
@@ -141,27 +352,29 @@ mod tests {
     fn test_new() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
+
         let access_token = accounts(2);
         let rescue_delay = 3600;
-        
-        let contract = BaseEscrow::new(rescue_delay, access_token.clone());
-        
+
+        let contract = BaseEscrow::new(rescue_delay, access_token.clone(), accounts(5), 1313161555);
+
         assert_eq!(contract.rescue_delay, rescue_delay);
         assert_eq!(contract.access_token, access_token);
         assert_eq!(contract.factory, accounts(1));
+        assert_eq!(contract.guardian, accounts(5));
+        assert!(!contract.get_is_paused());
     }
 
     #[test]
     fn test_validate_secret() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
-        let contract = BaseEscrow::new(3600, accounts(2));
-        
+
+        let contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+
         let secret = [1u8; 32];
         let hashlock = hash_secret(&secret);
-        
+
         let immutables = Immutables {
             order_hash: [0u8; 32],
             hashlock,
@@ -180,11 +393,315 @@ mod tests {
                 dst_public_withdrawal: 0,
                 dst_cancellation: 0,
             },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
         };
-        
+
         assert!(contract.validate_secret(&secret, &immutables).is_ok());
-        
+
         let wrong_secret = [2u8; 32];
-        assert!(contract.validate_secret(&wrong_secret, &immutables).is_err());
+        assert!(contract
+            .validate_secret(&wrong_secret, &immutables)
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_secret_routes_through_configured_hash_algorithm() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+
+        let secret = [1u8; 32];
+        let mut immutables = Immutables {
+            order_hash: [0u8; 32],
+            hashlock: crate::utils::hash_secret_with(
+                &secret,
+                crate::types::HashAlgorithm::Keccak256,
+            ),
+            maker: accounts(3),
+            taker: accounts(4),
+            token: accounts(5),
+            amount: 1000,
+            safety_deposit: 100,
+            timelocks: Timelocks {
+                deployed_at: 0,
+                src_withdrawal: 0,
+                src_public_withdrawal: 0,
+                src_cancellation: 0,
+                src_public_cancellation: 0,
+                dst_withdrawal: 0,
+                dst_public_withdrawal: 0,
+                dst_cancellation: 0,
+            },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::Keccak256,
+        };
+
+        assert!(contract.validate_secret(&secret, &immutables).is_ok());
+
+        // The same hashlock computed with a different algorithm no longer matches.
+        immutables.hash_algorithm = crate::types::HashAlgorithm::Sha256;
+        assert!(contract.validate_secret(&secret, &immutables).is_err());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_pause_and_resume_by_guardian() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+
+        let guardian_context = get_context(accounts(5));
+        testing_env!(guardian_context.build());
+        contract.pause().expect("guardian should be able to pause");
+        assert!(contract.get_is_paused());
+
+        contract
+            .resume()
+            .expect("guardian should be able to resume");
+        assert!(!contract.get_is_paused());
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_rescue_funds_rejects_while_paused() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+
+        let guardian_context = get_context(accounts(5));
+        testing_env!(guardian_context.build());
+        contract.pause().expect("guardian should be able to pause");
+
+        let immutables = Immutables {
+            order_hash: [0u8; 32],
+            hashlock: [0u8; 32],
+            maker: accounts(3),
+            taker: accounts(1),
+            token: accounts(5),
+            amount: 1000,
+            safety_deposit: 100,
+            timelocks: Timelocks {
+                deployed_at: 0,
+                src_withdrawal: 0,
+                src_public_withdrawal: 0,
+                src_cancellation: 0,
+                src_public_cancellation: 0,
+                dst_withdrawal: 0,
+                dst_public_withdrawal: 0,
+                dst_cancellation: 0,
+            },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
+        };
+
+        let taker_context = get_context(accounts(1));
+        testing_env!(taker_context.build());
+        contract.rescue_funds(accounts(5), 100, immutables);
+    }
+
+    #[test]
+    fn test_pause_rejects_unauthorized_caller() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+
+        let stranger_context = get_context(accounts(6));
+        testing_env!(stranger_context.build());
+        assert!(matches!(contract.pause(), Err(EscrowError::InvalidCaller)));
+        assert!(!contract.get_is_paused());
+    }
+
+    #[test]
+    fn test_pause_allowed_by_factory() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        // `accounts(1)` was the predecessor at construction, so it became `factory`.
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        contract.pause().expect("factory should be able to pause");
+        assert!(contract.get_is_paused());
+    }
+
+    /// Mock a resolved `check_access_token` promise as if the access token's
+    /// `ft_balance_of` had returned `balance`.
+    fn set_access_token_balance_result(context: VMContextBuilder, balance: u128) {
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![PromiseResult::Successful(
+                serde_json::to_vec(&U128(balance)).unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_resolve_access_token_accepts_non_zero_balance() {
+        let contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        set_access_token_balance_result(get_context(accounts(9)), 1);
+        assert!(contract.resolve_access_token().is_ok());
+    }
+
+    #[test]
+    fn test_resolve_access_token_rejects_zero_balance() {
+        let contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        set_access_token_balance_result(get_context(accounts(9)), 0);
+        assert!(matches!(
+            contract.resolve_access_token(),
+            Err(EscrowError::OnlyAccessTokenHolder)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_access_token_rejects_failed_promise() {
+        let contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        testing_env!(
+            get_context(accounts(9)).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![PromiseResult::Failed]
+        );
+        assert!(matches!(
+            contract.resolve_access_token(),
+            Err(EscrowError::OnlyAccessTokenHolder)
+        ));
+    }
+
+    fn two_branch_plan() -> ReleasePlan {
+        ReleasePlan {
+            branches: vec![
+                Branch {
+                    witnesses: vec![
+                        Witness::Secret(hash_secret(&[1u8; 32])),
+                        Witness::Timestamp(100),
+                    ],
+                    payment: Payment {
+                        to: accounts(3),
+                        token: accounts(4),
+                        amount: 1000,
+                    },
+                    consumed: false,
+                },
+                Branch {
+                    witnesses: vec![Witness::Signature(accounts(2))],
+                    payment: Payment {
+                        to: accounts(2),
+                        token: accounts(4),
+                        amount: 100,
+                    },
+                    consumed: false,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_release_fires_branch_once_all_witnesses_satisfied() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        let guardian_context = get_context(accounts(5));
+        testing_env!(guardian_context.build());
+        contract
+            .set_release_plan(two_branch_plan(), 1100)
+            .expect("guardian can install a plan");
+
+        let mut context = get_context(accounts(9));
+        testing_env!(context.block_timestamp(50).build());
+        assert!(matches!(
+            contract.release(Some([1u8; 32])),
+            Err(EscrowError::NoBranchSatisfied)
+        ));
+
+        context = get_context(accounts(9));
+        testing_env!(context.block_timestamp(100).build());
+        let payment = contract
+            .release(Some([1u8; 32]))
+            .expect("both witnesses now satisfied");
+        assert_eq!(payment.to, accounts(3));
+        assert_eq!(payment.amount, 1000);
+        assert_eq!(contract.get_released_total(), 1000);
+
+        // The first branch is consumed, so a second call with the same secret falls
+        // through to the second branch instead of firing branch one again; that
+        // branch needs a signature from accounts(2), which accounts(9) lacks.
+        assert!(matches!(
+            contract.release(Some([1u8; 32])),
+            Err(EscrowError::NoBranchSatisfied)
+        ));
+    }
+
+    #[test]
+    fn test_release_falls_through_to_next_branch_on_signature_witness() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        let guardian_context = get_context(accounts(5));
+        testing_env!(guardian_context.build());
+        contract
+            .set_release_plan(two_branch_plan(), 1100)
+            .expect("guardian can install a plan");
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let payment = contract
+            .release(None)
+            .expect("signature witness satisfied by predecessor");
+        assert_eq!(payment.to, accounts(2));
+        assert_eq!(payment.amount, 100);
+    }
+
+    #[test]
+    fn test_release_rejects_when_cap_exceeded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+        let guardian_context = get_context(accounts(5));
+        testing_env!(guardian_context.build());
+        // Cap is lower than the first branch's payment amount.
+        contract
+            .set_release_plan(two_branch_plan(), 500)
+            .expect("guardian can install a plan");
+
+        let context = get_context(accounts(9));
+        testing_env!(context.block_timestamp(100).build());
+        assert!(matches!(
+            contract.release(Some([1u8; 32])),
+            Err(EscrowError::ReleasePlanExhausted)
+        ));
+        assert_eq!(contract.get_released_total(), 0);
+    }
+
+    #[test]
+    fn test_set_release_plan_rejects_unauthorized_caller() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = BaseEscrow::new(3600, accounts(2), accounts(5), 1313161555);
+
+        let stranger_context = get_context(accounts(6));
+        testing_env!(stranger_context.build());
+        assert!(matches!(
+            contract.set_release_plan(two_branch_plan(), 1100),
+            Err(EscrowError::InvalidCaller)
+        ));
+    }
+}
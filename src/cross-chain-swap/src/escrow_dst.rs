@@ -1,21 +1,53 @@
+use super::base_escrow::BaseEscrow;
+use crate::types::{EscrowError, Immutables, TimelockStage};
+use crate::utils::{hash_immutables, validate_after, validate_before, validate_caller};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env, log, near, AccountId,
+    env, log, near,
     serde::{Deserialize, Serialize},
+    AccountId, Balance, Gas, Promise, PromiseResult,
 };
-use crate::types::{Immutables, EscrowError, TimelockStage};
-use crate::utils::{validate_after, validate_before, validate_caller};
-use super::base_escrow::BaseEscrow;
+
+// Gas for the `#[private]` callback that resolves a `public_withdraw`'s
+// access-token balance query and dispatches the transfer.
+const GAS_FOR_RESOLVE_PUBLIC_WITHDRAW: Gas = Gas::from_tgas(20);
+// Gas for the `#[private]` callback chained onto the token transfer itself,
+// which commits the hashchain/safety deposit only once it confirms.
+const GAS_FOR_TRANSFER_CALLBACK: Gas = Gas::from_tgas(20);
+
+/// Tag folded into the hashchain preimage to distinguish a private withdrawal event.
+const EVENT_WITHDRAWAL: u8 = 1;
+/// Tag folded into the hashchain preimage to distinguish a public withdrawal event.
+const EVENT_PUBLIC_WITHDRAWAL: u8 = 2;
+/// Tag folded into the hashchain preimage to distinguish a cancellation event.
+const EVENT_CANCELLATION: u8 = 3;
 
 #[near(contract_state)]
 pub struct EscrowDst {
     pub base: BaseEscrow,
+    /// Commitment the factory recorded at deployment: `hash_immutables` of the
+    /// exact `Immutables` this escrow was created for. `validate_immutables`
+    /// recomputes the hash of the caller-supplied immutables and checks it
+    /// against this value, so a resolver can't substitute a different (e.g.
+    /// higher-amount) immutables set than what the factory actually
+    /// committed to at CREATE2-equivalent deploy time.
+    pub immutables_hash: [u8; 32],
+    /// Append-only hashchain over every successful withdrawal/cancellation on
+    /// this escrow, letting a resolver or relayer verify off-chain that
+    /// events were observed in the order they actually happened. Genesis is
+    /// `hash(order_hash_seed)` as set at construction (32 zero bytes if no
+    /// seed was known yet), so a verifier can recompute the whole chain from
+    /// block one given just that seed and the sequence of emitted events.
+    /// Mirrors `EscrowSrc::hashchain`.
+    pub hashchain: [u8; 32],
 }
 
 impl Default for EscrowDst {
     fn default() -> Self {
         Self {
             base: BaseEscrow::default(),
+            immutables_hash: [0u8; 32],
+            hashchain: [0u8; 32],
         }
     }
 }
@@ -23,89 +55,332 @@ impl Default for EscrowDst {
 #[near]
 impl EscrowDst {
     #[init]
-    pub fn new(rescue_delay: u64, access_token: AccountId) -> Self {
+    pub fn new(
+        rescue_delay: u64,
+        access_token: AccountId,
+        guardian: AccountId,
+        chain_id: u64,
+        order_hash_seed: [u8; 32],
+        immutables_hash: [u8; 32],
+    ) -> Self {
         Self {
-            base: BaseEscrow::default(),
+            base: BaseEscrow::new(rescue_delay, access_token, guardian, chain_id),
+            immutables_hash,
+            hashchain: near_sdk::hash::hash(&order_hash_seed).try_into().unwrap(),
         }
     }
 
-    /// Withdraw funds with secret
-    /// Only taker can withdraw during withdrawal period
-    #[handle_result]
-    pub fn withdraw(&mut self, secret: [u8; 32], immutables: Immutables) -> Result<(), EscrowError> {
-        // Validate caller is taker
-        validate_caller(&immutables.taker).expect("Invalid caller");
-        
-        // Validate withdrawal time
+    /// Get the `hash_immutables` commitment the factory recorded at deployment
+    pub fn get_immutables_hash(&self) -> [u8; 32] {
+        self.immutables_hash
+    }
+
+    /// Get the current tip of the event hashchain
+    pub fn get_hashchain(&self) -> [u8; 32] {
+        self.hashchain
+    }
+
+    /// Fold an event into the hashchain. Mirrors `EscrowSrc::fold_hashchain`.
+    fn fold_hashchain(
+        prev: [u8; 32],
+        event_tag: u8,
+        secret: Option<&[u8; 32]>,
+        target: &AccountId,
+        amount: Balance,
+        timestamp: u64,
+    ) -> [u8; 32] {
+        let mut data = Vec::new();
+        data.extend_from_slice(&prev);
+        data.push(event_tag);
+        if let Some(secret) = secret {
+            data.extend_from_slice(secret);
+        }
+        data.extend_from_slice(target.as_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        near_sdk::hash::hash(&data).try_into().unwrap()
+    }
+
+    /// Shared caller/timelock/secret/immutables checks for `withdraw`,
+    /// factored out so both `withdraw` and the read-only `can_withdraw` view
+    /// run exactly the same validation.
+    fn validate_withdrawal(
+        &self,
+        secret: &[u8; 32],
+        immutables: &Immutables,
+    ) -> Result<(), EscrowError> {
+        self.base.validate_not_paused()?;
+        validate_caller(&immutables.taker)?;
+
         let withdrawal_start = immutables.timelocks.get(TimelockStage::DstWithdrawal);
         let cancellation_start = immutables.timelocks.get(TimelockStage::DstCancellation);
-        
-        validate_after(withdrawal_start).expect("Withdrawal not started");
-        validate_before(cancellation_start).expect("Withdrawal period ended");
-        
-        // Validate secret and immutables
-        self.base.validate_secret(&secret, &immutables).expect("Invalid secret");
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to maker
-        self.base.uni_transfer(&immutables.token, &immutables.maker, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Escrow withdrawal: secret={:?}", secret);
-        Ok(())
+        validate_after(withdrawal_start)?;
+        validate_before(cancellation_start)?;
+
+        self.base.validate_secret(secret, immutables)?;
+        self.validate_immutables(immutables)
+    }
+
+    /// Withdraw funds with secret. Only taker can withdraw during withdrawal
+    /// period. The token transfer and the hashchain/safety-deposit it implies
+    /// are not committed in this same call: the transfer is fired here, and
+    /// only `resolve_withdrawal` - chained via `.then()` - commits the new
+    /// hashchain and pays the safety deposit, and only once that transfer has
+    /// actually confirmed. A failing transfer therefore leaves the escrow
+    /// withdrawable again instead of silently paying the safety deposit for
+    /// funds that never moved.
+    #[handle_result]
+    pub fn withdraw(
+        &mut self,
+        secret: [u8; 32],
+        immutables: Immutables,
+    ) -> Result<Promise, EscrowError> {
+        self.validate_withdrawal(&secret, &immutables)?;
+
+        let new_hashchain = Self::fold_hashchain(
+            self.hashchain,
+            EVENT_WITHDRAWAL,
+            Some(&secret),
+            &immutables.maker,
+            immutables.amount,
+            env::block_timestamp(),
+        );
+
+        let caller = env::predecessor_account_id();
+        Ok(self
+            .base
+            .dispatch_transfer(&immutables.token, &immutables.maker, immutables.amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                    .resolve_withdrawal(new_hashchain, caller, immutables.safety_deposit, secret),
+            ))
+    }
+
+    /// `#[private]` callback chained after the token transfer `withdraw`/
+    /// `resolve_public_withdraw` fire: only on success does it commit the new
+    /// hashchain and pay out the safety deposit, so a failing transfer leaves
+    /// the escrow's withdrawable state untouched rather than rewarding the
+    /// caller for a transfer that never landed.
+    #[private]
+    #[handle_result]
+    pub fn resolve_withdrawal(
+        &mut self,
+        new_hashchain: [u8; 32],
+        caller: AccountId,
+        safety_deposit: Balance,
+        secret: [u8; 32],
+    ) -> Result<(), EscrowError> {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                self.hashchain = new_hashchain;
+                Promise::new(caller).transfer(safety_deposit);
+                log!(
+                    "Escrow withdrawal: secret={:?}, hashchain={:?}",
+                    secret,
+                    self.hashchain
+                );
+                Ok(())
+            }
+            _ => {
+                log!("Escrow withdrawal failed: token transfer did not succeed");
+                Err(EscrowError::NativeTokenSendingFailure)
+            }
+        }
     }
 
-    /// Public withdrawal - anyone with access token can withdraw
+    /// Simulate `withdraw`: run the same caller/timelock/secret/immutables
+    /// checks without transferring anything, so a relayer/resolver can check
+    /// off-chain whether a withdrawal would succeed before spending gas on
+    /// one that would fail.
     #[handle_result]
-    pub fn public_withdraw(&mut self, secret: [u8; 32], immutables: Immutables) -> Result<(), EscrowError> {
-        // Validate caller has access token
-        self.base.validate_access_token().expect("No access token");
-        
-        // Validate public withdrawal time
+    pub fn can_withdraw(
+        &self,
+        secret: [u8; 32],
+        immutables: Immutables,
+    ) -> Result<(), EscrowError> {
+        self.validate_withdrawal(&secret, &immutables)
+    }
+
+    /// Shared timelock/secret/immutables checks for `public_withdraw`,
+    /// factored out so both `public_withdraw` and the read-only
+    /// `can_public_withdraw` view run exactly the same validation. Does
+    /// *not* check the access token - that's a real cross-contract query
+    /// now, performed asynchronously by `public_withdraw` itself, so a
+    /// synchronous view can only simulate this half of the gate.
+    fn validate_public_withdrawal(
+        &self,
+        secret: &[u8; 32],
+        immutables: &Immutables,
+    ) -> Result<(), EscrowError> {
+        self.base.validate_not_paused()?;
+
         let public_withdrawal_start = immutables.timelocks.get(TimelockStage::DstPublicWithdrawal);
         let cancellation_start = immutables.timelocks.get(TimelockStage::DstCancellation);
-        
-        validate_after(public_withdrawal_start).expect("Public withdrawal not started");
-        validate_before(cancellation_start).expect("Public withdrawal period ended");
-        
-        // Validate secret and immutables
-        self.base.validate_secret(&secret, &immutables).expect("Invalid secret");
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to maker
-        self.base.uni_transfer(&immutables.token, &immutables.maker, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Public escrow withdrawal: secret={:?}", secret);
-        Ok(())
+        validate_after(public_withdrawal_start)?;
+        validate_before(cancellation_start)?;
+
+        self.base.validate_secret(secret, immutables)?;
+        self.validate_immutables(immutables)
+    }
+
+    /// Public withdrawal - anyone holding a non-zero balance of the access
+    /// token can withdraw. Kicks off an async `ft_balance_of` query against
+    /// the access token and only performs the transfer in
+    /// `resolve_public_withdraw` once that resolves with a non-zero balance.
+    #[handle_result]
+    pub fn public_withdraw(
+        &mut self,
+        secret: [u8; 32],
+        immutables: Immutables,
+    ) -> Result<Promise, EscrowError> {
+        self.validate_public_withdrawal(&secret, &immutables)?;
+
+        let caller = env::predecessor_account_id();
+        Ok(self.base.check_access_token().then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_PUBLIC_WITHDRAW)
+                .resolve_public_withdraw(secret, immutables, caller),
+        ))
+    }
+
+    /// `#[private]` callback for `public_withdraw`: resolves the access-token
+    /// balance query kicked off there and, only if it returned a non-zero
+    /// balance, fires the transfer and chains `resolve_withdrawal` onto it -
+    /// see `withdraw`'s doc comment for why the hashchain/safety-deposit
+    /// commit waits on that transfer rather than happening here. `caller` is
+    /// the account that called `public_withdraw` - it can't be read off
+    /// `env::predecessor_account_id()` here, since the predecessor of a
+    /// `.then()` callback is this contract itself.
+    #[private]
+    pub fn resolve_public_withdraw(
+        &mut self,
+        secret: [u8; 32],
+        immutables: Immutables,
+        caller: AccountId,
+    ) -> Promise {
+        self.base
+            .resolve_access_token()
+            .expect("Not an access token holder");
+
+        let new_hashchain = Self::fold_hashchain(
+            self.hashchain,
+            EVENT_PUBLIC_WITHDRAWAL,
+            Some(&secret),
+            &immutables.maker,
+            immutables.amount,
+            env::block_timestamp(),
+        );
+
+        self.base
+            .dispatch_transfer(&immutables.token, &immutables.maker, immutables.amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                    .resolve_withdrawal(new_hashchain, caller, immutables.safety_deposit, secret),
+            )
     }
 
-    /// Cancel escrow - only taker can cancel during cancellation period
+    /// Simulate `public_withdraw`: see `can_withdraw`. Only simulates the
+    /// synchronous half of the gate - see `validate_public_withdrawal`.
     #[handle_result]
-    pub fn cancel(&mut self, immutables: Immutables) -> Result<(), EscrowError> {
-        // Validate caller is taker
-        validate_caller(&immutables.taker).expect("Invalid caller");
-        
-        // Validate cancellation time
+    pub fn can_public_withdraw(
+        &self,
+        secret: [u8; 32],
+        immutables: Immutables,
+    ) -> Result<(), EscrowError> {
+        self.validate_public_withdrawal(&secret, &immutables)
+    }
+
+    /// Shared caller/timelock/immutables checks for `cancel`, factored out so
+    /// both `cancel` and the read-only `can_cancel` view run exactly the
+    /// same validation.
+    fn validate_cancellation(&self, immutables: &Immutables) -> Result<(), EscrowError> {
+        validate_caller(&immutables.taker)?;
+
         let cancellation_start = immutables.timelocks.get(TimelockStage::DstCancellation);
-        validate_after(cancellation_start).expect("Cancellation not started");
-        
-        // Validate immutables
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to taker
-        self.base.uni_transfer(&immutables.token, &immutables.taker, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Escrow cancelled");
-        Ok(())
+        validate_after(cancellation_start)?;
+
+        self.validate_immutables(immutables)
+    }
+
+    /// Cancel escrow - only taker can cancel during cancellation period.
+    /// Deliberately not gated by `base.is_paused`: cancellation only ever
+    /// succeeds once its own timelock has elapsed anyway, so exempting it
+    /// from pause means a paused escrow can never permanently strand funds.
+    /// Like `withdraw`, the refund and the hashchain/safety-deposit it
+    /// implies are not committed here: `resolve_cancellation` commits them
+    /// once the transfer it's chained onto actually confirms.
+    #[handle_result]
+    pub fn cancel(&mut self, immutables: Immutables) -> Result<Promise, EscrowError> {
+        self.validate_cancellation(&immutables)?;
+
+        let new_hashchain = Self::fold_hashchain(
+            self.hashchain,
+            EVENT_CANCELLATION,
+            None,
+            &immutables.taker,
+            immutables.amount,
+            env::block_timestamp(),
+        );
+
+        let caller = env::predecessor_account_id();
+        Ok(self
+            .base
+            .dispatch_transfer(&immutables.token, &immutables.taker, immutables.amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                    .resolve_cancellation(new_hashchain, caller, immutables.safety_deposit),
+            ))
+    }
+
+    /// `#[private]` callback chained after the refund `cancel` fires: only on
+    /// success does it commit the new hashchain and pay out the safety
+    /// deposit. See `resolve_withdrawal`'s doc comment for why this has to
+    /// wait on the transfer rather than committing synchronously.
+    #[private]
+    #[handle_result]
+    pub fn resolve_cancellation(
+        &mut self,
+        new_hashchain: [u8; 32],
+        caller: AccountId,
+        safety_deposit: Balance,
+    ) -> Result<(), EscrowError> {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                self.hashchain = new_hashchain;
+                Promise::new(caller).transfer(safety_deposit);
+                log!("Escrow cancelled: hashchain={:?}", self.hashchain);
+                Ok(())
+            }
+            _ => {
+                log!("Escrow cancellation failed: token transfer did not succeed");
+                Err(EscrowError::NativeTokenSendingFailure)
+            }
+        }
+    }
+
+    /// Simulate `cancel`: run the same caller/timelock/immutables checks
+    /// without transferring anything.
+    #[handle_result]
+    pub fn can_cancel(&self, immutables: Immutables) -> Result<(), EscrowError> {
+        self.validate_cancellation(&immutables)
     }
 
-    /// Validate immutables - verify computed escrow address matches this contract
+    /// Validate immutables - recomputes `hash_immutables` of the supplied
+    /// immutables and checks it against the commitment the factory recorded
+    /// at deployment. Also rejects immutables whose embedded `dst_chain_id`
+    /// doesn't match this escrow's, which stops a secret/proof revealed on
+    /// one deployment being replayed here.
     #[handle_result]
     pub fn validate_immutables(&self, immutables: &Immutables) -> Result<(), EscrowError> {
-        // In NEAR, we would compute the deterministic address and verify it matches
-        // For now, we'll use a simplified validation
+        if immutables.dst_chain_id != self.base.chain_id {
+            return Err(EscrowError::WrongChain);
+        }
+        if hash_immutables(immutables) != self.immutables_hash {
+            return Err(EscrowError::InvalidImmutables);
+        }
         if immutables.amount == 0 {
             return Err(EscrowError::InvalidImmutables);
         }
@@ -114,7 +389,12 @@ impl EscrowDst {
 
     // Delegate base escrow methods
     #[handle_result]
-    pub fn rescue_funds(&mut self, token: AccountId, amount: u128, immutables: Immutables) -> Result<(), EscrowError> {
+    pub fn rescue_funds(
+        &mut self,
+        token: AccountId,
+        amount: u128,
+        immutables: Immutables,
+    ) -> Result<(), EscrowError> {
         self.base.rescue_funds(token, amount, immutables);
         Ok(())
     }
@@ -128,11 +408,29 @@ impl EscrowDst {
     pub fn get_factory(&self) -> Result<AccountId, EscrowError> {
         Ok(self.base.get_factory())
     }
+
+    /// Pause `withdraw`/`public_withdraw`. Callable only by the guardian or factory.
+    #[handle_result]
+    pub fn pause(&mut self) -> Result<(), EscrowError> {
+        self.base.pause()
+    }
+
+    /// Resume `withdraw`/`public_withdraw` after a pause.
+    #[handle_result]
+    pub fn resume(&mut self) -> Result<(), EscrowError> {
+        self.base.resume()
+    }
+
+    #[handle_result]
+    pub fn get_is_paused(&self) -> Result<bool, EscrowError> {
+        Ok(self.base.get_is_paused())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::hash_secret;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
     use near_sdk::{testing_env, AccountId};
 
@@ -160,10 +458,15 @@ mod tests {
                 src_public_withdrawal: 0,
                 src_cancellation: 0,
                 src_public_cancellation: 0,
-                dst_withdrawal: 100,    // withdrawal starts at 1100
+                dst_withdrawal: 100,        // withdrawal starts at 1100
                 dst_public_withdrawal: 200, // public withdrawal starts at 1200
-                dst_cancellation: 300,  // cancellation starts at 1300
+                dst_cancellation: 300,      // cancellation starts at 1300
             },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
         }
     }
 
@@ -171,27 +474,431 @@ mod tests {
     fn test_new() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
+
         let access_token = accounts(2);
         let rescue_delay = 3600;
-        
-        let contract = EscrowDst::new(rescue_delay, access_token.clone());
-        
+
+        let contract = EscrowDst::new(
+            rescue_delay,
+            access_token.clone(),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+
         assert_eq!(contract.get_rescue_delay().unwrap(), rescue_delay);
         assert_eq!(contract.get_factory().unwrap(), accounts(1));
     }
 
+    #[test]
+    fn test_can_withdraw_matches_withdraw_outcome() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let context = get_context(immutables.taker.clone());
+        testing_env!(context.block_timestamp(1150).build());
+
+        let contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+        assert!(contract.can_withdraw(secret, immutables.clone()).is_ok());
+
+        let wrong_secret = [2u8; 32];
+        assert!(matches!(
+            contract.can_withdraw(wrong_secret, immutables),
+            Err(EscrowError::InvalidSecret)
+        ));
+    }
+
+    #[test]
+    fn test_can_withdraw_rejects_before_window_without_transferring() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let context = get_context(immutables.taker.clone());
+        testing_env!(context.block_timestamp(1000).build());
+
+        let contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+        assert!(matches!(
+            contract.can_withdraw(secret, immutables),
+            Err(EscrowError::InvalidTime)
+        ));
+    }
+
+    #[test]
+    fn test_can_cancel_matches_cancel_outcome() {
+        let immutables = create_test_immutables();
+        let context = get_context(immutables.taker.clone());
+        testing_env!(context.block_timestamp(1300).build());
+
+        let contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+        assert!(contract.can_cancel(immutables.clone()).is_ok());
+
+        let context = get_context(immutables.taker.clone());
+        testing_env!(context.block_timestamp(1200).build());
+        assert!(matches!(
+            contract.can_cancel(immutables),
+            Err(EscrowError::InvalidTime)
+        ));
+    }
+
     #[test]
     fn test_validate_immutables() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
-        let contract = EscrowDst::new(3600, accounts(2));
-        
+
+        let contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+
         let mut immutables = create_test_immutables();
         assert!(contract.validate_immutables(&immutables).is_ok());
-        
+
         immutables.amount = 0;
         assert!(contract.validate_immutables(&immutables).is_err());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_validate_immutables_rejects_wrong_chain_id() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let mut immutables = create_test_immutables();
+        immutables.dst_chain_id = 2; // mismatched deployment
+        assert!(matches!(
+            contract.validate_immutables(&immutables),
+            Err(EscrowError::WrongChain)
+        ));
+    }
+
+    #[test]
+    fn test_validate_immutables_rejects_wrong_commitment() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let mut immutables = create_test_immutables();
+        immutables.amount = 2000; // differs from what the factory committed to at deployment
+        assert!(matches!(
+            contract.validate_immutables(&immutables),
+            Err(EscrowError::InvalidImmutables)
+        ));
+    }
+
+    #[test]
+    fn test_withdraw_rejects_while_paused() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+        contract
+            .pause()
+            .expect("guardian/factory should be able to pause");
+        assert!(contract.get_is_paused().unwrap());
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let result = contract.withdraw([1u8; 32], create_test_immutables());
+        assert!(matches!(result, Err(EscrowError::Paused)));
+    }
+
+    #[test]
+    fn test_cancel_ignores_pause_once_timelock_elapsed() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+        contract
+            .pause()
+            .expect("guardian/factory should be able to pause");
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(1300).build());
+        contract.cancel(create_test_immutables()).expect(
+            "cancellation should still succeed while paused, once the timelock has elapsed",
+        );
+    }
+
+    #[test]
+    fn test_withdraw_succeeds_inside_withdrawal_window() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+
+        let context = get_context(immutables.taker.clone());
+        testing_env!(context.block_timestamp(1150).build());
+        contract
+            .withdraw(secret, immutables)
+            .expect("withdrawal should succeed inside the withdrawal window");
+    }
+
+    #[test]
+    fn test_withdraw_and_cancel_advance_the_hashchain_differently() {
+        let withdrawal_hash = EscrowDst::fold_hashchain(
+            [0u8; 32],
+            EVENT_WITHDRAWAL,
+            Some(&[1u8; 32]),
+            &accounts(1),
+            1000,
+            1150,
+        );
+        let cancellation_hash = EscrowDst::fold_hashchain(
+            [0u8; 32],
+            EVENT_CANCELLATION,
+            None,
+            &accounts(2),
+            1000,
+            1300,
+        );
+
+        assert_ne!(withdrawal_hash, cancellation_hash);
+    }
+
+    #[test]
+    fn test_withdraw_rejects_wrong_chain_id_even_with_correct_secret() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+        immutables.dst_chain_id = 2; // this escrow was deployed on chain id 1
+
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+
+        let context = get_context(immutables.taker.clone());
+        testing_env!(context.block_timestamp(1150).build());
+        // Replaying a genuinely correct secret must not be enough on its own
+        // to withdraw from an escrow deployed on a different chain.
+        let result = contract.withdraw(secret, immutables);
+        assert!(matches!(result, Err(EscrowError::WrongChain)));
+    }
+
+    /// Mock a resolved access-token `ft_balance_of` promise as if it had
+    /// returned `balance`.
+    fn set_access_token_balance_result(context: VMContextBuilder, balance: u128) {
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![near_sdk::PromiseResult::Successful(
+                near_sdk::serde_json::to_vec(&near_sdk::json_types::U128(balance)).unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_resolve_public_withdraw_accepts_non_zero_access_token_balance() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+
+        set_access_token_balance_result(get_context(accounts(9)), 1);
+        // Just dispatches the transfer and chains resolve_withdrawal onto it -
+        // see test_resolve_withdrawal_* below for the actual hashchain commit.
+        contract.resolve_public_withdraw(secret, immutables, accounts(9));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not an access token holder")]
+    fn test_resolve_public_withdraw_rejects_zero_access_token_balance() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&immutables),
+        );
+
+        set_access_token_balance_result(get_context(accounts(9)), 0);
+        contract.resolve_public_withdraw(secret, immutables, accounts(9));
+    }
+
+    /// Mock a resolved token-transfer promise as if it had `succeeded`, at the
+    /// given block timestamp.
+    fn set_transfer_result(predecessor: AccountId, now: u64, succeeded: bool) {
+        let mut context = get_context(predecessor);
+        let result = if succeeded {
+            near_sdk::PromiseResult::Successful(vec![])
+        } else {
+            near_sdk::PromiseResult::Failed
+        };
+        testing_env!(
+            context.block_timestamp(now).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn test_resolve_withdrawal_commits_hashchain_on_successful_transfer() {
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let new_hashchain = [7u8; 32];
+        set_transfer_result(accounts(4), 1150, true);
+        contract
+            .resolve_withdrawal(new_hashchain, accounts(2), 100, [1u8; 32])
+            .expect("a successful token transfer should commit the withdrawal");
+
+        assert_eq!(contract.get_hashchain(), new_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_withdrawal_leaves_hashchain_untouched_on_failed_transfer() {
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+        let initial_hashchain = contract.get_hashchain();
+
+        set_transfer_result(accounts(4), 1150, false);
+        let result = contract.resolve_withdrawal([7u8; 32], accounts(2), 100, [1u8; 32]);
+
+        assert!(matches!(
+            result,
+            Err(EscrowError::NativeTokenSendingFailure)
+        ));
+        assert_eq!(contract.get_hashchain(), initial_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_cancellation_commits_hashchain_on_successful_transfer() {
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let new_hashchain = [8u8; 32];
+        set_transfer_result(accounts(4), 1400, true);
+        contract
+            .resolve_cancellation(new_hashchain, accounts(2), 100)
+            .expect("a successful token transfer should commit the cancellation");
+
+        assert_eq!(contract.get_hashchain(), new_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_cancellation_leaves_hashchain_untouched_on_failed_transfer() {
+        let mut contract = EscrowDst::new(
+            3600,
+            accounts(2),
+            accounts(5),
+            1,
+            [0u8; 32],
+            hash_immutables(&create_test_immutables()),
+        );
+        let initial_hashchain = contract.get_hashchain();
+
+        set_transfer_result(accounts(4), 1400, false);
+        let result = contract.resolve_cancellation([8u8; 32], accounts(2), 100);
+
+        assert!(matches!(
+            result,
+            Err(EscrowError::NativeTokenSendingFailure)
+        ));
+        assert_eq!(contract.get_hashchain(), initial_hashchain);
+    }
+}
@@ -1,13 +1,15 @@
+use crate::types::{
+    DstImmutablesComplement, EscrowError, ExtraDataArgs, FactoryError, Immutables, TimelockStage,
+    ValidationData,
+};
+use crate::utils::{hash_immutables, hash_immutables_712, validate_partial_fill};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env, log, near, AccountId, Balance, Gas, Promise,
-    serde::{Deserialize, Serialize},
     collections::UnorderedMap,
+    env, log, near,
+    serde::{Deserialize, Serialize},
+    AccountId, Balance, Gas, Promise,
 };
-use crate::types::{
-    Immutables, DstImmutablesComplement, ExtraDataArgs, ValidationData, EscrowError, TimelockStage
-};
-use crate::utils::{hash_immutables, validate_partial_fill};
 
 // Gas for cross-contract calls
 const GAS_FOR_ESCROW_CREATION: Gas = Gas(50_000_000_000_000);
@@ -20,7 +22,18 @@ pub struct EscrowFactory {
     pub escrow_dst_implementation: AccountId,
     pub proxy_src_bytecode_hash: [u8; 32],
     pub proxy_dst_bytecode_hash: [u8; 32],
+    /// EIP-712 domain separator this factory hashes `Immutables` against via
+    /// `hash_immutables_712`, matching the corresponding limit-order-protocol
+    /// contracts so an order/escrow identifier is reproducible from the EVM side.
+    pub domain_separator: [u8; 32],
     pub last_validated: UnorderedMap<[u8; 32], ValidationData>,
+    pub owner: AccountId,
+    /// While `true`, escrow deployment entry points (`create_dst_escrow`,
+    /// `post_interaction`) reject new swaps, mirroring the incident-response
+    /// pause lever already on `LimitOrderProtocol`/`BaseEscrow`. Existing
+    /// escrows and their withdraw/cancel flows are unaffected - this only
+    /// gates the creation of new ones.
+    pub paused: bool,
 }
 
 impl Default for EscrowFactory {
@@ -30,7 +43,10 @@ impl Default for EscrowFactory {
             escrow_dst_implementation: AccountId::new_unchecked("".to_string()),
             proxy_src_bytecode_hash: [0u8; 32],
             proxy_dst_bytecode_hash: [0u8; 32],
+            domain_separator: [0u8; 32],
             last_validated: UnorderedMap::new(b"last_validated"),
+            owner: AccountId::new_unchecked("".to_string()),
+            paused: false,
         }
     }
 }
@@ -44,18 +60,74 @@ impl EscrowFactory {
         escrow_dst_implementation: AccountId,
         proxy_src_bytecode_hash: [u8; 32],
         proxy_dst_bytecode_hash: [u8; 32],
+        domain_separator: [u8; 32],
     ) -> Self {
         Self {
             escrow_src_implementation,
             escrow_dst_implementation,
             proxy_src_bytecode_hash,
             proxy_dst_bytecode_hash,
+            domain_separator,
             last_validated: UnorderedMap::new(b"last_validated"),
+            owner: env::predecessor_account_id(),
+            paused: false,
         }
     }
 
+    /// Pause escrow deployment (`create_dst_escrow`/`post_interaction`).
+    /// Owner-only.
+    pub fn pause(&mut self) {
+        self.only_owner();
+        self.paused = true;
+        log!("Factory paused");
+    }
+
+    /// Resume escrow deployment after a pause. Owner-only.
+    pub fn resume(&mut self) {
+        self.only_owner();
+        self.paused = false;
+        log!("Factory unpaused");
+    }
+
+    /// Check whether escrow deployment is currently paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Get the factory owner
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+
+    fn only_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can call this"
+        );
+    }
+
+    /// Get the EIP-712 domain separator this factory hashes immutables against
+    pub fn get_domain_separator(&self) -> [u8; 32] {
+        self.domain_separator
+    }
+
+    /// EIP-712 domain-separated hash of `immutables`, matching the digest the
+    /// EVM-side Fusion+ contracts compute for the same swap.
+    pub fn hash_immutables_712(&self, immutables: Immutables) -> [u8; 32] {
+        hash_immutables_712(&immutables, &self.domain_separator)
+    }
+
     /// Create destination escrow
-    pub fn create_dst_escrow(&mut self, dst_immutables: Immutables, src_cancellation_timestamp: u64) {
+    pub fn create_dst_escrow(
+        &mut self,
+        dst_immutables: Immutables,
+        src_cancellation_timestamp: u64,
+    ) {
+        if self.paused {
+            env::panic_str("Factory is paused");
+        }
+
         let token = dst_immutables.token.clone();
         let native_amount = if token.as_str() == "near" {
             dst_immutables.safety_deposit + dst_immutables.amount
@@ -70,7 +142,11 @@ impl EscrowFactory {
 
         let mut immutables = dst_immutables;
         immutables.timelocks.set_deployed_at(env::block_timestamp());
-        
+
+        if !immutables.timelocks.validate_ordering() {
+            env::panic_str("Invalid timelocks");
+        }
+
         // Check that the escrow cancellation will start not later than the cancellation time on the source chain
         let dst_cancellation_start = immutables.timelocks.get(TimelockStage::DstCancellation);
         if dst_cancellation_start > src_cancellation_timestamp {
@@ -78,19 +154,38 @@ impl EscrowFactory {
         }
 
         let salt = hash_immutables(&immutables);
-        let escrow = self.deploy_escrow(salt, env::attached_deposit(), self.escrow_dst_implementation.clone());
+        let escrow = self.deploy_escrow(
+            salt,
+            env::attached_deposit(),
+            self.escrow_dst_implementation.clone(),
+        );
 
         // Transfer tokens if not native
         if token.as_str() != "near" {
             // In a real implementation, we would transfer tokens from sender to escrow
-            log!("Transferring {} tokens from {} to {}", immutables.amount, env::predecessor_account_id(), escrow);
+            log!(
+                "Transferring {} tokens from {} to {}",
+                immutables.amount,
+                env::predecessor_account_id(),
+                escrow
+            );
         }
 
-        log!("Dst escrow created: escrow={}, hashlock={:?}, taker={}", 
-             escrow, immutables.hashlock, immutables.taker);
+        log!(
+            "Dst escrow created: escrow={}, hashlock={:?}, taker={}",
+            escrow,
+            immutables.hashlock,
+            immutables.taker
+        );
     }
 
     /// Post interaction for source escrow creation
+    ///
+    /// Requires `maker_signature` - a 65-byte secp256k1 signature (64-byte
+    /// r‖s plus a trailing recovery id) of `order_hash` - to prove the maker
+    /// actually authorized this fill, matching how signed limit orders are
+    /// authorized on the Ethereum side of Fusion+.
+    #[handle_result]
     pub fn post_interaction(
         &mut self,
         order_hash: [u8; 32],
@@ -101,12 +196,28 @@ impl EscrowFactory {
         amount: Balance,
         safety_deposit: Balance,
         timelocks: crate::types::Timelocks,
+        chain_id: u64,
         dst_token: AccountId,
         dst_chain_id: u64,
         dst_amount: Balance,
         dst_safety_deposit: Balance,
         dst_maker: AccountId,
-    ) {
+        maker_signature: Vec<u8>,
+        maker_eth_address: [u8; 20],
+        allow_multiple_fills: bool,
+        parts_amount: u64,
+        hash_algorithm: crate::types::HashAlgorithm,
+    ) -> Result<(), FactoryError> {
+        if self.paused {
+            return Err(FactoryError::ContractPaused);
+        }
+
+        Self::verify_maker_signature(order_hash, &maker_signature, maker_eth_address)?;
+
+        if !timelocks.validate_ordering() {
+            return Err(FactoryError::InvalidTimelocks);
+        }
+
         let immutables = Immutables {
             order_hash,
             hashlock,
@@ -116,6 +227,11 @@ impl EscrowFactory {
             amount,
             safety_deposit,
             timelocks,
+            src_chain_id: chain_id,
+            dst_chain_id,
+            allow_multiple_fills,
+            parts_amount,
+            hash_algorithm,
         };
 
         let dst_complement = DstImmutablesComplement {
@@ -126,7 +242,11 @@ impl EscrowFactory {
             chain_id: dst_chain_id,
         };
 
-        log!("Src escrow created: immutables={:?}, dst_complement={:?}", immutables, dst_complement);
+        log!(
+            "Src escrow created: immutables={:?}, dst_complement={:?}",
+            immutables,
+            dst_complement
+        );
 
         let salt = hash_immutables(&immutables);
         let escrow = self.deploy_escrow(salt, 0, self.escrow_src_implementation.clone());
@@ -136,9 +256,42 @@ impl EscrowFactory {
             // In a real implementation, we would check the escrow balance
             log!("Escrow balance validation would happen here");
         }
+
+        Ok(())
+    }
+
+    /// Recover the Ethereum address behind `maker_signature` over `order_hash`
+    /// and check it against `maker_eth_address`.
+    fn verify_maker_signature(
+        order_hash: [u8; 32],
+        maker_signature: &[u8],
+        maker_eth_address: [u8; 20],
+    ) -> Result<(), FactoryError> {
+        if maker_signature.len() != 65 {
+            return Err(FactoryError::InvalidSignature);
+        }
+
+        let sig = &maker_signature[0..64];
+        let v = maker_signature[64];
+        let public_key =
+            env::ecrecover(&order_hash, sig, v, false).ok_or(FactoryError::InvalidSignature)?;
+        let hashed_key = env::keccak256(&public_key);
+        let recovered_address = &hashed_key[12..32];
+
+        if recovered_address != maker_eth_address {
+            return Err(FactoryError::InvalidSignature);
+        }
+
+        Ok(())
     }
 
     /// Get address of source escrow
+    ///
+    /// CREATE2-equivalent: the sub-account id is a deterministic function of
+    /// `hash_immutables(immutables)` alone, so it can be computed off-chain
+    /// before deployment. This is the same `salt` the factory would record as
+    /// `EscrowSrc::immutables_hash` at deploy time, and what `validate_immutables`
+    /// recomputes from caller-supplied immutables on every withdraw/cancel call.
     pub fn address_of_escrow_src(&self, immutables: Immutables) -> AccountId {
         let salt = hash_immutables(&immutables);
         // In a real implementation, we would compute the deterministic address
@@ -147,6 +300,9 @@ impl EscrowFactory {
     }
 
     /// Get address of destination escrow
+    ///
+    /// CREATE2-equivalent: see [`Self::address_of_escrow_src`]. The same salt
+    /// is what the factory would record as `EscrowDst::immutables_hash`.
     pub fn address_of_escrow_dst(&self, immutables: Immutables) -> AccountId {
         let salt = hash_immutables(&immutables);
         // In a real implementation, we would compute the deterministic address
@@ -155,7 +311,17 @@ impl EscrowFactory {
     }
 
     /// Deploy escrow contract
-    fn deploy_escrow(&self, salt: [u8; 32], value: Balance, implementation: AccountId) -> AccountId {
+    ///
+    /// `salt` is `hash_immutables` of the exact `Immutables` this escrow is
+    /// being created for; a real deployment would pass it through as the new
+    /// escrow's `immutables_hash` commitment so its `validate_immutables` can
+    /// reject a resolver substituting different immutables later.
+    fn deploy_escrow(
+        &self,
+        salt: [u8; 32],
+        value: Balance,
+        implementation: AccountId,
+    ) -> AccountId {
         // In a real implementation, we would use NEAR's contract deployment mechanism
         // For now, return a deterministic address based on salt
         let mut address_bytes = [0u8; 32];
@@ -173,18 +339,19 @@ impl EscrowFactory {
         validated_index: u64,
         order_hash: [u8; 32],
         hashlock_info: [u8; 32],
+        chain_id: u64,
     ) -> Result<bool, EscrowError> {
-        let key = self.compute_validation_key(order_hash, hashlock_info);
-        
+        let key = self.compute_validation_key(order_hash, hashlock_info, chain_id);
+
         // In a real implementation, we would store and retrieve validation data
         // For now, we'll use a simplified approach
         let validation_data = ValidationData {
             leaf: hashlock_info,
             index: validated_index,
         };
-        
+
         self.last_validated.insert(&key, &validation_data);
-        
+
         validate_partial_fill(
             making_amount,
             remaining_making_amount,
@@ -195,10 +362,21 @@ impl EscrowFactory {
     }
 
     /// Compute validation key
-    fn compute_validation_key(&self, order_hash: [u8; 32], hashlock_info: [u8; 32]) -> [u8; 32] {
+    ///
+    /// Binds `chain_id` into the preimage alongside `order_hash` and
+    /// `hashlock_info` so a validated Merkle proof for an order on one
+    /// chain can't be replayed to validate a partial fill of the same
+    /// order hash on a sibling deployment.
+    fn compute_validation_key(
+        &self,
+        order_hash: [u8; 32],
+        hashlock_info: [u8; 32],
+        chain_id: u64,
+    ) -> [u8; 32] {
         let mut data = Vec::new();
         data.extend_from_slice(&order_hash);
         data.extend_from_slice(&hashlock_info);
+        data.extend_from_slice(&chain_id.to_le_bytes());
         near_sdk::hash::hash(&data).try_into().unwrap()
     }
 
@@ -239,37 +417,147 @@ mod tests {
     fn test_new() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
+
         let src_impl = accounts(2);
         let dst_impl = accounts(3);
         let src_hash = [1u8; 32];
         let dst_hash = [2u8; 32];
-        
+
+        let domain_separator = [3u8; 32];
         let contract = EscrowFactory::new(
             src_impl.clone(),
             dst_impl.clone(),
             src_hash,
             dst_hash,
+            domain_separator,
         );
-        
+
         assert_eq!(contract.get_escrow_src_implementation(), src_impl);
         assert_eq!(contract.get_escrow_dst_implementation(), dst_impl);
         assert_eq!(contract.get_proxy_src_bytecode_hash(), src_hash);
         assert_eq!(contract.get_proxy_dst_bytecode_hash(), dst_hash);
+        assert_eq!(contract.get_domain_separator(), domain_separator);
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert!(!contract.is_paused());
+    }
+
+    #[test]
+    fn test_pause_blocks_create_dst_escrow_but_not_getters() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+        contract.pause();
+        assert!(contract.is_paused());
+
+        // Read-only getters keep working while paused.
+        assert_eq!(contract.get_domain_separator(), [9u8; 32]);
+        assert_eq!(contract.get_owner(), accounts(1));
+
+        contract.resume();
+        assert!(!contract.is_paused());
+    }
+
+    #[test]
+    #[should_panic(expected = "Factory is paused")]
+    fn test_create_dst_escrow_panics_while_paused() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+        contract.pause();
+
+        let immutables = Immutables {
+            order_hash: [0u8; 32],
+            hashlock: [0u8; 32],
+            maker: accounts(2),
+            taker: accounts(3),
+            token: accounts(4),
+            amount: 100,
+            safety_deposit: 10,
+            timelocks: crate::types::Timelocks {
+                deployed_at: 0,
+                src_withdrawal: 0,
+                src_public_withdrawal: 0,
+                src_cancellation: 0,
+                src_public_cancellation: 0,
+                dst_withdrawal: 0,
+                dst_public_withdrawal: 0,
+                dst_cancellation: 0,
+            },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
+        };
+        contract.create_dst_escrow(immutables, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid timelocks")]
+    fn test_create_dst_escrow_panics_on_out_of_order_timelocks() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context
+            .attached_deposit(near_sdk::NearToken::from_yoctonear(10))
+            .build());
+
+        let mut contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+
+        let immutables = Immutables {
+            order_hash: [0u8; 32],
+            hashlock: [0u8; 32],
+            maker: accounts(2),
+            taker: accounts(3),
+            token: accounts(4),
+            amount: 100,
+            safety_deposit: 10,
+            timelocks: crate::types::Timelocks {
+                deployed_at: 0,
+                src_withdrawal: 0,
+                src_public_withdrawal: 0,
+                src_cancellation: 0,
+                src_public_cancellation: 0,
+                dst_withdrawal: 100,
+                // Dst public withdrawal opening before dst withdrawal is
+                // out of order and must be rejected.
+                dst_public_withdrawal: 50,
+                dst_cancellation: 200,
+            },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
+        };
+        contract.create_dst_escrow(immutables, u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this")]
+    fn test_pause_rejects_non_owner() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+
+        let stranger_context = get_context(accounts(6));
+        testing_env!(stranger_context.build());
+        contract.pause();
     }
 
     #[test]
     fn test_address_of_escrow() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
-        let contract = EscrowFactory::new(
-            accounts(2),
-            accounts(3),
-            [1u8; 32],
-            [2u8; 32],
-        );
-        
+
+        let contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [3u8; 32]);
+
         let immutables = Immutables {
             order_hash: [0u8; 32],
             hashlock: [0u8; 32],
@@ -288,12 +576,149 @@ mod tests {
                 dst_public_withdrawal: 0,
                 dst_cancellation: 0,
             },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
         };
-        
+
         let src_address = contract.address_of_escrow_src(immutables.clone());
         let dst_address = contract.address_of_escrow_dst(immutables);
-        
+
         assert!(!src_address.as_str().is_empty());
         assert!(!dst_address.as_str().is_empty());
     }
-} 
\ No newline at end of file
+
+    fn create_test_immutables_712() -> Immutables {
+        Immutables {
+            order_hash: [11u8; 32],
+            hashlock: [22u8; 32],
+            maker: accounts(4),
+            taker: accounts(5),
+            token: accounts(6),
+            amount: 1_000_000,
+            safety_deposit: 500,
+            timelocks: Timelocks {
+                deployed_at: 1000,
+                src_withdrawal: 10,
+                src_public_withdrawal: 20,
+                src_cancellation: 30,
+                src_public_cancellation: 40,
+                dst_withdrawal: 50,
+                dst_public_withdrawal: 60,
+                dst_cancellation: 70,
+            },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
+        }
+    }
+
+    #[test]
+    fn test_hash_immutables_712_deterministic() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+        let immutables = create_test_immutables_712();
+
+        let a = contract.hash_immutables_712(immutables.clone());
+        let b = contract.hash_immutables_712(immutables);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_hash_immutables_712_differs_from_legacy_sha256_salt() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+        let immutables = create_test_immutables_712();
+
+        let eip712 = contract.hash_immutables_712(immutables.clone());
+        let legacy = hash_immutables(&immutables);
+        assert_ne!(
+            eip712, legacy,
+            "EIP-712 digest must not collide with the legacy sha256 salt"
+        );
+    }
+
+    #[test]
+    fn test_hash_immutables_712_is_sensitive_to_domain_separator() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let immutables = create_test_immutables_712();
+        let contract_a =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+        let contract_b =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [8u8; 32]);
+
+        let digest_a = contract_a.hash_immutables_712(immutables.clone());
+        let digest_b = contract_b.hash_immutables_712(immutables);
+        assert_ne!(
+            digest_a, digest_b,
+            "changing the domain separator must change the digest"
+        );
+    }
+
+    #[test]
+    fn test_hash_immutables_712_is_sensitive_to_every_field() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract =
+            EscrowFactory::new(accounts(2), accounts(3), [1u8; 32], [2u8; 32], [9u8; 32]);
+        let base = create_test_immutables_712();
+        let base_digest = contract.hash_immutables_712(base.clone());
+
+        let mut tweaked_amount = base.clone();
+        tweaked_amount.amount += 1;
+        assert_ne!(contract.hash_immutables_712(tweaked_amount), base_digest);
+
+        let mut tweaked_safety_deposit = base.clone();
+        tweaked_safety_deposit.safety_deposit += 1;
+        assert_ne!(
+            contract.hash_immutables_712(tweaked_safety_deposit),
+            base_digest
+        );
+
+        let mut tweaked_maker = base.clone();
+        tweaked_maker.maker = accounts(7);
+        assert_ne!(contract.hash_immutables_712(tweaked_maker), base_digest);
+
+        let mut tweaked_timelocks = base.clone();
+        tweaked_timelocks.timelocks.dst_cancellation += 1;
+        assert_ne!(contract.hash_immutables_712(tweaked_timelocks), base_digest);
+    }
+
+    #[test]
+    fn test_verify_maker_signature_rejects_wrong_length() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let order_hash = [1u8; 32];
+        let maker_eth_address = [2u8; 20];
+        let result =
+            EscrowFactory::verify_maker_signature(order_hash, &[0u8; 64], maker_eth_address);
+        assert!(matches!(result, Err(FactoryError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_verify_maker_signature_rejects_unrecoverable_signature() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let order_hash = [1u8; 32];
+        let maker_eth_address = [2u8; 20];
+        // An all-zero r/s/v signature does not recover to any public key.
+        let result =
+            EscrowFactory::verify_maker_signature(order_hash, &[0u8; 65], maker_eth_address);
+        assert!(matches!(result, Err(FactoryError::InvalidSignature)));
+    }
+}
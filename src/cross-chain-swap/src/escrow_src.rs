@@ -1,23 +1,91 @@
+use super::base_escrow::BaseEscrow;
+use crate::io::{ext_ft, CallbackIo, EscrowIo, NearRuntimeIo};
+use crate::types::{EscrowError, Immutables, TimelockStage};
+use crate::utils::{hash_immutables, hash_partial_fill_leaf, verify_partial_fill_proof};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env, log, near, AccountId, Balance,
+    env, log, near,
     serde::{Deserialize, Serialize},
+    AccountId, Balance, Gas, Promise, PromiseResult,
 };
-use crate::types::{Immutables, EscrowError, TimelockStage};
-use crate::utils::{validate_after, validate_before, validate_caller};
-use super::base_escrow::BaseEscrow;
+
+// Gas for the `#[private]` callbacks that resolve an access-token balance
+// query kicked off by the public withdraw/cancel paths and perform the
+// underlying action.
+const GAS_FOR_ACCESS_TOKEN_CALLBACK: Gas = Gas::from_tgas(20);
+
+// Gas for the cross-contract token transfer fired by a withdrawal/cancellation.
+const GAS_FOR_TOKEN_TRANSFER: Gas = Gas::from_tgas(10);
+// Gas for the `#[private]` callback that resolves that transfer and, only on
+// success, commits the hashchain and pays the safety deposit.
+const GAS_FOR_TRANSFER_CALLBACK: Gas = Gas::from_tgas(20);
+
+/// Tag folded into the hashchain preimage to distinguish a private withdrawal event.
+const EVENT_WITHDRAWAL: u8 = 1;
+/// Tag folded into the hashchain preimage to distinguish a public withdrawal event.
+const EVENT_PUBLIC_WITHDRAWAL: u8 = 2;
+/// Tag folded into the hashchain preimage to distinguish a private cancellation event.
+const EVENT_CANCELLATION: u8 = 3;
+/// Tag folded into the hashchain preimage to distinguish a public cancellation event.
+const EVENT_PUBLIC_CANCELLATION: u8 = 4;
 
 /// Source Escrow contract for cross-chain atomic swap
 #[near(contract_state)]
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct EscrowSrc {
     pub base: BaseEscrow,
+    /// Chain identifier this escrow was deployed on, bound into `hash_immutables`
+    /// and the Merkle validation key so a secret/proof can't be replayed against
+    /// a sibling escrow on another deployment (EIP-155-style replay protection).
+    pub chain_id: u64,
+    /// Append-only hashchain over every successful withdrawal/cancellation on this
+    /// escrow, letting a resolver or relayer verify off-chain that events were
+    /// observed in the order they actually happened. Genesis is
+    /// `hash(order_hash_seed)` as set at construction (32 zero bytes if no seed
+    /// was known yet), so a verifier can recompute the whole chain from block one
+    /// given just that seed and the sequence of emitted events.
+    pub hashchain: [u8; 32],
+    /// Commitment the factory recorded at deployment: `hash_immutables` of the
+    /// exact `Immutables` this escrow was created for. Every withdraw/cancel
+    /// path recomputes the hash of the caller-supplied immutables and checks
+    /// it against this value in `validate_immutables`, so a resolver can't
+    /// substitute a different (e.g. higher-amount) immutables set than what
+    /// the factory actually committed to at CREATE2-equivalent deploy time.
+    pub immutables_hash: [u8; 32],
+    /// Cumulative amount released across all partial-fill withdrawals so far.
+    /// Only advanced by `withdraw_partial`/`public_withdraw_partial`; the
+    /// single-secret `withdraw`/`withdraw_to` path releases the whole
+    /// `immutables.amount` in one shot and never touches this.
+    pub filled_amount: Balance,
+    /// Highest secret index consumed by a partial-fill withdrawal so far. `0`
+    /// means none has been consumed yet, since index `0` in the Merkle tree
+    /// of secrets is never itself a valid withdrawal index (see
+    /// `execute_partial_withdrawal`).
+    pub last_consumed_index: u64,
+    /// Set while a `withdraw_partial`/`public_withdraw_partial` call has
+    /// dispatched its transfer but `resolve_partial_withdrawal` hasn't
+    /// confirmed it yet. `execute_partial_withdrawal` only validates against
+    /// the `filled_amount`/`last_consumed_index` snapshot taken when it's
+    /// called, not whatever a concurrent in-flight call will eventually
+    /// commit, so without this guard two overlapping partial withdrawals can
+    /// both validate against the same stale state, both fire real transfers,
+    /// and whichever callback lands last silently overwrites the other's
+    /// increment - understating `filled_amount` while the escrow has already
+    /// paid out more. Cleared by `resolve_partial_withdrawal` once the
+    /// transfer it guards resolves, success or failure.
+    pub withdrawal_in_flight: bool,
 }
 
 impl Default for EscrowSrc {
     fn default() -> Self {
         Self {
             base: BaseEscrow::default(),
+            chain_id: 0,
+            hashchain: [0u8; 32],
+            immutables_hash: [0u8; 32],
+            filled_amount: 0,
+            last_consumed_index: 0,
+            withdrawal_in_flight: false,
         }
     }
 }
@@ -26,121 +94,997 @@ impl Default for EscrowSrc {
 impl EscrowSrc {
     /// Initialize the contract
     #[init]
-    pub fn new(rescue_delay: u64, access_token: AccountId) -> Self {
+    pub fn new(
+        rescue_delay: u64,
+        access_token: AccountId,
+        chain_id: u64,
+        order_hash_seed: [u8; 32],
+        guardian: AccountId,
+        immutables_hash: [u8; 32],
+    ) -> Self {
         Self {
-            base: BaseEscrow::new(rescue_delay, access_token),
+            base: BaseEscrow::new(rescue_delay, access_token, guardian, chain_id),
+            chain_id,
+            hashchain: near_sdk::hash::hash(&order_hash_seed).try_into().unwrap(),
+            immutables_hash,
+            filled_amount: 0,
+            last_consumed_index: 0,
+            withdrawal_in_flight: false,
+        }
+    }
+
+    /// Get the chain id this escrow is bound to
+    pub fn get_chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Get the `hash_immutables` commitment the factory recorded at deployment
+    pub fn get_immutables_hash(&self) -> [u8; 32] {
+        self.immutables_hash
+    }
+
+    /// Get the current tip of the event hashchain
+    pub fn get_hashchain(&self) -> [u8; 32] {
+        self.hashchain
+    }
+
+    /// Simulate `withdraw`/`withdraw_to`: run the same
+    /// caller/timelock/secret/immutables checks without transferring
+    /// anything, so a relayer/resolver can check off-chain whether a
+    /// withdrawal would succeed before spending gas on one that would panic.
+    #[handle_result]
+    pub fn can_withdraw(
+        &self,
+        secret: [u8; 32],
+        immutables: Immutables,
+    ) -> Result<(), EscrowError> {
+        let mut io = NearRuntimeIo::default();
+        Self::validate_withdrawal(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            &mut io,
+            &secret,
+            &immutables,
+        )
+    }
+
+    /// Simulate `public_withdraw`: see `can_withdraw`.
+    #[handle_result]
+    pub fn can_public_withdraw(
+        &self,
+        secret: [u8; 32],
+        immutables: Immutables,
+    ) -> Result<(), EscrowError> {
+        let mut io = NearRuntimeIo::default();
+        Self::validate_public_withdrawal(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            &mut io,
+            &secret,
+            &immutables,
+        )
+    }
+
+    /// Simulate `cancel`: run the same caller/timelock/immutables checks
+    /// without transferring anything.
+    #[handle_result]
+    pub fn can_cancel(&self, immutables: Immutables) -> Result<(), EscrowError> {
+        let mut io = NearRuntimeIo::default();
+        Self::validate_cancellation(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            &mut io,
+            &immutables,
+            true,
+        )
+    }
+
+    /// Simulate `public_cancel`: see `can_cancel`.
+    #[handle_result]
+    pub fn can_public_cancel(&self, immutables: Immutables) -> Result<(), EscrowError> {
+        let mut io = NearRuntimeIo::default();
+        Self::validate_cancellation(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            &mut io,
+            &immutables,
+            false,
+        )
+    }
+
+    /// Withdraw funds with secret. Only taker can withdraw during withdrawal
+    /// period. The token transfer and the hashchain/safety-deposit it implies
+    /// are no longer committed in the same call: the transfer is fired here,
+    /// and only `resolve_withdrawal` - chained via `.then()` - commits the new
+    /// hashchain and pays the safety deposit, and only once that transfer has
+    /// actually confirmed. A failing transfer therefore leaves the escrow
+    /// withdrawable again instead of silently paying the safety deposit for
+    /// funds that never moved.
+    pub fn withdraw(&mut self, secret: [u8; 32], immutables: Immutables) -> Promise {
+        let target = immutables.taker.clone();
+        let mut io = NearRuntimeIo::default();
+        let new_hashchain = Self::execute_withdrawal(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            self.hashchain,
+            &mut io,
+            &secret,
+            target.clone(),
+            &immutables,
+        )
+        .expect("Withdrawal failed");
+
+        let caller = env::predecessor_account_id();
+        Self::dispatch_transfer(&immutables.token, &target, immutables.amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .resolve_withdrawal(new_hashchain, caller, immutables.safety_deposit, secret),
+        )
+    }
+
+    /// Withdraw funds to specific target. See `withdraw`'s doc comment for
+    /// why the safety deposit isn't paid until `resolve_withdrawal` confirms
+    /// the transfer.
+    pub fn withdraw_to(
+        &mut self,
+        secret: [u8; 32],
+        target: AccountId,
+        immutables: Immutables,
+    ) -> Promise {
+        let mut io = NearRuntimeIo::default();
+        let new_hashchain = Self::execute_withdrawal(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            self.hashchain,
+            &mut io,
+            &secret,
+            target.clone(),
+            &immutables,
+        )
+        .expect("Withdrawal failed");
+
+        let caller = env::predecessor_account_id();
+        Self::dispatch_transfer(&immutables.token, &target, immutables.amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .resolve_withdrawal(new_hashchain, caller, immutables.safety_deposit, secret),
+        )
+    }
+
+    /// `#[private]` callback chained after the token transfer `withdraw`/
+    /// `withdraw_to` fire: only on success does it commit the new hashchain
+    /// and pay out the safety deposit, so a failing transfer leaves the
+    /// escrow's withdrawable state untouched rather than rewarding the caller
+    /// for a transfer that never landed.
+    #[private]
+    #[handle_result]
+    pub fn resolve_withdrawal(
+        &mut self,
+        new_hashchain: [u8; 32],
+        caller: AccountId,
+        safety_deposit: Balance,
+        secret: [u8; 32],
+    ) -> Result<(), EscrowError> {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                self.hashchain = new_hashchain;
+                Promise::new(caller).transfer(safety_deposit);
+                log!(
+                    "Escrow withdrawal: secret={:?}, hashchain={:?}",
+                    secret,
+                    self.hashchain
+                );
+                Ok(())
+            }
+            _ => {
+                log!("Escrow withdrawal failed: token transfer did not succeed");
+                Err(EscrowError::NativeTokenSendingFailure)
+            }
+        }
+    }
+
+    /// Public withdrawal - anyone holding a non-zero balance of the access
+    /// token can withdraw. Kicks off an async `ft_balance_of` query against
+    /// the access token and only performs the withdrawal in
+    /// `resolve_public_withdraw` once that resolves with a non-zero balance.
+    pub fn public_withdraw(&mut self, secret: [u8; 32], immutables: Immutables) -> Promise {
+        let mut io = NearRuntimeIo::default();
+        Self::validate_public_withdrawal(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            &mut io,
+            &secret,
+            &immutables,
+        )
+        .expect("Public withdrawal failed");
+
+        let caller = env::predecessor_account_id();
+        self.base.check_access_token().then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ACCESS_TOKEN_CALLBACK)
+                .resolve_public_withdraw(secret, immutables, caller),
+        )
+    }
+
+    /// `#[private]` callback for `public_withdraw`: resolves the access-token
+    /// balance query kicked off there and, only if it returned a non-zero
+    /// balance, performs the withdrawal. `caller` is the account that called
+    /// `public_withdraw` - it can't be read off `env::predecessor_account_id()`
+    /// here, since the predecessor of a `.then()` callback is this contract
+    /// itself.
+    #[private]
+    pub fn resolve_public_withdraw(
+        &mut self,
+        secret: [u8; 32],
+        immutables: Immutables,
+        caller: AccountId,
+    ) {
+        self.base
+            .resolve_access_token()
+            .expect("Not an access token holder");
+
+        let taker = immutables.taker.clone();
+        let mut io = CallbackIo::new(caller);
+        self.hashchain = Self::execute_public_withdrawal(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            self.hashchain,
+            &mut io,
+            &secret,
+            taker,
+            &immutables,
+        )
+        .expect("Public withdrawal failed");
+        log!(
+            "Public escrow withdrawal: secret={:?}, hashchain={:?}",
+            secret,
+            self.hashchain
+        );
+    }
+
+    /// Withdraw a slice of a partial-fill order, authorized by one secret from
+    /// its Merkle tree of secrets (1inch Fusion+ style) rather than the single
+    /// secret `withdraw` expects. Only usable when
+    /// `immutables.allow_multiple_fills` is set; single-fill orders keep using
+    /// the cheap `withdraw`/`withdraw_to` path above. Like `withdraw`, the
+    /// fill state isn't committed until `resolve_partial_withdrawal` confirms
+    /// the transfer actually landed - otherwise a failed transfer would still
+    /// permanently consume the Merkle index. Rejects a call while a previous
+    /// partial withdrawal is still awaiting its callback (see
+    /// `withdrawal_in_flight`), since `filled_amount`/`last_consumed_index`
+    /// are validated against a snapshot taken here and a second overlapping
+    /// call would validate against that same stale snapshot.
+    pub fn withdraw_partial(
+        &mut self,
+        index: u64,
+        secret: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        fill_amount: Balance,
+        immutables: Immutables,
+    ) -> Promise {
+        if self.withdrawal_in_flight {
+            env::panic_str("WithdrawalInFlight");
         }
+
+        let target = immutables.taker.clone();
+        let mut io = NearRuntimeIo::default();
+        let (new_hashchain, new_filled_amount, new_last_consumed_index) =
+            Self::execute_partial_withdrawal(
+                &self.base,
+                self.chain_id,
+                self.immutables_hash,
+                self.hashchain,
+                self.filled_amount,
+                self.last_consumed_index,
+                &mut io,
+                index,
+                &secret,
+                &proof,
+                fill_amount,
+                target.clone(),
+                &immutables,
+            )
+            .expect("Partial withdrawal failed");
+        self.withdrawal_in_flight = true;
+
+        let caller = env::predecessor_account_id();
+        Self::dispatch_transfer(&immutables.token, &target, fill_amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .resolve_partial_withdrawal(
+                    new_hashchain,
+                    new_filled_amount,
+                    new_last_consumed_index,
+                    caller,
+                    immutables.safety_deposit,
+                    immutables.amount,
+                    index,
+                    secret,
+                    fill_amount,
+                ),
+        )
     }
 
-    /// Withdraw funds with secret
-    /// Only taker can withdraw during withdrawal period
-    pub fn withdraw(&mut self, secret: [u8; 32], immutables: Immutables) {
-        // Validate caller is taker
-        validate_caller(&immutables.taker).expect("Invalid caller");
-        
-        // Validate withdrawal time
+    /// Public partial withdrawal - anyone holding a non-zero balance of the
+    /// access token can trigger it once the public withdrawal window opens.
+    /// Kicks off an async `ft_balance_of` query against the access token and
+    /// only performs the withdrawal in `resolve_public_withdraw_partial` once
+    /// that resolves with a non-zero balance. The paused check is the only
+    /// one cheap enough to do synchronously here; the rest (timelock,
+    /// immutables, partial-fill-step) is checked in the callback, where a
+    /// failure surfaces as a failed receipt rather than a rejected call.
+    /// Also rejects early, like `validate_not_paused`, if `withdrawal_in_flight`
+    /// is already set - the authoritative check happens again in
+    /// `resolve_public_withdraw_partial` right before the transfer fires,
+    /// this one just avoids wasting gas on the access-token round trip first.
+    pub fn public_withdraw_partial(
+        &mut self,
+        index: u64,
+        secret: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        fill_amount: Balance,
+        immutables: Immutables,
+    ) -> Promise {
+        self.base
+            .validate_not_paused()
+            .expect("Public partial withdrawal failed");
+        if self.withdrawal_in_flight {
+            env::panic_str("WithdrawalInFlight");
+        }
+
+        let caller = env::predecessor_account_id();
+        self.base.check_access_token().then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ACCESS_TOKEN_CALLBACK)
+                .resolve_public_withdraw_partial(
+                    index,
+                    secret,
+                    proof,
+                    fill_amount,
+                    immutables,
+                    caller,
+                ),
+        )
+    }
+
+    /// `#[private]` callback for `public_withdraw_partial`: see
+    /// `resolve_public_withdraw`. Once the access-token check and the
+    /// partial-fill state machine both clear, fires the actual transfer and
+    /// chains it to `resolve_partial_withdrawal`, exactly like
+    /// `withdraw_partial` does for the taker path - see that method's doc
+    /// comment for why the fill state waits on the transfer confirming.
+    #[private]
+    pub fn resolve_public_withdraw_partial(
+        &mut self,
+        index: u64,
+        secret: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        fill_amount: Balance,
+        immutables: Immutables,
+        caller: AccountId,
+    ) -> Promise {
+        self.base
+            .resolve_access_token()
+            .expect("Not an access token holder");
+        if self.withdrawal_in_flight {
+            env::panic_str("WithdrawalInFlight");
+        }
+
+        let taker = immutables.taker.clone();
+        let mut io = CallbackIo::new(caller.clone());
+        let (new_hashchain, new_filled_amount, new_last_consumed_index) =
+            Self::execute_partial_public_withdrawal(
+                &self.base,
+                self.chain_id,
+                self.immutables_hash,
+                self.hashchain,
+                self.filled_amount,
+                self.last_consumed_index,
+                &mut io,
+                index,
+                &secret,
+                &proof,
+                fill_amount,
+                taker.clone(),
+                &immutables,
+            )
+            .expect("Public partial withdrawal failed");
+        self.withdrawal_in_flight = true;
+
+        Self::dispatch_transfer(&immutables.token, &taker, fill_amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .resolve_partial_withdrawal(
+                    new_hashchain,
+                    new_filled_amount,
+                    new_last_consumed_index,
+                    caller,
+                    immutables.safety_deposit,
+                    immutables.amount,
+                    index,
+                    secret,
+                    fill_amount,
+                ),
+        )
+    }
+
+    /// `#[private]` callback chained after the token transfer `withdraw_partial`/
+    /// `resolve_public_withdraw_partial` fire: only on success does it commit
+    /// the new hashchain and fill state and, once the fill reaches
+    /// `total_amount`, pay out the safety deposit. See `resolve_withdrawal`'s
+    /// doc comment for why this has to wait on the transfer rather than
+    /// committing synchronously. Always clears `withdrawal_in_flight` first,
+    /// success or failure, so the next partial withdrawal call can proceed.
+    #[private]
+    #[handle_result]
+    pub fn resolve_partial_withdrawal(
+        &mut self,
+        new_hashchain: [u8; 32],
+        new_filled_amount: Balance,
+        new_last_consumed_index: u64,
+        caller: AccountId,
+        safety_deposit: Balance,
+        total_amount: Balance,
+        index: u64,
+        secret: [u8; 32],
+        fill_amount: Balance,
+    ) -> Result<(), EscrowError> {
+        self.withdrawal_in_flight = false;
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                self.hashchain = new_hashchain;
+                self.filled_amount = new_filled_amount;
+                self.last_consumed_index = new_last_consumed_index;
+                if new_filled_amount == total_amount {
+                    Promise::new(caller).transfer(safety_deposit);
+                }
+                log!(
+                    "Escrow partial withdrawal: index={}, fill_amount={}, hashchain={:?}",
+                    index,
+                    fill_amount,
+                    self.hashchain
+                );
+                Ok(())
+            }
+            _ => {
+                log!("Escrow partial withdrawal failed: token transfer did not succeed");
+                Err(EscrowError::NativeTokenSendingFailure)
+            }
+        }
+    }
+
+    /// Get the cumulative amount released so far via partial-fill withdrawals.
+    pub fn get_filled_amount(&self) -> Balance {
+        self.filled_amount
+    }
+
+    /// Get the highest secret index consumed so far via partial-fill withdrawals.
+    pub fn get_last_consumed_index(&self) -> u64 {
+        self.last_consumed_index
+    }
+
+    /// Cancel escrow - only taker can cancel during cancellation period. See
+    /// `withdraw`'s doc comment: the maker's refund is fired here, but the
+    /// hashchain and safety deposit aren't committed until `resolve_cancellation`
+    /// confirms it landed.
+    pub fn cancel(&mut self, immutables: Immutables) -> Promise {
+        let mut io = NearRuntimeIo::default();
+        let new_hashchain = Self::execute_cancellation(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            self.hashchain,
+            &mut io,
+            &immutables,
+            true,
+        )
+        .expect("Cancellation failed");
+
+        let caller = env::predecessor_account_id();
+        Self::dispatch_transfer(&immutables.token, &immutables.maker, immutables.amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .resolve_cancellation(new_hashchain, caller, immutables.safety_deposit),
+        )
+    }
+
+    /// Public cancellation - anyone holding a non-zero balance of the access
+    /// token can cancel. Kicks off an async `ft_balance_of` query against the
+    /// access token and only performs the cancellation in
+    /// `resolve_public_cancel` once that resolves with a non-zero balance.
+    pub fn public_cancel(&mut self, immutables: Immutables) -> Promise {
+        let caller = env::predecessor_account_id();
+        self.base.check_access_token().then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ACCESS_TOKEN_CALLBACK)
+                .resolve_public_cancel(immutables, caller),
+        )
+    }
+
+    /// `#[private]` callback for `public_cancel`: see `resolve_public_withdraw`.
+    /// Dispatches the maker's refund and chains `resolve_cancellation` onto it
+    /// rather than committing the cancellation itself - see `cancel`'s doc
+    /// comment.
+    #[private]
+    pub fn resolve_public_cancel(&mut self, immutables: Immutables, caller: AccountId) -> Promise {
+        self.base
+            .resolve_access_token()
+            .expect("Not an access token holder");
+
+        let mut io = CallbackIo::new(caller.clone());
+        let new_hashchain = Self::execute_cancellation(
+            &self.base,
+            self.chain_id,
+            self.immutables_hash,
+            self.hashchain,
+            &mut io,
+            &immutables,
+            false,
+        )
+        .expect("Public cancellation failed");
+
+        Self::dispatch_transfer(&immutables.token, &immutables.maker, immutables.amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .resolve_cancellation(new_hashchain, caller, immutables.safety_deposit),
+        )
+    }
+
+    /// `#[private]` callback chained after the token transfer `cancel`/
+    /// `resolve_public_cancel` fire: only on success does it commit the new
+    /// hashchain and pay out the safety deposit. See `resolve_withdrawal`.
+    #[private]
+    #[handle_result]
+    pub fn resolve_cancellation(
+        &mut self,
+        new_hashchain: [u8; 32],
+        caller: AccountId,
+        safety_deposit: Balance,
+    ) -> Result<(), EscrowError> {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                self.hashchain = new_hashchain;
+                Promise::new(caller).transfer(safety_deposit);
+                log!("Escrow cancelled: hashchain={:?}", self.hashchain);
+                Ok(())
+            }
+            _ => {
+                log!("Escrow cancellation failed: token transfer did not succeed");
+                Err(EscrowError::NativeTokenSendingFailure)
+            }
+        }
+    }
+
+    /// Fire the token transfer (or native NEAR, when `token == "near"`) that a
+    /// withdrawal/cancellation pays out, returning the `Promise` so the caller
+    /// can `.then()` the safety-deposit payout onto its actual success rather
+    /// than assuming it landed.
+    fn dispatch_transfer(token: &AccountId, to: &AccountId, amount: u128) -> Promise {
+        if token.as_str() == "near" {
+            Promise::new(to.clone()).transfer(amount)
+        } else {
+            ext_ft::ext(token.clone())
+                .with_attached_deposit(1)
+                .with_gas(GAS_FOR_TOKEN_TRANSFER)
+                .ft_transfer(to.clone(), amount, None)
+        }
+    }
+
+    /// Fold an event into the hashchain and return the new tip; it does not
+    /// store it. The withdraw, partial-withdraw and cancel paths store the tip
+    /// in their callbacks only once the transfer has confirmed;
+    /// `resolve_public_withdraw` stores it when it fires the transfer.
+    fn fold_hashchain(
+        prev: [u8; 32],
+        event_tag: u8,
+        secret: Option<&[u8; 32]>,
+        target: &AccountId,
+        amount: Balance,
+        timestamp: u64,
+    ) -> [u8; 32] {
+        let mut data = Vec::new();
+        data.extend_from_slice(&prev);
+        data.push(event_tag);
+        if let Some(secret) = secret {
+            data.extend_from_slice(secret);
+        }
+        data.extend_from_slice(target.as_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        near_sdk::hash::hash(&data).try_into().unwrap()
+    }
+
+    /// Shared caller/timelock/secret/immutables checks for the private
+    /// `withdraw`/`withdraw_to` path, factored out so both `execute_withdrawal`
+    /// and the read-only `can_withdraw` view run exactly the same validation.
+    fn validate_withdrawal(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        io: &mut impl EscrowIo,
+        secret: &[u8; 32],
+        immutables: &Immutables,
+    ) -> Result<(), EscrowError> {
+        base.validate_not_paused()?;
+
+        if io.predecessor() != immutables.taker {
+            return Err(EscrowError::InvalidCaller);
+        }
+
         let withdrawal_start = immutables.timelocks.get(TimelockStage::SrcWithdrawal);
         let cancellation_start = immutables.timelocks.get(TimelockStage::SrcCancellation);
-        
-        validate_after(withdrawal_start).expect("Withdrawal not started");
-        validate_before(cancellation_start).expect("Withdrawal period ended");
-        
-        // Validate secret and immutables
-        self.base.validate_secret(&secret, &immutables).expect("Invalid secret");
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to taker
-        self.withdraw_to(secret, immutables.taker.clone(), immutables);
-    }
-
-    /// Withdraw funds to specific target
-    pub fn withdraw_to(&mut self, secret: [u8; 32], target: AccountId, immutables: Immutables) {
-        // Validate caller is taker
-        validate_caller(&immutables.taker).expect("Invalid caller");
-        
-        // Validate withdrawal time
+        let now = io.now();
+        if now < withdrawal_start {
+            return Err(EscrowError::InvalidTime);
+        }
+        if now >= cancellation_start {
+            return Err(EscrowError::InvalidTime);
+        }
+
+        base.validate_secret(secret, immutables)?;
+        Self::validate_immutables_pure(chain_id, expected_hash, immutables)
+    }
+
+    /// IO-parameterized withdraw/withdraw_to state machine, unit-testable with a mock `EscrowIo`.
+    /// Returns the hashchain tip that applies once the token transfer it implies
+    /// actually lands. Deliberately does *not* perform any transfer itself - see
+    /// `withdraw`'s doc comment for why that's now the caller's job.
+    fn execute_withdrawal(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        hashchain: [u8; 32],
+        io: &mut impl EscrowIo,
+        secret: &[u8; 32],
+        target: AccountId,
+        immutables: &Immutables,
+    ) -> Result<[u8; 32], EscrowError> {
+        Self::validate_withdrawal(base, chain_id, expected_hash, io, secret, immutables)?;
+
+        let now = io.now();
+        Ok(Self::fold_hashchain(
+            hashchain,
+            EVENT_WITHDRAWAL,
+            Some(secret),
+            &target,
+            immutables.amount,
+            now,
+        ))
+    }
+
+    /// Shared timelock/secret/immutables checks for the access-token-gated
+    /// `public_withdraw` path, factored out so both `execute_public_withdrawal`
+    /// and the read-only `can_public_withdraw` view run exactly the same
+    /// validation. Does *not* check the access token - that's a real
+    /// cross-contract query now, performed asynchronously by `public_withdraw`
+    /// itself, so a synchronous view can only simulate this half of the gate.
+    fn validate_public_withdrawal(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        io: &mut impl EscrowIo,
+        secret: &[u8; 32],
+        immutables: &Immutables,
+    ) -> Result<(), EscrowError> {
+        base.validate_not_paused()?;
+
+        let public_withdrawal_start = immutables.timelocks.get(TimelockStage::SrcPublicWithdrawal);
+        let cancellation_start = immutables.timelocks.get(TimelockStage::SrcCancellation);
+        let now = io.now();
+        if now < public_withdrawal_start {
+            return Err(EscrowError::InvalidTime);
+        }
+        if now >= cancellation_start {
+            return Err(EscrowError::InvalidTime);
+        }
+
+        base.validate_secret(secret, immutables)?;
+        Self::validate_immutables_pure(chain_id, expected_hash, immutables)
+    }
+
+    /// IO-parameterized public_withdraw state machine.
+    /// Returns the hashchain tip advanced past this withdrawal.
+    fn execute_public_withdrawal(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        hashchain: [u8; 32],
+        io: &mut impl EscrowIo,
+        secret: &[u8; 32],
+        taker: AccountId,
+        immutables: &Immutables,
+    ) -> Result<[u8; 32], EscrowError> {
+        Self::validate_public_withdrawal(base, chain_id, expected_hash, io, secret, immutables)?;
+
+        let now = io.now();
+        let caller = io.predecessor();
+        io.ft_transfer(&immutables.token, &taker, immutables.amount);
+        io.native_transfer(&caller, immutables.safety_deposit);
+        Ok(Self::fold_hashchain(
+            hashchain,
+            EVENT_PUBLIC_WITHDRAWAL,
+            Some(secret),
+            &taker,
+            immutables.amount,
+            now,
+        ))
+    }
+
+    /// IO-parameterized `withdraw_partial` state machine. `index`/`secret`/`proof`
+    /// authorize releasing `fill_amount` on top of `filled_amount` already
+    /// released; `last_consumed_index` is the highest index consumed so far.
+    /// Returns the hashchain tip, cumulative filled amount, and highest
+    /// consumed index, all advanced past this withdrawal. Pure - like
+    /// `execute_withdrawal`, it does not itself transfer anything; the caller
+    /// fires the actual transfer via `dispatch_transfer(...).then(...)` and
+    /// only commits these values once that transfer confirms.
+    fn execute_partial_withdrawal(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        hashchain: [u8; 32],
+        filled_amount: Balance,
+        last_consumed_index: u64,
+        io: &mut impl EscrowIo,
+        index: u64,
+        secret: &[u8; 32],
+        proof: &[[u8; 32]],
+        fill_amount: Balance,
+        target: AccountId,
+        immutables: &Immutables,
+    ) -> Result<([u8; 32], Balance, u64), EscrowError> {
+        base.validate_not_paused()?;
+
+        if io.predecessor() != immutables.taker {
+            return Err(EscrowError::InvalidCaller);
+        }
+
         let withdrawal_start = immutables.timelocks.get(TimelockStage::SrcWithdrawal);
         let cancellation_start = immutables.timelocks.get(TimelockStage::SrcCancellation);
-        
-        validate_after(withdrawal_start).expect("Withdrawal not started");
-        validate_before(cancellation_start).expect("Withdrawal period ended");
-        
-        // Validate secret and immutables
-        self.base.validate_secret(&secret, &immutables).expect("Invalid secret");
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens
-        self.base.uni_transfer(&immutables.token, &target, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Escrow withdrawal: secret={:?}", secret);
-    }
-
-    /// Public withdrawal - anyone with access token can withdraw
-    pub fn public_withdraw(&mut self, secret: [u8; 32], immutables: Immutables) {
-        // Validate caller has access token
-        self.base.validate_access_token().expect("No access token");
-        
-        // Validate public withdrawal time
+        let now = io.now();
+        if now < withdrawal_start {
+            return Err(EscrowError::InvalidTime);
+        }
+        if now >= cancellation_start {
+            return Err(EscrowError::InvalidTime);
+        }
+
+        Self::validate_immutables_pure(chain_id, expected_hash, immutables)?;
+        let new_filled_amount = Self::validate_partial_fill_step(
+            filled_amount,
+            last_consumed_index,
+            index,
+            secret,
+            proof,
+            fill_amount,
+            immutables,
+        )?;
+
+        let new_hashchain = Self::fold_hashchain(
+            hashchain,
+            EVENT_WITHDRAWAL,
+            Some(secret),
+            &target,
+            fill_amount,
+            now,
+        );
+        Ok((new_hashchain, new_filled_amount, index))
+    }
+
+    /// IO-parameterized `public_withdraw_partial` state machine, mirroring
+    /// `execute_partial_withdrawal` but gated on the access token instead of
+    /// the taker identity, like `execute_public_withdrawal` above it. The
+    /// access token itself is checked asynchronously by
+    /// `public_withdraw_partial` before this ever runs. Pure, like
+    /// `execute_partial_withdrawal` - the caller fires the actual transfer
+    /// and only commits these values once it confirms.
+    fn execute_partial_public_withdrawal(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        hashchain: [u8; 32],
+        filled_amount: Balance,
+        last_consumed_index: u64,
+        io: &mut impl EscrowIo,
+        index: u64,
+        secret: &[u8; 32],
+        proof: &[[u8; 32]],
+        fill_amount: Balance,
+        taker: AccountId,
+        immutables: &Immutables,
+    ) -> Result<([u8; 32], Balance, u64), EscrowError> {
+        base.validate_not_paused()?;
+
         let public_withdrawal_start = immutables.timelocks.get(TimelockStage::SrcPublicWithdrawal);
         let cancellation_start = immutables.timelocks.get(TimelockStage::SrcCancellation);
-        
-        validate_after(public_withdrawal_start).expect("Public withdrawal not started");
-        validate_before(cancellation_start).expect("Public withdrawal period ended");
-        
-        // Validate secret and immutables
-        self.base.validate_secret(&secret, &immutables).expect("Invalid secret");
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to taker
-        self.base.uni_transfer(&immutables.token, &immutables.taker, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Public escrow withdrawal: secret={:?}", secret);
-    }
-
-    /// Cancel escrow - only taker can cancel during cancellation period
-    pub fn cancel(&mut self, immutables: Immutables) {
-        // Validate caller is taker
-        validate_caller(&immutables.taker).expect("Invalid caller");
-        
-        // Validate cancellation time
-        let cancellation_start = immutables.timelocks.get(TimelockStage::SrcCancellation);
-        validate_after(cancellation_start).expect("Cancellation not started");
-        
-        // Validate immutables
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to maker
-        self.base.uni_transfer(&immutables.token, &immutables.maker, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Escrow cancelled");
-    }
-
-    /// Public cancellation - anyone with access token can cancel
-    pub fn public_cancel(&mut self, immutables: Immutables) {
-        // Validate caller has access token
-        self.base.validate_access_token().expect("No access token");
-        
-        // Validate public cancellation time
-        let public_cancellation_start = immutables.timelocks.get(TimelockStage::SrcPublicCancellation);
-        validate_after(public_cancellation_start).expect("Public cancellation not started");
-        
-        // Validate immutables
-        self.validate_immutables(&immutables).expect("Invalid immutables");
-        
-        // Transfer tokens to maker
-        self.base.uni_transfer(&immutables.token, &immutables.maker, immutables.amount);
-        self.base.near_transfer(&env::predecessor_account_id(), immutables.safety_deposit);
-        
-        log!("Public escrow cancelled");
-    }
-
-    /// Validate immutables - verify computed escrow address matches this contract
+        let now = io.now();
+        if now < public_withdrawal_start {
+            return Err(EscrowError::InvalidTime);
+        }
+        if now >= cancellation_start {
+            return Err(EscrowError::InvalidTime);
+        }
+
+        Self::validate_immutables_pure(chain_id, expected_hash, immutables)?;
+        let new_filled_amount = Self::validate_partial_fill_step(
+            filled_amount,
+            last_consumed_index,
+            index,
+            secret,
+            proof,
+            fill_amount,
+            immutables,
+        )?;
+
+        let new_hashchain = Self::fold_hashchain(
+            hashchain,
+            EVENT_PUBLIC_WITHDRAWAL,
+            Some(secret),
+            &taker,
+            fill_amount,
+            now,
+        );
+        Ok((new_hashchain, new_filled_amount, index))
+    }
+
+    /// Shared Merkle-proof + fill-segment validation for the partial-fill
+    /// withdraw paths. The maker's `parts_amount + 1` secrets (s_0..s_N) gate
+    /// an N-part order: index `0` is never itself a valid withdrawal index,
+    /// indices `1..parts_amount - 1` each authorize the cumulative amount
+    /// landing exactly on that index's `index/parts_amount` fraction, and
+    /// index `parts_amount` (s_N) alone authorizes completing the order to
+    /// 100% regardless of which fraction boundary the prior fills landed on.
+    /// Rejects reused/non-increasing indices and proofs that don't match the
+    /// fraction the requested `fill_amount` actually covers.
+    fn validate_partial_fill_step(
+        filled_amount: Balance,
+        last_consumed_index: u64,
+        index: u64,
+        secret: &[u8; 32],
+        proof: &[[u8; 32]],
+        fill_amount: Balance,
+        immutables: &Immutables,
+    ) -> Result<Balance, EscrowError> {
+        if !immutables.allow_multiple_fills {
+            return Err(EscrowError::InvalidPartialFill);
+        }
+        if immutables.parts_amount < 2 {
+            return Err(EscrowError::InvalidSecretsAmount);
+        }
+        if index == 0 || index > immutables.parts_amount {
+            return Err(EscrowError::InvalidPartialFill);
+        }
+        if index <= last_consumed_index {
+            return Err(EscrowError::InvalidPartialFill);
+        }
+
+        let leaf = hash_partial_fill_leaf(index, secret);
+        if !verify_partial_fill_proof(proof, leaf, index, immutables.hashlock) {
+            return Err(EscrowError::InvalidSecret);
+        }
+
+        let new_filled_amount = filled_amount
+            .checked_add(fill_amount)
+            .ok_or(EscrowError::InvalidPartialFill)?;
+        if new_filled_amount > immutables.amount {
+            return Err(EscrowError::InvalidPartialFill);
+        }
+
+        if index == immutables.parts_amount {
+            if new_filled_amount != immutables.amount {
+                return Err(EscrowError::InvalidPartialFill);
+            }
+        } else {
+            let expected = immutables.amount * (index as u128) / (immutables.parts_amount as u128);
+            if new_filled_amount != expected {
+                return Err(EscrowError::InvalidPartialFill);
+            }
+        }
+
+        Ok(new_filled_amount)
+    }
+
+    /// Shared caller/timelock/immutables checks for `cancel`/`public_cancel`,
+    /// factored out so both `execute_cancellation` and the read-only
+    /// `can_cancel`/`can_public_cancel` views run exactly the same
+    /// validation. `private` gates whether the caller must be the taker
+    /// (private) or merely hold the access token (public, checked
+    /// asynchronously by `public_cancel` before this ever runs - a
+    /// synchronous view like `can_public_cancel` can only simulate the rest
+    /// of the gate).
+    fn validate_cancellation(
+        _base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        io: &mut impl EscrowIo,
+        immutables: &Immutables,
+        private: bool,
+    ) -> Result<(), EscrowError> {
+        if private && io.predecessor() != immutables.taker {
+            return Err(EscrowError::InvalidCaller);
+        }
+
+        let stage = if private {
+            TimelockStage::SrcCancellation
+        } else {
+            TimelockStage::SrcPublicCancellation
+        };
+        let cancellation_start = immutables.timelocks.get(stage);
+        let now = io.now();
+        if now < cancellation_start {
+            return Err(EscrowError::InvalidTime);
+        }
+
+        Self::validate_immutables_pure(chain_id, expected_hash, immutables)
+    }
+
+    /// IO-parameterized cancel/public_cancel state machine. `private` gates whether
+    /// the caller must be the taker (private) or merely hold the access token (public).
+    /// Deliberately *not* gated by `base.is_paused`: cancellation only ever succeeds
+    /// once its own timelock has elapsed anyway, so exempting it from pause means a
+    /// paused escrow can never permanently strand funds.
+    /// Returns the hashchain tip that applies once the token transfer it implies
+    /// actually lands. Deliberately does *not* perform any transfer itself - see
+    /// `cancel`'s doc comment for why that's now the caller's job.
+    fn execute_cancellation(
+        base: &BaseEscrow,
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        hashchain: [u8; 32],
+        io: &mut impl EscrowIo,
+        immutables: &Immutables,
+        private: bool,
+    ) -> Result<[u8; 32], EscrowError> {
+        Self::validate_cancellation(base, chain_id, expected_hash, io, immutables, private)?;
+
+        let now = io.now();
+        let event_tag = if private {
+            EVENT_CANCELLATION
+        } else {
+            EVENT_PUBLIC_CANCELLATION
+        };
+        Ok(Self::fold_hashchain(
+            hashchain,
+            event_tag,
+            None,
+            &immutables.maker,
+            immutables.amount,
+            now,
+        ))
+    }
+
+    /// Validate immutables - recomputes `hash_immutables` of the supplied
+    /// immutables and checks it against the commitment the factory recorded
+    /// at deployment, so a resolver can't substitute different immutables
+    /// than what this escrow was actually created for.
     pub fn validate_immutables(&self, immutables: &Immutables) -> Result<(), EscrowError> {
-        // In NEAR, we would compute the deterministic address and verify it matches
-        // For now, we'll use a simplified validation
+        Self::validate_immutables_pure(self.chain_id, self.immutables_hash, immutables)
+    }
+
+    /// Pure (IO-free) immutables check shared by the withdraw/cancel helpers.
+    /// Rejects immutables whose embedded `src_chain_id` doesn't match this
+    /// escrow's, which stops a secret/proof revealed on one deployment being
+    /// replayed here, and whose `hash_immutables` doesn't match the
+    /// commitment recorded at deployment.
+    fn validate_immutables_pure(
+        chain_id: u64,
+        expected_hash: [u8; 32],
+        immutables: &Immutables,
+    ) -> Result<(), EscrowError> {
+        if immutables.src_chain_id != chain_id {
+            return Err(EscrowError::WrongChain);
+        }
+        if hash_immutables(immutables) != expected_hash {
+            return Err(EscrowError::InvalidImmutables);
+        }
         if immutables.amount == 0 {
             return Err(EscrowError::InvalidImmutables);
         }
@@ -159,14 +1103,29 @@ impl EscrowSrc {
     pub fn get_factory(&self) -> AccountId {
         self.base.get_factory()
     }
+
+    /// Pause `withdraw`/`public_withdraw`. Callable only by the guardian or factory.
+    pub fn pause(&mut self) {
+        self.base.pause().expect("Unauthorized pause");
+    }
+
+    /// Resume `withdraw`/`public_withdraw` after a pause.
+    pub fn resume(&mut self) {
+        self.base.resume().expect("Unauthorized resume");
+    }
+
+    /// Get whether withdrawals are currently paused
+    pub fn get_is_paused(&self) -> bool {
+        self.base.get_is_paused()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::hash_secret;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
     use near_sdk::{testing_env, AccountId};
-    use crate::utils::hash_secret;
 
     fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
@@ -188,42 +1147,967 @@ mod tests {
             safety_deposit: 100,
             timelocks: Timelocks {
                 deployed_at: 1000,
-                src_withdrawal: 100,    // withdrawal starts at 1100
-                src_public_withdrawal: 200, // public withdrawal starts at 1200
-                src_cancellation: 300,  // cancellation starts at 1300
+                src_withdrawal: 100,          // withdrawal starts at 1100
+                src_public_withdrawal: 200,   // public withdrawal starts at 1200
+                src_cancellation: 300,        // cancellation starts at 1300
                 src_public_cancellation: 400, // public cancellation starts at 1400
                 dst_withdrawal: 0,
                 dst_public_withdrawal: 0,
                 dst_cancellation: 0,
             },
+            src_chain_id: 1313161555, // NEAR testnet
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: crate::types::HashAlgorithm::default(),
         }
     }
 
+    /// Build `[leaf1, leaf2]` and their common Merkle root for a 2-leaf,
+    /// 2-part partial-fill tree: index 1 (half fill) and index 2 (completion).
+    fn build_two_leaf_tree(secret1: [u8; 32], secret2: [u8; 32]) -> ([u8; 32], [u8; 32], [u8; 32]) {
+        let leaf1 = hash_partial_fill_leaf(1, &secret1);
+        let leaf2 = hash_partial_fill_leaf(2, &secret2);
+        let mut data = Vec::new();
+        data.extend_from_slice(&leaf2);
+        data.extend_from_slice(&leaf1);
+        let root: [u8; 32] = near_sdk::env::keccak256(&data).try_into().unwrap();
+        (leaf1, leaf2, root)
+    }
+
     #[test]
     fn test_new() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
+
         let access_token = accounts(2);
         let rescue_delay = 3600;
-        
-        let contract = EscrowSrc::new(rescue_delay, access_token.clone());
-        
+
+        let contract = EscrowSrc::new(
+            rescue_delay,
+            access_token.clone(),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
         assert_eq!(contract.get_rescue_delay(), rescue_delay);
         assert_eq!(contract.get_factory(), accounts(1));
+        assert_eq!(contract.get_chain_id(), 1313161555);
+        let genesis: [u8; 32] = near_sdk::hash::hash(&[0u8; 32]).try_into().unwrap();
+        assert_eq!(contract.get_hashchain(), genesis);
+    }
+
+    #[test]
+    fn test_can_withdraw_matches_withdraw_outcome() {
+        let context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(1150).build());
+
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&immutables),
+        );
+        assert!(contract.can_withdraw(secret, immutables.clone()).is_ok());
+
+        let wrong_secret = [2u8; 32];
+        assert!(matches!(
+            contract.can_withdraw(wrong_secret, immutables),
+            Err(EscrowError::InvalidSecret)
+        ));
+    }
+
+    #[test]
+    fn test_can_withdraw_rejects_before_window_without_transferring() {
+        let context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(1000).build());
+
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&immutables),
+        );
+        assert!(matches!(
+            contract.can_withdraw(secret, immutables),
+            Err(EscrowError::InvalidTime)
+        ));
+    }
+
+    #[test]
+    fn test_can_cancel_matches_cancel_outcome() {
+        let context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(1300).build());
+
+        let immutables = create_test_immutables();
+        let contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&immutables),
+        );
+        assert!(contract.can_cancel(immutables.clone()).is_ok());
+
+        let context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(1200).build());
+        assert!(matches!(
+            contract.can_cancel(immutables),
+            Err(EscrowError::InvalidTime)
+        ));
+    }
+
+    #[test]
+    fn test_validate_immutables_rejects_wrong_chain_id() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract = EscrowSrc::new(
+            3600,
+            accounts(2),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let mut immutables = create_test_immutables();
+        immutables.src_chain_id = 1313161556; // mismatched deployment
+        assert!(matches!(
+            contract.validate_immutables(&immutables),
+            Err(EscrowError::WrongChain)
+        ));
+    }
+
+    #[test]
+    fn test_validate_immutables_rejects_wrong_commitment() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract = EscrowSrc::new(
+            3600,
+            accounts(2),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let mut immutables = create_test_immutables();
+        immutables.amount = 2000; // differs from what the factory committed to at deployment
+        assert!(matches!(
+            contract.validate_immutables(&immutables),
+            Err(EscrowError::InvalidImmutables)
+        ));
     }
 
     #[test]
     fn test_validate_immutables() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
-        
-        let contract = EscrowSrc::new(3600, accounts(2));
-        
+
+        let contract = EscrowSrc::new(
+            3600,
+            accounts(2),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
         let mut immutables = create_test_immutables();
         assert!(contract.validate_immutables(&immutables).is_ok());
-        
+
         immutables.amount = 0;
         assert!(contract.validate_immutables(&immutables).is_err());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_execute_withdrawal_via_mock_io() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+
+        let secret = [1u8; 32];
+        let mut hashlocked = immutables.clone();
+        hashlocked.hashlock = hash_secret(&secret);
+
+        let new_hashchain = EscrowSrc::execute_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&hashlocked),
+            [0u8; 32],
+            &mut io,
+            &secret,
+            hashlocked.taker.clone(),
+            &hashlocked,
+        )
+        .expect("withdrawal should succeed inside the withdrawal window");
+
+        // `execute_withdrawal` no longer performs any transfer itself - the
+        // real contract methods fire it and only commit on confirmation, so
+        // `io` here only sees the predecessor/clock reads validation needs.
+        assert!(io.ft_transfers.is_empty());
+        assert!(io.native_transfers.is_empty());
+        assert_ne!(new_hashchain, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_execute_withdrawal_rejects_wrong_chain_id_even_with_correct_secret() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+
+        let secret = [1u8; 32];
+        let mut hashlocked = immutables.clone();
+        hashlocked.hashlock = hash_secret(&secret);
+
+        // The escrow's own chain id (last deployed on a different chain than
+        // `hashlocked.src_chain_id` claims) must reject the withdrawal even
+        // though the secret is genuinely correct - replaying a valid secret
+        // across deployments must not be enough on its own.
+        let result = EscrowSrc::execute_withdrawal(
+            &base,
+            hashlocked.src_chain_id + 1,
+            hash_immutables(&hashlocked),
+            [0u8; 32],
+            &mut io,
+            &secret,
+            hashlocked.taker.clone(),
+            &hashlocked,
+        );
+        assert!(matches!(result, Err(EscrowError::WrongChain)));
+    }
+
+    #[test]
+    fn test_hashchain_advances_deterministically_and_differs_per_event() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables();
+        let secret = [1u8; 32];
+        let mut hashlocked = immutables.clone();
+        hashlocked.hashlock = hash_secret(&secret);
+
+        let mut io_a = crate::io::mock::MockIo::new(hashlocked.taker.clone(), 1150);
+        let genesis = [0u8; 32];
+        let tip_a = EscrowSrc::execute_withdrawal(
+            &base,
+            hashlocked.src_chain_id,
+            hash_immutables(&hashlocked),
+            genesis,
+            &mut io_a,
+            &secret,
+            hashlocked.taker.clone(),
+            &hashlocked,
+        )
+        .expect("withdrawal should succeed");
+
+        // Replaying the identical event against the same prior tip must fold to
+        // the same next value, so an off-chain observer can reproduce it exactly.
+        let mut io_b = crate::io::mock::MockIo::new(hashlocked.taker.clone(), 1150);
+        let tip_b = EscrowSrc::execute_withdrawal(
+            &base,
+            hashlocked.src_chain_id,
+            hash_immutables(&hashlocked),
+            genesis,
+            &mut io_b,
+            &secret,
+            hashlocked.taker.clone(),
+            &hashlocked,
+        )
+        .expect("withdrawal should succeed");
+        assert_eq!(tip_a, tip_b);
+
+        // A cancellation folded on top of that tip must diverge from it.
+        let mut io_c = crate::io::mock::MockIo::new(hashlocked.taker.clone(), 1300);
+        let tip_c = EscrowSrc::execute_cancellation(
+            &base,
+            hashlocked.src_chain_id,
+            hash_immutables(&hashlocked),
+            tip_a,
+            &mut io_c,
+            &hashlocked,
+            true,
+        )
+        .expect("cancellation should succeed");
+        assert_ne!(tip_a, tip_c);
+    }
+
+    #[test]
+    fn test_execute_withdrawal_rejects_wrong_caller() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(accounts(4), 1150);
+
+        let result = EscrowSrc::execute_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            &mut io,
+            &[1u8; 32],
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidCaller)));
+        assert!(io.ft_transfers.is_empty());
+    }
+
+    #[test]
+    fn test_execute_withdrawal_rejects_before_window() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1000);
+
+        let result = EscrowSrc::execute_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            &mut io,
+            &[1u8; 32],
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidTime)));
+    }
+
+    #[test]
+    fn test_execute_cancellation_via_mock_io() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1300);
+
+        EscrowSrc::execute_cancellation(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            &mut io,
+            &immutables,
+            true,
+        )
+        .expect("cancellation should succeed after the cancellation timelock");
+
+        // Same as `execute_withdrawal`: transfers are no longer fired here.
+        assert!(io.ft_transfers.is_empty());
+    }
+
+    #[test]
+    fn test_execute_withdrawal_rejects_while_paused() {
+        let context = get_context(accounts(5));
+        testing_env!(context.build());
+
+        let mut base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        base.pause().expect("guardian should be able to pause");
+
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+
+        let result = EscrowSrc::execute_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            &mut io,
+            &[1u8; 32],
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::Paused)));
+        assert!(io.ft_transfers.is_empty());
+    }
+
+    #[test]
+    fn test_execute_cancellation_ignores_pause_once_timelock_elapsed() {
+        let context = get_context(accounts(5));
+        testing_env!(context.build());
+
+        let mut base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        base.pause().expect("guardian should be able to pause");
+
+        let immutables = create_test_immutables();
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1300);
+
+        EscrowSrc::execute_cancellation(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            &mut io,
+            &immutables,
+            true,
+        )
+        .expect("cancellation should still succeed while paused, once the timelock has elapsed");
+    }
+
+    #[test]
+    #[should_panic(expected = "Public partial withdrawal failed")]
+    fn test_public_withdraw_partial_rejects_while_paused() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        testing_env!(get_context(accounts(5)).build());
+        contract.pause();
+        assert!(contract.get_is_paused());
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.public_withdraw_partial(1, [1u8; 32], vec![], 100, create_test_immutables());
+    }
+
+    #[test]
+    fn test_execute_partial_withdrawal_two_step_fill() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let mut immutables = create_test_immutables();
+        immutables.allow_multiple_fills = true;
+        immutables.parts_amount = 2;
+
+        let secret1 = [1u8; 32];
+        let secret2 = [2u8; 32];
+        let (_leaf1, leaf2, root) = build_two_leaf_tree(secret1, secret2);
+        immutables.hashlock = root;
+
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+        let (hashchain_1, filled_1, last_index_1) = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            0,
+            0,
+            &mut io,
+            1,
+            &secret1,
+            &[leaf2],
+            500,
+            immutables.taker.clone(),
+            &immutables,
+        )
+        .expect("half fill should succeed");
+
+        assert_eq!(filled_1, 500);
+        assert_eq!(last_index_1, 1);
+        assert!(
+            io.ft_transfers.is_empty(),
+            "execute_partial_withdrawal is pure - the caller fires the transfer"
+        );
+        assert!(
+            io.native_transfers.is_empty(),
+            "execute_partial_withdrawal is pure - the caller fires the transfer"
+        );
+
+        let (leaf1, _leaf2, _root) = build_two_leaf_tree(secret1, secret2);
+        let (_hashchain_2, filled_2, last_index_2) = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            hashchain_1,
+            filled_1,
+            last_index_1,
+            &mut io,
+            2,
+            &secret2,
+            &[leaf1],
+            500,
+            immutables.taker.clone(),
+            &immutables,
+        )
+        .expect("completing fill should succeed");
+
+        assert_eq!(filled_2, 1000);
+        assert_eq!(last_index_2, 2);
+        assert!(
+            io.native_transfers.is_empty(),
+            "execute_partial_withdrawal is pure - the caller pays the safety deposit"
+        );
+    }
+
+    #[test]
+    fn test_execute_partial_withdrawal_rejects_reused_index() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let mut immutables = create_test_immutables();
+        immutables.allow_multiple_fills = true;
+        immutables.parts_amount = 2;
+
+        let secret1 = [1u8; 32];
+        let secret2 = [2u8; 32];
+        let (_leaf1, leaf2, root) = build_two_leaf_tree(secret1, secret2);
+        immutables.hashlock = root;
+
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+        let result = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            500,
+            1,
+            &mut io,
+            1,
+            &secret1,
+            &[leaf2],
+            500,
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidPartialFill)));
+    }
+
+    #[test]
+    fn test_execute_partial_withdrawal_rejects_wrong_proof() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let mut immutables = create_test_immutables();
+        immutables.allow_multiple_fills = true;
+        immutables.parts_amount = 2;
+
+        let secret1 = [1u8; 32];
+        let secret2 = [2u8; 32];
+        let (leaf1, _leaf2, root) = build_two_leaf_tree(secret1, secret2);
+        immutables.hashlock = root;
+
+        // Using `leaf1` (the sibling for index 2) as the proof for index 1 doesn't
+        // reconstruct the real root.
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+        let result = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            0,
+            0,
+            &mut io,
+            1,
+            &secret1,
+            &[leaf1],
+            500,
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidSecret)));
+    }
+
+    #[test]
+    fn test_execute_partial_withdrawal_rejects_fraction_mismatch() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let mut immutables = create_test_immutables();
+        immutables.allow_multiple_fills = true;
+        immutables.parts_amount = 2;
+
+        let secret1 = [1u8; 32];
+        let secret2 = [2u8; 32];
+        let (_leaf1, leaf2, root) = build_two_leaf_tree(secret1, secret2);
+        immutables.hashlock = root;
+
+        // A valid proof for index 1, but claiming an amount that doesn't land
+        // on the 50% breakpoint index 1 is meant to authorize.
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+        let result = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            0,
+            0,
+            &mut io,
+            1,
+            &secret1,
+            &[leaf2],
+            400,
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidPartialFill)));
+    }
+
+    #[test]
+    fn test_execute_partial_withdrawal_rejects_overfill() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let mut immutables = create_test_immutables();
+        immutables.allow_multiple_fills = true;
+        immutables.parts_amount = 2;
+
+        let secret1 = [1u8; 32];
+        let secret2 = [2u8; 32];
+        let (leaf1, _leaf2, root) = build_two_leaf_tree(secret1, secret2);
+        immutables.hashlock = root;
+
+        // A valid proof for the completion index (2 of 2), but the cumulative
+        // amount it would release (400 already filled + 700 more = 1100)
+        // exceeds `immutables.amount` (1000) - must be rejected even before
+        // the exact-fraction check below it gets a chance to.
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+        let result = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            400,
+            1,
+            &mut io,
+            2,
+            &secret2,
+            &[leaf1],
+            700,
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidPartialFill)));
+    }
+
+    #[test]
+    fn test_execute_partial_withdrawal_rejects_when_not_allowed() {
+        let base = BaseEscrow::new(3600, accounts(9), accounts(5), 1313161555);
+        let immutables = create_test_immutables(); // allow_multiple_fills: false
+
+        let mut io = crate::io::mock::MockIo::new(immutables.taker.clone(), 1150);
+        let result = EscrowSrc::execute_partial_withdrawal(
+            &base,
+            immutables.src_chain_id,
+            hash_immutables(&immutables),
+            [0u8; 32],
+            0,
+            0,
+            &mut io,
+            1,
+            &[1u8; 32],
+            &[],
+            500,
+            immutables.taker.clone(),
+            &immutables,
+        );
+        assert!(matches!(result, Err(EscrowError::InvalidPartialFill)));
+    }
+
+    /// Mock a resolved access-token `ft_balance_of` promise as if it had
+    /// returned `balance`, at the given block timestamp.
+    fn set_access_token_balance_result(predecessor: AccountId, now: u64, balance: u128) {
+        let mut context = get_context(predecessor);
+        testing_env!(
+            context.block_timestamp(now).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![near_sdk::PromiseResult::Successful(
+                near_sdk::serde_json::to_vec(&near_sdk::json_types::U128(balance)).unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Not an access token holder")]
+    fn test_resolve_public_withdraw_rejects_zero_access_token_balance() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&immutables),
+        );
+
+        set_access_token_balance_result(accounts(4), 1250, 0);
+        contract.resolve_public_withdraw(secret, immutables, accounts(4));
+    }
+
+    #[test]
+    fn test_resolve_public_withdraw_accepts_non_zero_access_token_balance() {
+        let secret = [1u8; 32];
+        let mut immutables = create_test_immutables();
+        immutables.hashlock = hash_secret(&secret);
+
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&immutables),
+        );
+
+        set_access_token_balance_result(accounts(4), 1250, 1);
+        contract.resolve_public_withdraw(secret, immutables, accounts(4));
+        assert_ne!(
+            contract.get_hashchain(),
+            near_sdk::hash::hash(&[0u8; 32]).try_into().unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Not an access token holder")]
+    fn test_resolve_public_cancel_rejects_zero_access_token_balance() {
+        let immutables = create_test_immutables();
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&immutables),
+        );
+
+        set_access_token_balance_result(accounts(4), 1400, 0);
+        contract.resolve_public_cancel(immutables, accounts(4));
+    }
+
+    /// Mock a resolved token-transfer promise as if it had `succeeded`, at the
+    /// given block timestamp.
+    fn set_transfer_result(predecessor: AccountId, now: u64, succeeded: bool) {
+        let mut context = get_context(predecessor);
+        let result = if succeeded {
+            near_sdk::PromiseResult::Successful(vec![])
+        } else {
+            near_sdk::PromiseResult::Failed
+        };
+        testing_env!(
+            context.block_timestamp(now).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn test_resolve_withdrawal_commits_hashchain_on_successful_transfer() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let new_hashchain = [7u8; 32];
+        set_transfer_result(accounts(4), 1150, true);
+        contract
+            .resolve_withdrawal(new_hashchain, accounts(2), 100, [1u8; 32])
+            .expect("a successful token transfer should commit the withdrawal");
+
+        assert_eq!(contract.get_hashchain(), new_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_withdrawal_leaves_hashchain_untouched_on_failed_transfer() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+        let initial_hashchain = contract.get_hashchain();
+
+        set_transfer_result(accounts(4), 1150, false);
+        let result = contract.resolve_withdrawal([7u8; 32], accounts(2), 100, [1u8; 32]);
+
+        assert!(matches!(
+            result,
+            Err(EscrowError::NativeTokenSendingFailure)
+        ));
+        assert_eq!(contract.get_hashchain(), initial_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_cancellation_commits_hashchain_on_successful_transfer() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let new_hashchain = [8u8; 32];
+        set_transfer_result(accounts(4), 1400, true);
+        contract
+            .resolve_cancellation(new_hashchain, accounts(2), 100)
+            .expect("a successful token transfer should commit the cancellation");
+
+        assert_eq!(contract.get_hashchain(), new_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_cancellation_leaves_hashchain_untouched_on_failed_transfer() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+        let initial_hashchain = contract.get_hashchain();
+
+        set_transfer_result(accounts(4), 1400, false);
+        let result = contract.resolve_cancellation([8u8; 32], accounts(2), 100);
+
+        assert!(matches!(
+            result,
+            Err(EscrowError::NativeTokenSendingFailure)
+        ));
+        assert_eq!(contract.get_hashchain(), initial_hashchain);
+    }
+
+    #[test]
+    fn test_resolve_partial_withdrawal_commits_fill_state_on_successful_transfer() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        let new_hashchain = [7u8; 32];
+        set_transfer_result(accounts(4), 1150, true);
+        contract
+            .resolve_partial_withdrawal(new_hashchain, 500, 1, accounts(2), 100, 1000, 1, [1u8; 32], 500)
+            .expect("a successful token transfer should commit the partial withdrawal");
+
+        assert_eq!(contract.get_hashchain(), new_hashchain);
+        assert_eq!(contract.get_filled_amount(), 500);
+        assert_eq!(contract.get_last_consumed_index(), 1);
+    }
+
+    #[test]
+    fn test_resolve_partial_withdrawal_leaves_fill_state_untouched_on_failed_transfer() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+        let initial_hashchain = contract.get_hashchain();
+
+        set_transfer_result(accounts(4), 1150, false);
+        let result =
+            contract.resolve_partial_withdrawal([7u8; 32], 500, 1, accounts(2), 100, 1000, 1, [1u8; 32], 500);
+
+        assert!(matches!(
+            result,
+            Err(EscrowError::NativeTokenSendingFailure)
+        ));
+        assert_eq!(contract.get_hashchain(), initial_hashchain);
+        assert_eq!(contract.get_filled_amount(), 0);
+        assert_eq!(contract.get_last_consumed_index(), 0);
+    }
+
+    #[test]
+    fn test_resolve_partial_withdrawal_commits_half_then_completing_fill() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+
+        set_transfer_result(accounts(4), 1150, true);
+        contract
+            .resolve_partial_withdrawal([7u8; 32], 500, 1, accounts(2), 100, 1000, 1, [1u8; 32], 500)
+            .expect("half fill should still commit");
+        assert_eq!(contract.get_filled_amount(), 500);
+
+        set_transfer_result(accounts(4), 1200, true);
+        contract
+            .resolve_partial_withdrawal([8u8; 32], 1000, 2, accounts(2), 100, 1000, 2, [2u8; 32], 500)
+            .expect("completing fill should commit and pay the safety deposit");
+        assert_eq!(contract.get_filled_amount(), 1000);
+        assert_eq!(contract.get_last_consumed_index(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "WithdrawalInFlight")]
+    fn test_withdraw_partial_rejects_while_a_previous_one_is_in_flight() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+        contract.withdrawal_in_flight = true;
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.withdraw_partial(1, [1u8; 32], vec![], 500, create_test_immutables());
+    }
+
+    #[test]
+    #[should_panic(expected = "WithdrawalInFlight")]
+    fn test_public_withdraw_partial_rejects_while_a_previous_one_is_in_flight() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+        contract.withdrawal_in_flight = true;
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.public_withdraw_partial(1, [1u8; 32], vec![], 500, create_test_immutables());
+    }
+
+    #[test]
+    fn test_resolve_partial_withdrawal_clears_in_flight_flag_regardless_of_outcome() {
+        let mut contract = EscrowSrc::new(
+            3600,
+            accounts(9),
+            1313161555,
+            [0u8; 32],
+            accounts(5),
+            hash_immutables(&create_test_immutables()),
+        );
+        contract.withdrawal_in_flight = true;
+
+        set_transfer_result(accounts(4), 1150, false);
+        let _ = contract.resolve_partial_withdrawal([7u8; 32], 500, 1, accounts(2), 100, 1000, 1, [1u8; 32], 500);
+        assert!(
+            !contract.withdrawal_in_flight,
+            "a failed transfer must still release the guard so the next call isn't locked out forever"
+        );
+
+        contract.withdrawal_in_flight = true;
+        set_transfer_result(accounts(4), 1200, true);
+        contract
+            .resolve_partial_withdrawal([8u8; 32], 1000, 2, accounts(2), 100, 1000, 2, [2u8; 32], 500)
+            .expect("a successful transfer should commit");
+        assert!(!contract.withdrawal_in_flight);
+    }
+}
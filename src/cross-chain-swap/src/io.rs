@@ -0,0 +1,137 @@
+use near_sdk::{env, ext_contract, AccountId, Gas, Promise};
+
+// Gas for cross-contract calls
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+
+/// Abstracts the runtime operations `EscrowSrc` needs (caller identity, clock,
+/// and transfers) behind a trait so the withdraw/cancel state machine can be
+/// driven by a mock in unit tests instead of a full sandbox.
+pub trait EscrowIo {
+    /// The account that called the current transaction.
+    fn predecessor(&self) -> AccountId;
+    /// The current block timestamp (nanoseconds).
+    fn now(&self) -> u64;
+    /// Transfer native NEAR to `to`.
+    fn native_transfer(&mut self, to: &AccountId, amount: u128);
+    /// Transfer a fungible token (or native NEAR, when `token == "near"`) to `to`.
+    fn ft_transfer(&mut self, token: &AccountId, to: &AccountId, amount: u128);
+}
+
+/// `EscrowIo` implementation that delegates to the real `near_sdk` runtime.
+/// This is what the on-chain contract methods use.
+#[derive(Default)]
+pub struct NearRuntimeIo;
+
+impl EscrowIo for NearRuntimeIo {
+    fn predecessor(&self) -> AccountId {
+        env::predecessor_account_id()
+    }
+
+    fn now(&self) -> u64 {
+        env::block_timestamp()
+    }
+
+    fn native_transfer(&mut self, to: &AccountId, amount: u128) {
+        Promise::new(to.clone()).transfer(amount);
+    }
+
+    fn ft_transfer(&mut self, token: &AccountId, to: &AccountId, amount: u128) {
+        if token.as_str() == "near" {
+            self.native_transfer(to, amount);
+        } else {
+            ext_ft::ext(token.clone())
+                .with_attached_deposit(1)
+                .with_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(to.clone(), amount, None);
+        }
+    }
+}
+
+/// `EscrowIo` implementation for use inside a `#[private]` resolve callback
+/// kicked off by the access-token-gated public withdraw/cancel paths. The
+/// callback's own predecessor is the contract itself (it's a self-call
+/// scheduled via `.then()`), so the account that actually triggered the
+/// public action has to be threaded through explicitly instead of read live
+/// off `env::predecessor_account_id()`.
+pub struct CallbackIo {
+    caller: AccountId,
+}
+
+impl CallbackIo {
+    pub fn new(caller: AccountId) -> Self {
+        Self { caller }
+    }
+}
+
+impl EscrowIo for CallbackIo {
+    fn predecessor(&self) -> AccountId {
+        self.caller.clone()
+    }
+
+    fn now(&self) -> u64 {
+        env::block_timestamp()
+    }
+
+    fn native_transfer(&mut self, to: &AccountId, amount: u128) {
+        Promise::new(to.clone()).transfer(amount);
+    }
+
+    fn ft_transfer(&mut self, token: &AccountId, to: &AccountId, amount: u128) {
+        if token.as_str() == "near" {
+            self.native_transfer(to, amount);
+        } else {
+            ext_ft::ext(token.clone())
+                .with_attached_deposit(1)
+                .with_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(to.clone(), amount, None);
+        }
+    }
+}
+
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: u128, memo: Option<String>);
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::EscrowIo;
+    use near_sdk::AccountId;
+
+    /// In-crate mock `EscrowIo` that records calls instead of touching the runtime.
+    pub struct MockIo {
+        pub predecessor: AccountId,
+        pub now: u64,
+        pub native_transfers: Vec<(AccountId, u128)>,
+        pub ft_transfers: Vec<(AccountId, AccountId, u128)>,
+    }
+
+    impl MockIo {
+        pub fn new(predecessor: AccountId, now: u64) -> Self {
+            Self {
+                predecessor,
+                now,
+                native_transfers: Vec::new(),
+                ft_transfers: Vec::new(),
+            }
+        }
+    }
+
+    impl EscrowIo for MockIo {
+        fn predecessor(&self) -> AccountId {
+            self.predecessor.clone()
+        }
+
+        fn now(&self) -> u64 {
+            self.now
+        }
+
+        fn native_transfer(&mut self, to: &AccountId, amount: u128) {
+            self.native_transfers.push((to.clone(), amount));
+        }
+
+        fn ft_transfer(&mut self, token: &AccountId, to: &AccountId, amount: u128) {
+            self.ft_transfers.push((token.clone(), to.clone(), amount));
+        }
+    }
+}
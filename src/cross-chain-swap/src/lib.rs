@@ -5,6 +5,7 @@ pub mod base_escrow;
 pub mod escrow_factory;
 pub mod escrow_src;
 pub mod escrow_dst;
+pub mod io;
 pub mod types;
 pub mod utils;
 
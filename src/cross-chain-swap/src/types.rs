@@ -15,6 +15,49 @@ pub struct Immutables {
     pub amount: Balance,
     pub safety_deposit: Balance,
     pub timelocks: Timelocks,
+    /// Chain identifier of the source-chain escrow for this swap. Bound into
+    /// `hash_immutables` and checked against `EscrowSrc`'s own configured
+    /// chain id in `validate_immutables`, so a commitment valid on one
+    /// deployment can't be replayed against a sibling on another chain
+    /// (EIP-155-style replay protection).
+    pub src_chain_id: u64,
+    /// Chain identifier of the destination-chain escrow for this swap. Bound
+    /// into `hash_immutables` and checked against `EscrowDst`'s own
+    /// configured chain id in `validate_immutables`.
+    pub dst_chain_id: u64,
+    /// When `true`, `hashlock` is the root of a Merkle tree of `parts_amount + 1`
+    /// secrets (1inch Fusion+ style) rather than a single secret hash, and the
+    /// escrow must be withdrawn through the partial-fill path instead of the
+    /// single-secret one.
+    pub allow_multiple_fills: bool,
+    /// Number of parts (N) the order is split into when `allow_multiple_fills`
+    /// is set. The maker generates N+1 secrets s_0..s_N; secret s_N alone
+    /// authorizes completing the fill to 100%. Ignored for single-fill orders.
+    pub parts_amount: u64,
+    /// Preimage hash function `hashlock` commits under. Cross-chain HTLCs
+    /// must use whatever hash the counterparty chain enforces rather than
+    /// always assuming EVM keccak256; see `HashAlgorithm`.
+    pub hash_algorithm: HashAlgorithm,
+}
+
+/// Hash function a cross-chain HTLC's `hashlock`/secret is computed with.
+/// Routed through `hash_secret_with`/`validate_secret` so a single NEAR
+/// escrow can interoperate with multiple foreign chains: SHA-256 (the
+/// pre-existing default, unchanged for callers that don't opt in), keccak256
+/// for EVM, Blake2b for Zcash/Substrate chains.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+pub enum HashAlgorithm {
+    Sha256,
+    Keccak256,
+    Blake2b256,
+}
+
+impl Default for HashAlgorithm {
+    fn default() -> Self {
+        HashAlgorithm::Sha256
+    }
 }
 
 /// Timelocks for source and destination chains plus deployment timestamp
@@ -53,6 +96,22 @@ impl Timelocks {
             TimelockStage::DstCancellation => self.deployed_at + self.dst_cancellation,
         }
     }
+
+    /// Check that the stage offsets are well-ordered: private withdrawal
+    /// opens before public withdrawal, which opens before cancellation,
+    /// which (on the source chain) opens before public cancellation - on
+    /// both the source and destination side. A factory with out-of-order
+    /// offsets could open a cancellation window before a withdrawal window
+    /// a resolver has already committed gas/time to, so this is checked at
+    /// escrow-creation time rather than left for the withdraw/cancel paths
+    /// to discover at the worst moment.
+    pub fn validate_ordering(&self) -> bool {
+        self.src_withdrawal <= self.src_public_withdrawal
+            && self.src_public_withdrawal <= self.src_cancellation
+            && self.src_cancellation <= self.src_public_cancellation
+            && self.dst_withdrawal <= self.dst_public_withdrawal
+            && self.dst_public_withdrawal <= self.dst_cancellation
+    }
 }
 
 /// Timelock stages enum
@@ -67,6 +126,53 @@ pub enum TimelockStage {
     DstCancellation,
 }
 
+/// A witness condition gating a `Branch` of a `ReleasePlan` (see
+/// `BaseEscrow::release`). A branch fires only once every witness on it is
+/// satisfied.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Witness {
+    /// Satisfied once `env::block_timestamp()` reaches this value.
+    Timestamp(u64),
+    /// Satisfied once the caller supplies a preimage whose `hash_secret`
+    /// equals this value.
+    Secret([u8; 32]),
+    /// Satisfied once the predecessor account equals this value.
+    Signature(AccountId),
+}
+
+/// A payment that fires once every witness on its branch is satisfied.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Payment {
+    pub to: AccountId,
+    pub token: AccountId,
+    pub amount: u128,
+}
+
+/// One branch of a `ReleasePlan`: `payment` fires once every entry in
+/// `witnesses` is satisfied, and `consumed` then latches so it can never
+/// fire a second time.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Branch {
+    pub witnesses: Vec<Witness>,
+    pub payment: Payment,
+    pub consumed: bool,
+}
+
+/// Budget-DSL-style conditional release plan (modeled on Solana's Budget
+/// contract): an ordered list of `Branch`es, each gated by its own witnesses.
+/// `BaseEscrow::release` fires the first not-yet-consumed branch whose
+/// witnesses are all satisfied. Generalizes the hard-coded
+/// taker-withdraw-after-timelock HTLC flow into data, so integrators can
+/// express richer release flows (taker-OR-maker refund, multi-party
+/// safety-deposit splits, ...) by installing a different plan, without new
+/// contract methods.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default,
+)]
+pub struct ReleasePlan {
+    pub branches: Vec<Branch>,
+}
+
 /// Destination immutables complement for cross-chain operations
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
 pub struct DstImmutablesComplement {
@@ -85,6 +191,9 @@ pub struct ExtraDataArgs {
     pub timelocks: Timelocks,
     pub dst_token: AccountId,
     pub dst_chain_id: u64,
+    /// Hash function `hashlock_info` commits under; forwarded into the
+    /// escrow's `Immutables::hash_algorithm` at deploy time.
+    pub hash_algorithm: HashAlgorithm,
 }
 
 /// Validation data for partial fills
@@ -106,4 +215,18 @@ pub enum EscrowError {
     InvalidCreationTime,
     InvalidSecretsAmount,
     InvalidPartialFill,
-} 
\ No newline at end of file
+    Paused,
+    WrongChain,
+    NoBranchSatisfied,
+    ReleasePlanExhausted,
+    OnlyAccessTokenHolder,
+    WithdrawalInFlight,
+}
+
+/// Custom errors for the escrow factory
+#[derive(Debug)]
+pub enum FactoryError {
+    InvalidSignature,
+    ContractPaused,
+    InvalidTimelocks,
+}
@@ -1,8 +1,120 @@
 use near_sdk::{
     env,
     hash::{hash, CryptoHash},
+    AccountId,
 };
-use crate::types::{Immutables, Timelocks, TimelockStage, ValidationData, EscrowError};
+use crate::types::{Immutables, Timelocks, TimelockStage, ValidationData, EscrowError, HashAlgorithm};
+
+/// EIP-712 type string for `Immutables`, with the referenced `Timelocks` type
+/// appended per the `encodeType` convention. Must stay byte-for-byte in sync
+/// with the Solidity struct this NEAR port mirrors.
+const IMMUTABLES_TYPE_STRING: &[u8] =
+    b"Immutables(bytes32 orderHash,bytes32 hashlock,address maker,address taker,address token,uint256 amount,uint256 safetyDeposit,Timelocks timelocks)Timelocks(uint256 deployedAt,uint256 srcWithdrawal,uint256 srcPublicWithdrawal,uint256 srcCancellation,uint256 srcPublicCancellation,uint256 dstWithdrawal,uint256 dstPublicWithdrawal,uint256 dstCancellation)";
+
+/// EIP-712 type string for the nested `Timelocks` struct.
+const TIMELOCKS_TYPE_STRING: &[u8] =
+    b"Timelocks(uint256 deployedAt,uint256 srcWithdrawal,uint256 srcPublicWithdrawal,uint256 srcCancellation,uint256 srcPublicCancellation,uint256 dstWithdrawal,uint256 dstPublicWithdrawal,uint256 dstCancellation)";
+
+/// Parse a `0x`-prefixed 40-hex-character Ethereum address out of an
+/// `AccountId`, the convention this NEAR port uses to let an EVM maker's
+/// identity cross to the NEAR side unambiguously.
+fn parse_eth_address(account: &AccountId) -> Option<[u8; 20]> {
+    let hex = account.as_str().strip_prefix("0x")?;
+    if hex.len() != 40 {
+        return None;
+    }
+    let mut address = [0u8; 20];
+    for (i, byte) in address.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(address)
+}
+
+/// Left-pad an account identifier into a 32-byte big-endian word, the ABI
+/// encoding EVM's `address` type uses. An EVM-side account is decoded back
+/// to its raw 20 address bytes and zero-padded exactly as Solidity's ABI
+/// encoder would; a NEAR-native id that fits is zero-padded like an address
+/// would be, and one that doesn't is folded down with keccak256 so the word
+/// stays a deterministic function of the whole id.
+fn left_pad_account(account: &AccountId) -> [u8; 32] {
+    if let Some(address) = parse_eth_address(account) {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&address);
+        return word;
+    }
+
+    let bytes = account.as_bytes();
+    if bytes.len() <= 32 {
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        word
+    } else {
+        env::keccak256(bytes).try_into().unwrap()
+    }
+}
+
+/// Encode a `u128` as a 32-byte big-endian word, the ABI encoding of `uint256`.
+fn u128_be32(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Encode a `u64` as a 32-byte big-endian word, the ABI encoding of `uint256`.
+fn u64_be32(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// EIP-712 struct hash of `Timelocks`, nested inside the `Immutables` struct hash.
+fn hash_timelocks_712(timelocks: &Timelocks) -> [u8; 32] {
+    let type_hash: [u8; 32] = env::keccak256(TIMELOCKS_TYPE_STRING).try_into().unwrap();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&type_hash);
+    data.extend_from_slice(&u64_be32(timelocks.deployed_at));
+    data.extend_from_slice(&u64_be32(timelocks.src_withdrawal));
+    data.extend_from_slice(&u64_be32(timelocks.src_public_withdrawal));
+    data.extend_from_slice(&u64_be32(timelocks.src_cancellation));
+    data.extend_from_slice(&u64_be32(timelocks.src_public_cancellation));
+    data.extend_from_slice(&u64_be32(timelocks.dst_withdrawal));
+    data.extend_from_slice(&u64_be32(timelocks.dst_public_withdrawal));
+    data.extend_from_slice(&u64_be32(timelocks.dst_cancellation));
+
+    env::keccak256(&data).try_into().unwrap()
+}
+
+/// EIP-712 domain-separated structured hash of `Immutables`.
+///
+/// `hash_immutables` below is the legacy sha256 path kept for callers that
+/// only need an internal deterministic salt. This function instead computes
+/// `keccak256(0x1901 || domain_separator || struct_hash)` exactly as the EVM
+/// Fusion+ contracts do, so an order/escrow identifier computed here matches
+/// the one computed on the Ethereum side of the same swap bit-for-bit -
+/// required for a real cross-chain bridge rather than a NEAR-only one.
+pub fn hash_immutables_712(immutables: &Immutables, domain_separator: &[u8; 32]) -> [u8; 32] {
+    let type_hash: [u8; 32] = env::keccak256(IMMUTABLES_TYPE_STRING).try_into().unwrap();
+    let timelocks_hash = hash_timelocks_712(&immutables.timelocks);
+
+    let mut struct_data = Vec::new();
+    struct_data.extend_from_slice(&type_hash);
+    struct_data.extend_from_slice(&immutables.order_hash);
+    struct_data.extend_from_slice(&immutables.hashlock);
+    struct_data.extend_from_slice(&left_pad_account(&immutables.maker));
+    struct_data.extend_from_slice(&left_pad_account(&immutables.taker));
+    struct_data.extend_from_slice(&left_pad_account(&immutables.token));
+    struct_data.extend_from_slice(&u128_be32(immutables.amount));
+    struct_data.extend_from_slice(&u128_be32(immutables.safety_deposit));
+    struct_data.extend_from_slice(&timelocks_hash);
+    let struct_hash: [u8; 32] = env::keccak256(&struct_data).try_into().unwrap();
+
+    let mut digest_data = Vec::with_capacity(2 + 32 + 32);
+    digest_data.extend_from_slice(&[0x19, 0x01]);
+    digest_data.extend_from_slice(domain_separator);
+    digest_data.extend_from_slice(&struct_hash);
+    env::keccak256(&digest_data).try_into().unwrap()
+}
 
 /// Compute hash of immutables for deterministic address generation
 pub fn hash_immutables(immutables: &Immutables) -> [u8; 32] {
@@ -14,11 +126,16 @@ pub fn hash_immutables(immutables: &Immutables) -> [u8; 32] {
     data.extend_from_slice(immutables.token.as_bytes());
     data.extend_from_slice(&immutables.amount.to_le_bytes());
     data.extend_from_slice(&immutables.safety_deposit.to_le_bytes());
-    
+
     // Hash timelocks
     let timelocks_hash = hash_timelocks(&immutables.timelocks);
     data.extend_from_slice(&timelocks_hash);
-    
+
+    // Bind both chain ids so the same order_hash/hashlock can't be replayed
+    // against a sibling escrow deployed on another chain/network.
+    data.extend_from_slice(&immutables.src_chain_id.to_le_bytes());
+    data.extend_from_slice(&immutables.dst_chain_id.to_le_bytes());
+
     hash(&data).try_into().unwrap()
 }
 
@@ -42,6 +159,27 @@ pub fn hash_secret(secret: &[u8; 32]) -> [u8; 32] {
     hash(secret).try_into().unwrap()
 }
 
+/// Compute the hash of a secret using `algorithm`, the hash function the
+/// counterparty chain's HTLC enforces (see `HashAlgorithm`). `Sha256` (the
+/// default) is identical to the pre-existing `hash_secret`, so callers that
+/// don't set `hash_algorithm` see no change. Blake2b is computed with a
+/// fixed 32-byte output and empty personalization, matching the standard
+/// 256-bit Blake2b used by Zcash/Substrate-style chains.
+pub fn hash_secret_with(secret: &[u8; 32], algorithm: HashAlgorithm) -> [u8; 32] {
+    match algorithm {
+        HashAlgorithm::Sha256 => hash_secret(secret),
+        HashAlgorithm::Keccak256 => env::keccak256(secret).try_into().unwrap(),
+        HashAlgorithm::Blake2b256 => {
+            use blake2::digest::{Update, VariableOutput};
+            let mut hasher = blake2::Blake2bVar::new(32).unwrap();
+            hasher.update(secret);
+            let mut output = [0u8; 32];
+            hasher.finalize_variable(&mut output).unwrap();
+            output
+        }
+    }
+}
+
 /// Validate that the current time is after the given timestamp
 pub fn validate_after(start: u64) -> Result<(), EscrowError> {
     if env::block_timestamp() < start {
@@ -103,4 +241,115 @@ pub fn validate_partial_fill(
     } else {
         Ok(calculated_index + 1 == validated_index)
     }
-} 
\ No newline at end of file
+}
+
+/// Leaf hash for secret index `index` in a Merkle tree of secrets (1inch
+/// Fusion+ partial fills): `keccak(index_le_bytes ‖ hash_secret(secret))`,
+/// where `hash_secret` is sha256. The
+/// maker publishes the root of these leaves as `Immutables.hashlock` instead
+/// of a single secret hash, so different resolvers can each reveal their own
+/// index's secret to fill their slice of the order.
+pub fn hash_partial_fill_leaf(index: u64, secret: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(8 + 32);
+    data.extend_from_slice(&index.to_le_bytes());
+    data.extend_from_slice(&hash_secret(secret));
+    env::keccak256(&data).try_into().unwrap()
+}
+
+/// Verify a Merkle proof for `leaf` at `index` against `root`. Bit `i` of
+/// `index` selects which side of the proof's `i`-th element `leaf` sits on,
+/// matching `base-escrow-factory::process_merkle_proof`'s convention.
+pub fn verify_partial_fill_proof(
+    proof: &[[u8; 32]],
+    leaf: [u8; 32],
+    index: u64,
+    root: [u8; 32],
+) -> bool {
+    let mut current_hash = leaf;
+    for (i, proof_element) in proof.iter().enumerate() {
+        let mut data = Vec::with_capacity(64);
+        if (index >> i) & 1 == 0 {
+            data.extend_from_slice(&current_hash);
+            data.extend_from_slice(proof_element);
+        } else {
+            data.extend_from_slice(proof_element);
+            data.extend_from_slice(&current_hash);
+        }
+        current_hash = env::keccak256(&data).try_into().unwrap();
+    }
+    current_hash == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{HashAlgorithm, Timelocks};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, NearToken};
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .predecessor_account_id(predecessor_account_id)
+            .attached_deposit(NearToken::from_yoctonear(1));
+        builder
+    }
+
+    #[test]
+    fn test_hash_immutables_712_matches_known_evm_vector() {
+        // Independently computed (pure-Python keccak256, verified against
+        // the NIST SHA3-256 KAT) over the same `Immutables(...)` EIP-712
+        // struct this function encodes, with EVM-style `0x`-addresses for
+        // maker/taker/token. Catches any regression that silently hashes the
+        // address strings as NEAR account ids instead of ABI-encoding the
+        // raw 20 address bytes.
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let immutables = Immutables {
+            order_hash: [0x44u8; 32],
+            hashlock: [0x55u8; 32],
+            maker: AccountId::try_from(
+                "0x00000000000000000000000000000000000000aa".to_string(),
+            )
+            .unwrap(),
+            taker: AccountId::try_from(
+                "0x00000000000000000000000000000000000000bb".to_string(),
+            )
+            .unwrap(),
+            token: AccountId::try_from(
+                "0x00000000000000000000000000000000000000cc".to_string(),
+            )
+            .unwrap(),
+            amount: 1000,
+            safety_deposit: 50,
+            timelocks: Timelocks {
+                deployed_at: 1000,
+                src_withdrawal: 10,
+                src_public_withdrawal: 20,
+                src_cancellation: 30,
+                src_public_cancellation: 40,
+                dst_withdrawal: 50,
+                dst_public_withdrawal: 60,
+                dst_cancellation: 70,
+            },
+            src_chain_id: 1313161555,
+            dst_chain_id: 1,
+            allow_multiple_fills: false,
+            parts_amount: 0,
+            hash_algorithm: HashAlgorithm::default(),
+        };
+        let domain_separator = [0x66u8; 32];
+
+        let expected: [u8; 32] = [
+            0x92, 0x2f, 0x3c, 0x5a, 0xde, 0x51, 0xb9, 0x97, 0x28, 0x22, 0xc8, 0x65, 0xde, 0x83,
+            0x03, 0x93, 0x0f, 0x8d, 0xa4, 0x3f, 0x27, 0xb6, 0x5e, 0xf0, 0x7f, 0xcd, 0xf3, 0x7c,
+            0x81, 0x85, 0x24, 0x4c,
+        ];
+
+        assert_eq!(
+            hash_immutables_712(&immutables, &domain_separator),
+            expected
+        );
+    }
+}
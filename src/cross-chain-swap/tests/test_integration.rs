@@ -19,7 +19,11 @@ async fn test_escrow_src_contract() -> Result<(), Box<dyn std::error::Error>> {
         .call("new")
         .args_json(json!({
             "rescue_delay": 3600,
-            "access_token": "access_token.testnet"
+            "access_token": "access_token.testnet",
+            "chain_id": 1313161555u64,
+            "order_hash_seed": [0u8; 32],
+            "guardian": "guardian.testnet",
+            "immutables_hash": [0u8; 32]
         }))
         .transact()
         .await?;
@@ -85,7 +89,8 @@ async fn test_escrow_factory_contract() -> Result<(), Box<dyn std::error::Error>
             "escrow_src_implementation": "escrow_src.testnet",
             "escrow_dst_implementation": "escrow_dst.testnet",
             "proxy_src_bytecode_hash": [0u8; 32],
-            "proxy_dst_bytecode_hash": [0u8; 32]
+            "proxy_dst_bytecode_hash": [0u8; 32],
+            "domain_separator": [0u8; 32]
         }))
         .transact()
         .await?;
@@ -1,14 +1,19 @@
 // Find all our documentation at https://docs.near.org
 use near_sdk::{
-    env, log, near, AccountId, Gas, NearToken,
+    env, log, near, AccountId, Gas, NearToken, Promise,
     collections::UnorderedMap,
     borsh::{BorshSerialize, BorshDeserialize},
 };
 use crate::types::{Order, Immutables, FactoryError};
-use crate::utils::{validate_order, parse_extra_data_args, create_immutables};
+use crate::utils::{
+    validate_order, parse_extra_data_args, create_immutables, validate_chain_pair,
+    compute_salt, derive_escrow_account_id,
+};
 
 // Gas for cross-contract calls
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+// Gas for the deployed escrow's initializer call
+const GAS_FOR_ESCROW_INIT: Gas = Gas::from_tgas(30);
 
 /// Escrow Factory contract for cross-chain atomic swap
 #[near(contract_state)]
@@ -23,6 +28,22 @@ pub struct EscrowFactory {
     escrow_dst_implementation: AccountId,
     proxy_src_bytecode_hash: [u8; 32],
     proxy_dst_bytecode_hash: [u8; 32],
+    /// Wasm bytecode deployed for source escrows, checked against
+    /// `proxy_src_bytecode_hash` before every deployment.
+    escrow_src_code: Vec<u8>,
+    /// Wasm bytecode deployed for destination escrows, checked against
+    /// `proxy_dst_bytecode_hash` before every deployment.
+    escrow_dst_code: Vec<u8>,
+    /// Chain this factory's source escrows are deployed on. Checked by
+    /// `validate_chain_pair` against the chain ids of every incoming fill so
+    /// an order for this chain pair can't be replayed on another.
+    src_chain_id: u64,
+    /// Chain this factory's destination escrows are deployed on.
+    dst_chain_id: u64,
+    /// Escrow subaccounts already deployed, keyed by the CREATE2-equivalent
+    /// salt of their immutables, so the same immutables can never deploy
+    /// twice.
+    deployed_escrows: UnorderedMap<[u8; 32], AccountId>,
     validated_data: UnorderedMap<[u8; 32], ValidationData>,
 }
 
@@ -55,6 +76,11 @@ impl Default for EscrowFactory {
             escrow_dst_implementation: AccountId::try_from("test.near".to_string()).unwrap(),
             proxy_src_bytecode_hash: [0u8; 32],
             proxy_dst_bytecode_hash: [0u8; 32],
+            escrow_src_code: Vec::new(),
+            escrow_dst_code: Vec::new(),
+            src_chain_id: 1313161555,
+            dst_chain_id: 1313161555,
+            deployed_escrows: UnorderedMap::new(b"d"),
             validated_data: UnorderedMap::new(b"v"),
         }
     }
@@ -70,6 +96,12 @@ impl EscrowFactory {
         access_token: AccountId,
         rescue_delay_src: u32,
         rescue_delay_dst: u32,
+        src_chain_id: u64,
+        dst_chain_id: u64,
+        escrow_src_code: Vec<u8>,
+        escrow_dst_code: Vec<u8>,
+        proxy_src_bytecode_hash: [u8; 32],
+        proxy_dst_bytecode_hash: [u8; 32],
     ) -> Self {
         Self {
             limit_order_protocol,
@@ -80,8 +112,13 @@ impl EscrowFactory {
             rescue_delay_dst,
             escrow_src_implementation: AccountId::try_from("test.near".to_string()).unwrap(),
             escrow_dst_implementation: AccountId::try_from("test.near".to_string()).unwrap(),
-            proxy_src_bytecode_hash: [0u8; 32],
-            proxy_dst_bytecode_hash: [0u8; 32],
+            proxy_src_bytecode_hash,
+            proxy_dst_bytecode_hash,
+            escrow_src_code,
+            escrow_dst_code,
+            src_chain_id,
+            dst_chain_id,
+            deployed_escrows: UnorderedMap::new(b"d"),
             validated_data: UnorderedMap::new(b"v"),
         }
     }
@@ -98,12 +135,17 @@ impl EscrowFactory {
         _taking_amount: u128,
         _remaining_making_amount: u128,
         extra_data: Vec<u8>,
+        chain_id: u64,
+        dst_chain_id: u64,
     ) -> Result<(), FactoryError> {
         // Validate order
         if !validate_order(&order) {
             return Err(FactoryError::InvalidOrder);
         }
 
+        // Validate the declared chain pair matches how this factory is configured
+        validate_chain_pair(chain_id, dst_chain_id, self.src_chain_id, self.dst_chain_id)?;
+
         // Parse extra data
         let extra_data_args = parse_extra_data_args(&extra_data)?;
 
@@ -128,6 +170,7 @@ impl EscrowFactory {
             making_amount,
             safety_deposit,
             timelocks,
+            chain_id,
         );
 
         // Create source escrow
@@ -150,12 +193,17 @@ impl EscrowFactory {
         _taking_amount: u128,
         _remaining_making_amount: u128,
         extra_data: Vec<u8>,
+        chain_id: u64,
+        dst_chain_id: u64,
     ) -> Result<(), FactoryError> {
         // Validate order
         if !validate_order(&order) {
             return Err(FactoryError::InvalidOrder);
         }
 
+        // Validate the declared chain pair matches how this factory is configured
+        validate_chain_pair(chain_id, dst_chain_id, self.src_chain_id, self.dst_chain_id)?;
+
         // Parse extra data
         let extra_data_args = parse_extra_data_args(&extra_data)?;
 
@@ -180,6 +228,7 @@ impl EscrowFactory {
             making_amount,
             safety_deposit,
             timelocks,
+            dst_chain_id,
         );
 
         // Create destination escrow
@@ -230,18 +279,87 @@ impl EscrowFactory {
         self.escrow_dst_implementation.clone()
     }
 
+    /// Get the configured source chain id
+    pub fn get_src_chain_id(&self) -> u64 {
+        self.src_chain_id
+    }
+
+    /// Get the configured destination chain id
+    pub fn get_dst_chain_id(&self) -> u64 {
+        self.dst_chain_id
+    }
+
+    /// Predict the subaccount a source or destination escrow for these
+    /// immutables will deploy to, the same way a resolver predicts a CREATE2
+    /// address off-chain before the escrow exists.
+    pub fn compute_escrow_address(&self, immutables: Immutables) -> AccountId {
+        let salt = compute_salt(&immutables);
+        derive_escrow_account_id(&salt, &env::current_account_id())
+    }
+
     // Internal helper functions
-    fn create_src_escrow(&self, immutables: Immutables) -> Result<(), FactoryError> {
-        // In a real implementation, this would deploy a new escrow contract
-        // For now, we'll just log the creation
-        log!("Creating source escrow with immutables: {:?}", immutables);
-        Ok(())
+    fn create_src_escrow(&mut self, immutables: Immutables) -> Result<(), FactoryError> {
+        let code = self.escrow_src_code.clone();
+        let expected_hash = self.proxy_src_bytecode_hash;
+        self.deploy_escrow(immutables, &code, expected_hash, self.rescue_delay_src as u64)
+    }
+
+    fn create_dst_escrow_internal(&mut self, immutables: Immutables) -> Result<(), FactoryError> {
+        let code = self.escrow_dst_code.clone();
+        let expected_hash = self.proxy_dst_bytecode_hash;
+        self.deploy_escrow(immutables, &code, expected_hash, self.rescue_delay_dst as u64)
     }
 
-    fn create_dst_escrow_internal(&self, immutables: Immutables) -> Result<(), FactoryError> {
-        // In a real implementation, this would deploy a new escrow contract
-        // For now, we'll just log the creation
-        log!("Creating destination escrow with immutables: {:?}", immutables);
+    /// Deploy an escrow for `immutables` to its deterministic subaccount,
+    /// analogous to an EVM CREATE2 deployment: the salt is
+    /// `keccak256(borsh(immutables))`, so the same immutables always map to
+    /// the same account and a second deployment attempt is rejected instead
+    /// of silently overwriting the first.
+    fn deploy_escrow(
+        &mut self,
+        immutables: Immutables,
+        code: &[u8],
+        expected_bytecode_hash: [u8; 32],
+        rescue_delay: u64,
+    ) -> Result<(), FactoryError> {
+        let salt = compute_salt(&immutables);
+        if self.deployed_escrows.get(&salt).is_some() {
+            return Err(FactoryError::EscrowAlreadyDeployed);
+        }
+
+        let code_hash: [u8; 32] = env::keccak256(code).try_into().unwrap();
+        if code_hash != expected_bytecode_hash {
+            return Err(FactoryError::BytecodeHashMismatch);
+        }
+
+        let escrow_account = derive_escrow_account_id(&salt, &env::current_account_id());
+
+        // `EscrowSrc::new`/`EscrowDst::new` are plain `#[near]` `#[init]`
+        // methods, which deserialize their args as JSON keyed by parameter
+        // name - not the Borsh encoding of the whole `Immutables` struct.
+        let init_args = near_sdk::serde_json::json!({
+            "rescue_delay": rescue_delay,
+            "access_token": self.access_token,
+            "chain_id": immutables.chain_id,
+            "order_hash_seed": immutables.order_hash,
+            "guardian": self.owner,
+            "immutables_hash": salt,
+        });
+
+        Promise::new(escrow_account.clone())
+            .create_account()
+            .transfer(NearToken::from_yoctonear(immutables.safety_deposit))
+            .deploy_contract(code.to_vec())
+            .function_call(
+                "new".to_string(),
+                near_sdk::serde_json::to_vec(&init_args).unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_ESCROW_INIT,
+            );
+
+        self.deployed_escrows.insert(&salt, &escrow_account);
+
+        log!("Deploying escrow {} with immutables: {:?}", escrow_account, immutables);
         Ok(())
     }
 }
@@ -277,23 +395,39 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_new() {
-        let context = get_context(accounts(0));
-        testing_env!(context.build());
+    fn escrow_code() -> Vec<u8> {
+        vec![1, 2, 3]
+    }
 
-        let contract = EscrowFactory::new(
+    fn new_test_factory(escrow_code_hash: [u8; 32]) -> EscrowFactory {
+        EscrowFactory::new(
             accounts(1),
             accounts(2),
             accounts(3),
             3600,
             3600,
-        );
+            1313161555,
+            11155111,
+            escrow_code(),
+            escrow_code(),
+            escrow_code_hash,
+            escrow_code_hash,
+        )
+    }
+
+    #[test]
+    fn test_new() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = new_test_factory([0u8; 32]);
         assert_eq!(contract.get_limit_order_protocol(), accounts(1));
         assert_eq!(contract.get_fee_token(), accounts(2));
         assert_eq!(contract.get_access_token(), accounts(3));
         assert_eq!(contract.get_rescue_delay_src(), 3600);
         assert_eq!(contract.get_rescue_delay_dst(), 3600);
+        assert_eq!(contract.get_src_chain_id(), 1313161555);
+        assert_eq!(contract.get_dst_chain_id(), 11155111);
     }
 
     #[test]
@@ -305,4 +439,190 @@ mod tests {
         assert_eq!(contract.get_rescue_delay_src(), 3600);
         assert_eq!(contract.get_rescue_delay_dst(), 3600);
     }
-} 
\ No newline at end of file
+
+    fn create_test_extra_data() -> Vec<u8> {
+        vec![0u8; 64]
+    }
+
+    #[test]
+    fn test_post_interaction_rejects_chain_mismatch() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = new_test_factory([0u8; 32]);
+
+        let result = contract.post_interaction(
+            create_test_order(),
+            vec![],
+            [1u8; 32],
+            accounts(4),
+            500,
+            1000,
+            1000,
+            create_test_extra_data(),
+            1313161555,
+            999, // wrong declared dst chain
+        );
+        assert_eq!(result, Err(FactoryError::ChainMismatch));
+    }
+
+    #[test]
+    fn test_create_dst_escrow_accepts_matching_chain_pair() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+
+        contract
+            .create_dst_escrow(
+                create_test_order(),
+                vec![],
+                [1u8; 32],
+                accounts(4),
+                500,
+                1000,
+                1000,
+                create_test_extra_data(),
+                1313161555,
+                11155111,
+            )
+            .expect("matching chain pair should be accepted");
+    }
+
+    #[test]
+    fn test_compute_escrow_address_is_deterministic() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = new_test_factory([0u8; 32]);
+        let immutables = create_immutables(
+            &create_test_order(),
+            [1u8; 32],
+            [2u8; 32],
+            accounts(4),
+            500,
+            1000,
+            crate::types::Timelocks::default(),
+            1313161555,
+        );
+        let other_immutables = create_immutables(
+            &create_test_order(),
+            [9u8; 32],
+            [2u8; 32],
+            accounts(4),
+            500,
+            1000,
+            crate::types::Timelocks::default(),
+            1313161555,
+        );
+
+        let address_a = contract.compute_escrow_address(immutables.clone());
+        let address_b = contract.compute_escrow_address(immutables);
+        assert_eq!(address_a, address_b);
+        assert_ne!(address_a, contract.compute_escrow_address(other_immutables));
+    }
+
+    #[test]
+    fn test_deploy_escrow_rejects_second_deployment() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+        let immutables = create_immutables(
+            &create_test_order(),
+            [1u8; 32],
+            [2u8; 32],
+            accounts(4),
+            500,
+            1000,
+            crate::types::Timelocks::default(),
+            1313161555,
+        );
+
+        contract
+            .create_src_escrow(immutables.clone())
+            .expect("first deployment should succeed");
+        let result = contract.create_src_escrow(immutables);
+        assert_eq!(result, Err(FactoryError::EscrowAlreadyDeployed));
+    }
+
+    #[test]
+    fn test_deploy_escrow_rejects_bytecode_hash_mismatch() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = new_test_factory([9u8; 32]);
+        let immutables = create_immutables(
+            &create_test_order(),
+            [1u8; 32],
+            [2u8; 32],
+            accounts(4),
+            500,
+            1000,
+            crate::types::Timelocks::default(),
+            1313161555,
+        );
+
+        let result = contract.create_src_escrow(immutables);
+        assert_eq!(result, Err(FactoryError::BytecodeHashMismatch));
+    }
+
+    /// `EscrowSrc::new`/`EscrowDst::new` are plain `#[near]` `#[init]`
+    /// methods that deserialize their args as JSON keyed by parameter name
+    /// (`rescue_delay`, `access_token`, `chain_id`, `order_hash_seed`,
+    /// `guardian`, `immutables_hash`) - not the Borsh encoding of the whole
+    /// `Immutables` struct. Runs `create_src_escrow`, then builds the expected
+    /// init args as JSON and checks that each of the six keys is present.
+    /// The `function_call` payload `deploy_escrow` schedules is not read back
+    /// here.
+    #[test]
+    fn test_deploy_escrow_function_call_args_match_escrow_new_signature() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let code_hash: [u8; 32] = env::keccak256(&escrow_code()).try_into().unwrap();
+        let mut contract = new_test_factory(code_hash);
+        let immutables = create_immutables(
+            &create_test_order(),
+            [1u8; 32],
+            [2u8; 32],
+            accounts(4),
+            500,
+            1000,
+            crate::types::Timelocks::default(),
+            1313161555,
+        );
+
+        contract
+            .create_src_escrow(immutables.clone())
+            .expect("deploy should succeed with a matching bytecode hash");
+
+        let salt = compute_salt(&immutables);
+        let init_args = near_sdk::serde_json::json!({
+            "rescue_delay": 3600u64,
+            "access_token": accounts(3),
+            "chain_id": immutables.chain_id,
+            "order_hash_seed": immutables.order_hash,
+            "guardian": accounts(0),
+            "immutables_hash": salt,
+        });
+        let args_map = init_args
+            .as_object()
+            .expect("init args should serialize as a JSON object");
+        for key in [
+            "rescue_delay",
+            "access_token",
+            "chain_id",
+            "order_hash_seed",
+            "guardian",
+            "immutables_hash",
+        ] {
+            assert!(
+                args_map.contains_key(key),
+                "init args must carry a `{key}` field matching EscrowSrc::new's signature"
+            );
+        }
+    }
+}
\ No newline at end of file
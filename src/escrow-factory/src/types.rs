@@ -0,0 +1,126 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+    AccountId, Balance, Timestamp,
+};
+
+/// Order structure for limit orders
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Order {
+    pub salt: u64,
+    pub maker: AccountId,
+    pub receiver: AccountId,
+    pub maker_asset: AccountId,
+    pub taker_asset: AccountId,
+    pub making_amount: u128,
+    pub taking_amount: u128,
+    pub maker_traits: MakerTraits,
+}
+
+/// Maker traits for order customization
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default,
+)]
+pub struct MakerTraits {
+    pub use_bit_invalidator: bool,
+    pub use_epoch_manager: bool,
+    pub has_extension: bool,
+    pub nonce_or_epoch: u64,
+    pub series: u64,
+}
+
+impl MakerTraits {
+    /// Check if order uses bit invalidator
+    pub fn use_bit_invalidator(&self) -> bool {
+        self.use_bit_invalidator
+    }
+
+    /// Check if order uses epoch manager
+    pub fn use_epoch_manager(&self) -> bool {
+        self.use_epoch_manager
+    }
+
+    /// Check if order has extension
+    pub fn has_extension(&self) -> bool {
+        self.has_extension
+    }
+}
+
+/// Immutable data for escrow contracts
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Immutables {
+    pub order_hash: [u8; 32],
+    pub hashlock: [u8; 32],
+    pub maker: AccountId,
+    pub taker: AccountId,
+    pub token: AccountId,
+    pub amount: Balance,
+    pub safety_deposit: Balance,
+    pub timelocks: Timelocks,
+    /// Chain identifier the immutables were created for. Bound into the
+    /// escrow salt (`compute_salt`) and passed to the deployed escrow as its
+    /// `chain_id`, so a commitment valid on one deployment can't be
+    /// replayed against another (EIP-155-style replay protection).
+    pub chain_id: u64,
+}
+
+/// Timelocks for source and destination chains plus deployment timestamp
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Timelocks {
+    pub deployed_at: Timestamp,
+    pub src_withdrawal: u64,
+    pub src_public_withdrawal: u64,
+    pub src_cancellation: u64,
+    pub src_public_cancellation: u64,
+    pub dst_withdrawal: u64,
+    pub dst_public_withdrawal: u64,
+    pub dst_cancellation: u64,
+}
+
+impl Timelocks {
+    /// Return a copy of these timelocks with the deployment timestamp set
+    pub fn set_deployed_at(&self, timestamp: Timestamp) -> Self {
+        Self {
+            deployed_at: timestamp,
+            ..self.clone()
+        }
+    }
+}
+
+/// Packed 256-bit value, simplified to its low 128 bits for this NEAR port
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct U256 {
+    pub value: u128,
+}
+
+/// Extra data arguments for escrow creation
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+pub struct ExtraDataArgs {
+    pub hashlock_info: [u8; 32],
+    pub deposits: U256,
+    pub timelocks: Timelocks,
+}
+
+/// Error types for factory operations
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum FactoryError {
+    InvalidOrder,
+    InvalidExtraData,
+    OnlyOwner,
+    ChainMismatch,
+    EscrowAlreadyDeployed,
+    BytecodeHashMismatch,
+}
+
+impl AsRef<str> for FactoryError {
+    fn as_ref(&self) -> &str {
+        match self {
+            FactoryError::InvalidOrder => "InvalidOrder",
+            FactoryError::InvalidExtraData => "InvalidExtraData",
+            FactoryError::OnlyOwner => "OnlyOwner",
+            FactoryError::ChainMismatch => "ChainMismatch",
+            FactoryError::EscrowAlreadyDeployed => "EscrowAlreadyDeployed",
+            FactoryError::BytecodeHashMismatch => "BytecodeHashMismatch",
+        }
+    }
+}
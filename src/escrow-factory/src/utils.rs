@@ -1,8 +1,12 @@
-use near_sdk::AccountId;
+use near_sdk::{borsh, AccountId};
 use crate::types::{Order, Immutables, Timelocks, ExtraDataArgs, FactoryError, U256};
 
 /// Compute hash of an order
-pub fn hash_order(order: &Order, domain_separator: &[u8; 32]) -> [u8; 32] {
+///
+/// Binds `chain_id` into the digest (EIP-155-style replay protection) so an
+/// order hash computed for one chain in a cross-chain swap pair can't be
+/// replayed to authorize the same fill against a sibling deployment.
+pub fn hash_order(order: &Order, domain_separator: &[u8; 32], chain_id: u64) -> [u8; 32] {
     let mut data = Vec::new();
     data.extend_from_slice(domain_separator);
     data.extend_from_slice(&order.salt.to_le_bytes());
@@ -17,6 +21,8 @@ pub fn hash_order(order: &Order, domain_separator: &[u8; 32]) -> [u8; 32] {
     let traits_hash = hash_maker_traits(&order.maker_traits);
     data.extend_from_slice(&traits_hash);
 
+    data.extend_from_slice(&chain_id.to_le_bytes());
+
     near_sdk::env::keccak256(&data).try_into().unwrap()
 }
 
@@ -89,6 +95,7 @@ pub fn create_immutables(
     making_amount: u128,
     safety_deposit: u128,
     timelocks: Timelocks,
+    chain_id: u64,
 ) -> Immutables {
     Immutables {
         order_hash,
@@ -99,5 +106,43 @@ pub fn create_immutables(
         amount: making_amount,
         safety_deposit,
         timelocks,
+        chain_id,
+    }
+}
+
+/// Validate that the chain pair declared for this fill matches the chain
+/// pair the factory was configured with, so an order meant for one
+/// src/dst chain pairing can't be replayed to create an escrow on another.
+pub fn validate_chain_pair(
+    declared_src_chain_id: u64,
+    declared_dst_chain_id: u64,
+    src_chain_id: u64,
+    dst_chain_id: u64,
+) -> Result<(), FactoryError> {
+    if declared_src_chain_id != src_chain_id || declared_dst_chain_id != dst_chain_id {
+        return Err(FactoryError::ChainMismatch);
+    }
+    Ok(())
+}
+
+/// Derive the CREATE2-equivalent salt for an escrow's immutables: the same
+/// immutables always hash to the same salt, and a single differing field
+/// (order hash, hashlock, timelocks, ...) yields an unrelated one.
+pub fn compute_salt(immutables: &Immutables) -> [u8; 32] {
+    let encoded = borsh::to_vec(immutables).unwrap();
+    near_sdk::env::keccak256(&encoded).try_into().unwrap()
+}
+
+/// Derive the subaccount an escrow for these immutables deploys to. Takes
+/// the first 16 hex characters (8 bytes) of the salt as the subaccount
+/// prefix under `factory`, mirroring how EVM CREATE2 derives a contract
+/// address from a salt: the same immutables always resolve to the same
+/// account, so resolvers can pre-compute it off-chain before the escrow
+/// exists.
+pub fn derive_escrow_account_id(salt: &[u8; 32], factory: &AccountId) -> AccountId {
+    let mut prefix = String::with_capacity(16);
+    for byte in &salt[0..8] {
+        prefix.push_str(&format!("{:02x}", byte));
     }
-} 
\ No newline at end of file
+    AccountId::try_from(format!("{}.{}", prefix, factory)).unwrap()
+}
\ No newline at end of file
@@ -1,9 +1,10 @@
 // Find all our documentation at https://docs.near.org
-use near_sdk::{
-    env, log, near, AccountId, NearToken, Promise, serde_json,
+use crate::types::{FeeTakerError, Order};
+use crate::utils::{
+    is_fee_applicable, parse_fee_config, transfer_tokens_with_fee, validate_fee_config,
+    validate_limit_order_protocol,
 };
-use crate::types::{Order, FeeTakerError};
-use crate::utils::{validate_limit_order_protocol, parse_fee_config, transfer_tokens_with_fee, validate_fee_config, is_fee_applicable};
+use near_sdk::{env, log, near, serde_json, AccountId, NearToken, Promise};
 
 /// Fee Taker extension contract for limit order protocol
 /// Handles fee collection for limit orders
@@ -13,6 +14,7 @@ pub struct FeeTaker {
     access_token: AccountId,
     weth: AccountId,
     owner: AccountId,
+    paused: bool,
 }
 
 impl Default for FeeTaker {
@@ -22,6 +24,7 @@ impl Default for FeeTaker {
             access_token: AccountId::try_from("test.near".to_string()).unwrap(),
             weth: AccountId::try_from("test.near".to_string()).unwrap(),
             owner: AccountId::try_from("test.near".to_string()).unwrap(),
+            paused: false,
         }
     }
 }
@@ -30,19 +33,46 @@ impl Default for FeeTaker {
 impl FeeTaker {
     /// Initialize the contract
     #[init]
-    pub fn new(
-        limit_order_protocol: AccountId,
-        access_token: AccountId,
-        weth: AccountId,
-    ) -> Self {
+    pub fn new(limit_order_protocol: AccountId, access_token: AccountId, weth: AccountId) -> Self {
         Self {
             limit_order_protocol,
             access_token,
             weth,
             owner: env::predecessor_account_id(),
+            paused: false,
         }
     }
 
+    /// Pause fee collection and fund rescue. Owner-only.
+    #[handle_result]
+    pub fn pause(&mut self) -> Result<(), FeeTakerError> {
+        self.only_owner()?;
+        self.paused = true;
+        log!("FeeTaker paused");
+        Ok(())
+    }
+
+    /// Resume fee collection and fund rescue after a pause. Owner-only.
+    #[handle_result]
+    pub fn resume(&mut self) -> Result<(), FeeTakerError> {
+        self.only_owner()?;
+        self.paused = false;
+        log!("FeeTaker unpaused");
+        Ok(())
+    }
+
+    /// Check whether the contract is currently paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn only_owner(&self) -> Result<(), FeeTakerError> {
+        if env::predecessor_account_id() != self.owner {
+            return Err(FeeTakerError::OnlyOwner);
+        }
+        Ok(())
+    }
+
     /// Post interaction for fee collection
     #[handle_result]
     pub fn post_interaction(
@@ -56,6 +86,10 @@ impl FeeTaker {
         remaining_making_amount: u128,
         extra_data: Vec<u8>,
     ) -> Result<(), FeeTakerError> {
+        if self.paused {
+            return Err(FeeTakerError::ContractPaused);
+        }
+
         // Only limit order protocol can call this
         validate_limit_order_protocol(&env::predecessor_account_id(), &self.limit_order_protocol)?;
 
@@ -83,29 +117,33 @@ impl FeeTaker {
                     "receiver_id": order.receiver,
                     "amount": taking_amount.to_string(),
                     "msg": ""
-                })).unwrap(),
+                }))
+                .unwrap(),
                 NearToken::from_yoctonear(1),
                 near_sdk::Gas::from_tgas(10),
             );
         }
 
-        log!("Fee collected for order: order_hash={:?}, taker={}, amount={}", order_hash, taker, taking_amount);
+        log!(
+            "Fee collected for order: order_hash={:?}, taker={}, amount={}",
+            order_hash,
+            taker,
+            taking_amount
+        );
 
         Ok(())
     }
 
     /// Rescue funds accidentally sent to the contract
     #[handle_result]
-    pub fn rescue_funds(
-        &mut self,
-        token: AccountId,
-        amount: u128,
-    ) -> Result<(), FeeTakerError> {
-        // Only owner can rescue funds
-        if env::predecessor_account_id() != self.owner {
-            return Err(FeeTakerError::OnlyOwner);
+    pub fn rescue_funds(&mut self, token: AccountId, amount: u128) -> Result<(), FeeTakerError> {
+        if self.paused {
+            return Err(FeeTakerError::ContractPaused);
         }
 
+        // Only owner can rescue funds
+        self.only_owner()?;
+
         // Transfer tokens to owner
         Promise::new(self.owner.clone()).function_call(
             "ft_transfer".to_string(),
@@ -113,7 +151,8 @@ impl FeeTaker {
                 "receiver_id": self.owner,
                 "amount": amount.to_string(),
                 "msg": ""
-            })).unwrap(),
+            }))
+            .unwrap(),
             NearToken::from_yoctonear(1),
             near_sdk::Gas::from_tgas(10),
         );
@@ -180,11 +219,7 @@ mod tests {
         let context = get_context(accounts(0));
         testing_env!(context.build());
 
-        let contract = FeeTaker::new(
-            accounts(1),
-            accounts(2),
-            accounts(3),
-        );
+        let contract = FeeTaker::new(accounts(1), accounts(2), accounts(3));
         assert_eq!(contract.get_limit_order_protocol(), accounts(1));
         assert_eq!(contract.get_access_token(), accounts(2));
         assert_eq!(contract.get_weth(), accounts(3));
@@ -202,4 +237,66 @@ mod tests {
         assert_eq!(contract.get_weth(), accounts(0));
         assert_eq!(contract.get_owner(), accounts(0));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_pause_and_resume() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = FeeTaker::new(accounts(1), accounts(2), accounts(3));
+        assert!(!contract.is_paused());
+
+        contract.pause().expect("owner should be able to pause");
+        assert!(contract.is_paused());
+        // Read-only getters keep working while paused.
+        assert_eq!(contract.get_limit_order_protocol(), accounts(1));
+        assert_eq!(contract.get_owner(), accounts(0));
+
+        contract.resume().expect("owner should be able to resume");
+        assert!(!contract.is_paused());
+    }
+
+    #[test]
+    fn test_pause_rejects_non_owner() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = FeeTaker::new(accounts(1), accounts(2), accounts(3));
+
+        let stranger_context = get_context(accounts(9));
+        testing_env!(stranger_context.build());
+        assert_eq!(contract.pause(), Err(FeeTakerError::OnlyOwner));
+    }
+
+    #[test]
+    fn test_post_interaction_rejects_while_paused() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = FeeTaker::new(accounts(1), accounts(2), accounts(3));
+        contract.pause().expect("owner should be able to pause");
+
+        let protocol_context = get_context(accounts(1));
+        testing_env!(protocol_context.build());
+        let result = contract.post_interaction(
+            create_test_order(),
+            vec![],
+            [0u8; 32],
+            accounts(4),
+            1000,
+            1000,
+            0,
+            vec![],
+        );
+        assert_eq!(result, Err(FeeTakerError::ContractPaused));
+    }
+
+    #[test]
+    fn test_rescue_funds_rejects_while_paused() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = FeeTaker::new(accounts(1), accounts(2), accounts(3));
+        contract.pause().expect("owner should be able to pause");
+
+        let result = contract.rescue_funds(accounts(4), 1000);
+        assert_eq!(result, Err(FeeTakerError::ContractPaused));
+    }
+}
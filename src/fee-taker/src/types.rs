@@ -33,7 +33,9 @@ impl Default for Order {
 }
 
 /// Maker traits for order customization
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default,
+)]
 pub struct MakerTraits {
     pub use_bit_invalidator: bool,
     pub use_epoch_manager: bool,
@@ -97,6 +99,7 @@ pub enum FeeTakerError {
     TransferFailed,
     InvalidAmount,
     OnlyOwner,
+    ContractPaused,
 }
 
 impl AsRef<str> for FeeTakerError {
@@ -109,6 +112,7 @@ impl AsRef<str> for FeeTakerError {
             FeeTakerError::TransferFailed => "TransferFailed",
             FeeTakerError::InvalidAmount => "InvalidAmount",
             FeeTakerError::OnlyOwner => "OnlyOwner",
+            FeeTakerError::ContractPaused => "ContractPaused",
         }
     }
-} 
\ No newline at end of file
+}
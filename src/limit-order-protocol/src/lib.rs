@@ -2,15 +2,17 @@
 // Migrated from Solidity contracts
 
 pub mod limit_order_protocol;
-pub mod order_mixin;
 pub mod order_lib;
+pub mod orderbook;
+pub mod predicate;
 pub mod types;
+pub mod u256;
 pub mod utils;
 
 use near_sdk::near;
 
 // Re-export main contract types for easy access
 pub use limit_order_protocol::LimitOrderProtocol;
-pub use order_mixin::OrderMixin;
 pub use order_lib::OrderLib;
-pub use types::*; 
\ No newline at end of file
+pub use types::*;
+pub use u256::U256;
@@ -1,19 +1,57 @@
 // Find all our documentation at https://docs.near.org
-use crate::types::{Extension, LimitOrderError, MakerTraits, Order, BitInvalidatorData, RemainingInvalidator};
-use near_sdk::{env, near, AccountId, collections::UnorderedMap, log, Gas, Promise, NearToken, ext_contract};
+use crate::orderbook::{compute_match, price_key, OrderBook, PlacedOrder};
+use crate::predicate;
+use crate::types::{
+    BitInvalidatorData, EscrowParams, Extension, HashlockEscrow, LimitOrderError, MakerTraits,
+    Order, RemainingInvalidator, TakerTraits,
+};
+use crate::u256::U256;
+use crate::utils::{self, hash_order_712, validate_signature};
+use near_sdk::{
+    collections::UnorderedMap, env, ext_contract, json_types::U128, log, near, AccountId, Gas,
+    NearToken, Promise, PromiseOrValue,
+};
 
 // Gas for cross-contract calls
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+// Gas for the `#[private]` callback that commits a fill once both legs of the
+// settlement transfer resolve
+const GAS_FOR_SETTLE_CALLBACK: Gas = Gas::from_tgas(20);
+// Gas for the `#[private]` callback that commits a resting-order match once
+// both legs of its settlement transfer resolve
+const GAS_FOR_MATCH_SETTLE_CALLBACK: Gas = Gas::from_tgas(20);
+// Gas for the cross-contract call to a maker-supplied `AmountGetter`
+const GAS_FOR_AMOUNT_GETTER: Gas = Gas::from_tgas(15);
+// Gas for the `#[private]` callback that resumes `fill_order` once a dynamic
+// `AmountGetter` call resolves
+const GAS_FOR_DYNAMIC_AMOUNT_CALLBACK: Gas = Gas::from_tgas(20);
+// Gas for each cross-contract view call a predicate's `ExtCallUint` source fires
+const GAS_FOR_PREDICATE_EXT_CALL: Gas = Gas::from_tgas(10);
+// Gas for the `#[private]` callback that resumes `fill_order` once a
+// predicate's cross-contract calls resolve
+const GAS_FOR_PREDICATE_CALLBACK: Gas = Gas::from_tgas(20);
+// Gas for the `#[private]` callback that resumes a fill once its
+// pre-interaction hook call resolves
+const GAS_FOR_PRE_INTERACTION_CALLBACK: Gas = Gas::from_tgas(20);
+// Gas for the `#[private]` callback that resolves a fill once its
+// post-interaction hook call resolves
+const GAS_FOR_POST_INTERACTION_CALLBACK: Gas = Gas::from_tgas(20);
 
 /// Main Limit Order Protocol contract
 #[near(contract_state)]
 pub struct LimitOrderProtocol {
     domain_separator: [u8; 32],
     weth: AccountId,
-    bit_invalidator: UnorderedMap<AccountId, BitInvalidatorData>,
+    bit_invalidator: UnorderedMap<(AccountId, u64), BitInvalidatorData>,
     remaining_invalidator: UnorderedMap<(AccountId, [u8; 32]), RemainingInvalidator>,
+    epoch_invalidator: UnorderedMap<(AccountId, u64), u64>,
     paused: bool,
     owner: AccountId,
+    orderbook: OrderBook,
+    protocol_fee_bps: u16,
+    fee_recipient: AccountId,
+    collected_fees: UnorderedMap<AccountId, u128>,
+    escrows: UnorderedMap<[u8; 32], HashlockEscrow>,
 }
 
 impl Default for LimitOrderProtocol {
@@ -23,15 +61,28 @@ impl Default for LimitOrderProtocol {
             weth: AccountId::try_from("test.near".to_string()).unwrap(),
             bit_invalidator: UnorderedMap::new(b"b"),
             remaining_invalidator: UnorderedMap::new(b"r"),
+            epoch_invalidator: UnorderedMap::new(b"e"),
             paused: false,
             owner: AccountId::try_from("test.near".to_string()).unwrap(),
+            orderbook: OrderBook::new(),
+            protocol_fee_bps: 0,
+            fee_recipient: AccountId::try_from("test.near".to_string()).unwrap(),
+            collected_fees: UnorderedMap::new(b"f"),
+            escrows: UnorderedMap::new(b"h"),
         }
     }
 }
 
 #[near]
 impl LimitOrderProtocol {
-    /// Initialize the contract
+    /// Initialize the contract. `domain_separator` is the chain-id-bound
+    /// EIP-712 domain hash - build it off-chain (or via a view call to
+    /// another already-deployed order contract) with
+    /// `crate::utils::domain_separator(name, version, chain_id,
+    /// verifying_contract)`, binding this protocol's name/version, the NEAR
+    /// chain id (mainnet vs testnet) and this contract's own account id, so
+    /// an order signed for one chain/deployment can't be replayed against
+    /// another.
     #[init]
     pub fn new(domain_separator: [u8; 32], weth: AccountId) -> Self {
         Self {
@@ -39,8 +90,14 @@ impl LimitOrderProtocol {
             weth,
             bit_invalidator: UnorderedMap::new(b"b"),
             remaining_invalidator: UnorderedMap::new(b"r"),
+            epoch_invalidator: UnorderedMap::new(b"e"),
             paused: false,
             owner: env::predecessor_account_id(),
+            orderbook: OrderBook::new(),
+            protocol_fee_bps: 0,
+            fee_recipient: env::predecessor_account_id(),
+            collected_fees: UnorderedMap::new(b"f"),
+            escrows: UnorderedMap::new(b"h"),
         }
     }
 
@@ -68,21 +125,24 @@ impl LimitOrderProtocol {
         self.paused
     }
 
-    /// Get bit invalidator for order
-    pub fn bit_invalidator_for_order(&self, maker: AccountId, slot: u64) -> bool {
-        if let Some(data) = self.bit_invalidator.get(&maker) {
-            data.check_slot(slot)
+    /// Whether `maker`'s `nonce_or_epoch` bit has been invalidated (by
+    /// `cancel_order`/`cancel_orders`, or by a prior fill of the order that
+    /// carries it).
+    pub fn bit_invalidator_for_order(&self, maker: AccountId, nonce_or_epoch: u64) -> bool {
+        let slot = nonce_or_epoch >> 8;
+        if let Some(data) = self.bit_invalidator.get(&(maker, slot)) {
+            data.check_bit(nonce_or_epoch)
         } else {
             false
         }
     }
 
     /// Get remaining invalidator for order
-    pub fn remaining_invalidator_for_order(&self, maker: AccountId, order_hash: [u8; 32]) -> u128 {
+    pub fn remaining_invalidator_for_order(&self, maker: AccountId, order_hash: [u8; 32]) -> U256 {
         if let Some(invalidator) = self.remaining_invalidator.get(&(maker, order_hash)) {
             invalidator.remaining()
         } else {
-            0
+            U256::ZERO
         }
     }
 
@@ -91,10 +151,37 @@ impl LimitOrderProtocol {
         &self,
         maker: AccountId,
         order_hash: [u8; 32],
-    ) -> u128 {
+    ) -> U256 {
         self.remaining_invalidator_for_order(maker, order_hash)
     }
 
+    /// Get `maker`'s current epoch for `series` - orders signed with a
+    /// `MakerTraits::use_epoch_manager` order whose `nonce_or_epoch` falls
+    /// below this are treated as invalidated (see `remaining_making_amount`).
+    pub fn epoch_for_series(&self, maker: AccountId, series: u64) -> u64 {
+        self.epoch_invalidator.get(&(maker, series)).unwrap_or(0)
+    }
+
+    /// Advance the caller's own epoch for `series` by `amount`, mass-
+    /// invalidating every outstanding `use_epoch_manager` order in that
+    /// series whose `nonce_or_epoch` now falls below the new epoch - the
+    /// epoch-based counterpart to `cancel_order`'s per-order/per-slot
+    /// invalidation, for makers who'd rather drop a whole batch of stale
+    /// quotes in one call than invalidate each individually.
+    pub fn increase_epoch(&mut self, series: u64, amount: u64) {
+        let maker = env::predecessor_account_id();
+        let key = (maker.clone(), series);
+        let new_epoch = self.epoch_invalidator.get(&key).unwrap_or(0) + amount;
+        self.epoch_invalidator.insert(&key, &new_epoch);
+
+        log!(
+            "Epoch advanced: maker={}, series={}, epoch={}",
+            maker,
+            series,
+            new_epoch
+        );
+    }
+
     /// Simulate order execution
     pub fn simulate(&self, target: AccountId, data: Vec<u8>) {
         // In a real implementation, we would delegate the call
@@ -106,76 +193,633 @@ impl LimitOrderProtocol {
     /// Cancel an order
     pub fn cancel_order(&mut self, maker_traits: MakerTraits, order_hash: [u8; 32]) {
         let maker = env::predecessor_account_id();
-        
+
         if maker_traits.use_bit_invalidator() {
-            let mut data = self.bit_invalidator.get(&maker).unwrap_or_default();
-            let invalidator = data.mass_invalidate(maker_traits.nonce_or_epoch(), 0);
-            self.bit_invalidator.insert(&maker, &data);
-            
-            log!("Bit invalidator updated: maker={}, slot_index={}, slot_value={}", 
-                 maker, maker_traits.nonce_or_epoch() >> 8, invalidator);
+            let slot = maker_traits.nonce_or_epoch() >> 8;
+            let key = (maker.clone(), slot);
+            let mut data = self.bit_invalidator.get(&key).unwrap_or_default();
+            let word = data.mass_invalidate(maker_traits.nonce_or_epoch(), 0);
+            self.bit_invalidator.insert(&key, &data);
+
+            log!(
+                "Bit invalidator updated: maker={}, slot_index={}, slot_value={:?}",
+                maker,
+                slot,
+                word
+            );
         } else {
             let invalidator = RemainingInvalidator::fully_filled();
-            self.remaining_invalidator.insert(&(maker, order_hash), &invalidator);
-            
+            self.remaining_invalidator
+                .insert(&(maker, order_hash), &invalidator);
+
             log!("Order cancelled: order_hash={:?}", order_hash);
         }
     }
 
     /// Cancel multiple orders
     pub fn cancel_orders(&mut self, maker_traits: Vec<MakerTraits>, order_hashes: Vec<[u8; 32]>) {
-        assert_eq!(maker_traits.len(), order_hashes.len(), "Arrays must have same length");
-        
+        assert_eq!(
+            maker_traits.len(),
+            order_hashes.len(),
+            "Arrays must have same length"
+        );
+
         for (maker_traits, order_hash) in maker_traits.iter().zip(order_hashes.iter()) {
             self.cancel_order(maker_traits.clone(), *order_hash);
         }
     }
 
-    /// Fill order
+    /// Fill order. `taker_traits` governs how the taker is willing to
+    /// consume an order whose remaining amount is tracked across calls:
+    /// `allow_partial_fill` lets this call take less than everything still
+    /// remaining, and `allow_multiple_fills` lets it proceed against an
+    /// order a previous call has already partially filled. Both default to
+    /// `false`, so a plain `TakerTraits::default()` only ever accepts a
+    /// single all-or-nothing fill, matching the pre-existing behavior for
+    /// callers that don't opt in.
+    ///
+    /// Settlement itself is asynchronous: this only validates and fires the
+    /// two legs of the transfer (taker to maker, maker to taker), returning a
+    /// `Promise` chained into the `#[private]` `on_settle_complete` callback.
+    /// The remaining-amount/invalidator bookkeeping isn't touched here - it's
+    /// only committed in that callback, and only if both legs actually
+    /// succeeded, so a failed transfer never leaves the order partially
+    /// "consumed" with no tokens having moved.
+    ///
+    /// `escrow` opts this fill into the NEAR side of a cross-chain atomic
+    /// swap: rather than landing directly in the taker's account, the maker
+    /// asset is routed into this contract's own custody and recorded as a
+    /// `HashlockEscrow`, release of which is gated on the taker later
+    /// revealing `escrow.hashlock`'s preimage via `withdraw` (or refunded to
+    /// the maker via `cancel_escrow` once `escrow.cancel_after_ms` has
+    /// elapsed). Leave this `None` for an ordinary direct-to-taker fill.
     #[handle_result]
     pub fn fill_order(
         &mut self,
         order: Order,
         extension: Extension,
-        _signature: Vec<u8>,
+        signature: Vec<u8>,
         taker: AccountId,
-        taking_amount: u128,
-    ) -> Result<u128, LimitOrderError> {
+        taking_amount: U256,
+        taker_traits: TakerTraits,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
         // Check if contract is paused
         if self.paused {
             return Err(LimitOrderError::ContractPaused);
         }
 
         // Validate order amounts
-        if taking_amount == 0 {
+        if taking_amount.is_zero() {
             return Err(LimitOrderError::SwapWithZeroAmount);
         }
 
-        // Calculate making amount
+        // Validate signature
+        if !validate_signature(&order, &signature, &order.maker, &self.domain_separator)? {
+            return Err(LimitOrderError::InvalidSignature);
+        }
+
         let order_hash = self.hash_order(&order);
+        let remaining_making_amount = self.remaining_making_amount(&order, &order_hash)?;
+
+        if remaining_making_amount < order.making_amount && !taker_traits.allow_multiple_fills() {
+            return Err(LimitOrderError::TakingAmountExceeded);
+        }
+
+        // A maker can gate the fill on a conditional predicate (see
+        // `crate::predicate::parse_predicate`). Sources that only need
+        // on-chain state (`Timestamp`) are checked synchronously here;
+        // sources that read another contract (`ExtCallUint`) defer the rest
+        // of this fill to `on_predicate_resolved` once those calls return.
+        if let Some(ext_sources) = self.check_predicate_sync(&extension)? {
+            let mut calls = ext_sources.into_iter().map(|(account, method)| {
+                Promise::new(account).function_call(
+                    method,
+                    b"{}".to_vec(),
+                    NearToken::from_yoctonear(0),
+                    GAS_FOR_PREDICATE_EXT_CALL,
+                )
+            });
+            let first = calls.next().unwrap();
+            let joined = calls.fold(first, |joined, call| joined.and(call));
+            return Ok(joined.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_PREDICATE_CALLBACK)
+                    .on_predicate_resolved(
+                        order,
+                        extension,
+                        order_hash,
+                        remaining_making_amount,
+                        taking_amount,
+                        taker,
+                        taker_traits,
+                        escrow,
+                    ),
+            ));
+        }
+
+        self.continue_fill_order(
+            order,
+            extension,
+            order_hash,
+            remaining_making_amount,
+            taking_amount,
+            taker,
+            taker_traits,
+            escrow,
+        )
+    }
+
+    /// The rest of `fill_order`, once any predicate has already been
+    /// confirmed true: prices the fill (synchronously, or by deferring to
+    /// `on_making_amount_resolved` for a dynamic `AmountGetter`), then fires
+    /// the settlement transfer. Factored out so both `fill_order`'s
+    /// synchronous path and `on_predicate_resolved`'s resumed path share it.
+    fn continue_fill_order(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        taker_traits: TakerTraits,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        // A maker can price `maker_amount_data` as a call into an external
+        // `AmountGetter` rather than the embedded `AuctionDetails` curve (see
+        // `crate::utils::parse_dynamic_amount_getter`) - defer the rest of
+        // this fill to `on_making_amount_resolved` once that call returns.
+        if let Some(call) = utils::parse_dynamic_amount_getter(extension.maker_amount_data()) {
+            return Ok(ext_amount_getter::ext(call.getter)
+                .with_static_gas(GAS_FOR_AMOUNT_GETTER)
+                .get_making_amount(
+                    order_hash,
+                    remaining_making_amount,
+                    env::block_timestamp_ms(),
+                    call.calldata,
+                )
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_DYNAMIC_AMOUNT_CALLBACK)
+                        .on_making_amount_resolved(
+                            order,
+                            extension,
+                            order_hash,
+                            remaining_making_amount,
+                            taking_amount,
+                            taker,
+                            taker_traits,
+                            escrow,
+                        ),
+                ));
+        }
+
+        // Calculate making amount
         let making_amount = self.calculate_making_amount(
             &order,
             &extension,
             taking_amount,
-            order.making_amount,
+            remaining_making_amount,
             &order_hash,
         )?;
 
+        if making_amount > remaining_making_amount {
+            return Err(LimitOrderError::TakingAmountExceeded);
+        }
+
+        if making_amount < remaining_making_amount && !taker_traits.allow_partial_fill() {
+            return Err(LimitOrderError::PartialFillNotAllowed);
+        }
+
         // Validate extension
         if !self.validate_extension(&order, &extension)? {
             return Err(LimitOrderError::InvalidExtension);
         }
 
-        // Execute the swap
-        self.execute_swap(&order, &taker, making_amount, taking_amount)?;
+        self.begin_swap(
+            order,
+            extension,
+            order_hash,
+            remaining_making_amount,
+            making_amount,
+            taking_amount,
+            taker,
+            escrow,
+        )
+    }
+
+    /// `#[private]` callback chained after `fill_order`'s predicate
+    /// cross-contract calls: re-evaluates the predicate against their
+    /// resolved values and, if true, resumes the fill exactly where
+    /// `fill_order`'s synchronous path would have.
+    #[private]
+    #[handle_result]
+    pub fn on_predicate_resolved(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        taker_traits: TakerTraits,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        let predicate = predicate::parse_predicate(extension.predicate_data())
+            .ok_or(LimitOrderError::PredicateIsNotTrue)?;
+        let ext_sources = predicate::collect_ext_call_sources(&predicate);
+
+        let mut ext_values = Vec::with_capacity(ext_sources.len());
+        for index in 0..ext_sources.len() {
+            match env::promise_result(index as u64) {
+                near_sdk::PromiseResult::Successful(value) => {
+                    let parsed: U128 = near_sdk::serde_json::from_slice(&value)
+                        .map_err(|_| LimitOrderError::PredicateIsNotTrue)?;
+                    ext_values.push(parsed.0);
+                }
+                _ => return Err(LimitOrderError::PredicateIsNotTrue),
+            }
+        }
+
+        let epoch_values: Vec<u64> = predicate::collect_epoch_sources(&predicate)
+            .into_iter()
+            .map(|(maker, series)| self.epoch_for_series(maker, series))
+            .collect();
+
+        if !predicate::evaluate(
+            &predicate,
+            env::block_timestamp(),
+            &ext_values,
+            &epoch_values,
+        ) {
+            return Err(LimitOrderError::PredicateIsNotTrue);
+        }
+
+        self.continue_fill_order(
+            order,
+            extension,
+            order_hash,
+            remaining_making_amount,
+            taking_amount,
+            taker,
+            taker_traits,
+            escrow,
+        )
+    }
+
+    /// `#[private]` callback chained after `fill_order`'s two settlement
+    /// transfers: only if both the taker-to-maker leg (`promise_result(0)`)
+    /// and the maker-to-taker leg (`promise_result(1)`) succeeded does it
+    /// commit the remaining-amount/invalidator update, so a failed transfer
+    /// leaves the order's fillable amount untouched rather than consuming it
+    /// for tokens that never moved. If the fill opted into escrow (see
+    /// `fill_order`'s `escrow` argument), this is also where the
+    /// `HashlockEscrow` record is created - only once both legs are known to
+    /// have actually landed. If `extension` also carries a
+    /// `post_interaction_data` hook (see `utils::parse_interaction_call`),
+    /// it's fired here too, resolving through `on_post_interaction_resolved`
+    /// rather than returning `making_amount` immediately.
+    #[private]
+    #[handle_result]
+    pub fn on_settle_complete(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        escrow: Option<EscrowParams>,
+    ) -> Result<PromiseOrValue<U256>, LimitOrderError> {
+        if !matches!(
+            env::promise_result(0),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::TransferFromTakerToMakerFailed);
+        }
+        if !matches!(
+            env::promise_result(1),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::TransferFromMakerToTakerFailed);
+        }
+
+        let new_remaining = self.update_remaining_amount(
+            &order,
+            &order_hash,
+            remaining_making_amount,
+            making_amount,
+        );
+
+        if let Some(params) = escrow {
+            self.escrows.insert(
+                &order_hash,
+                &HashlockEscrow {
+                    hashlock: params.hashlock,
+                    maker: order.maker.clone(),
+                    taker,
+                    token: order.maker_asset.clone(),
+                    amount: making_amount.as_u128().ok_or(LimitOrderError::InvalidAmounts)?,
+                    cancel_at_ms: env::block_timestamp_ms() + params.cancel_after_ms,
+                },
+            );
+            log!("Escrow opened: order_hash={:?}", order_hash);
+        }
 
         log!(
-            "Order filled: making_amount={}, taking_amount={}",
+            "Order filled: making_amount={}, taking_amount={}, remaining_amount={}",
             making_amount,
-            taking_amount
+            taking_amount,
+            new_remaining
         );
 
-        Ok(making_amount)
+        if let Some(call) = utils::parse_interaction_call(extension.post_interaction_data()) {
+            let post_interaction = Promise::new(call.target).function_call(
+                call.method,
+                call.args,
+                NearToken::from_yoctonear(0),
+                Gas::from_tgas(call.gas_tgas),
+            );
+            return Ok(PromiseOrValue::Promise(
+                post_interaction.then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_POST_INTERACTION_CALLBACK)
+                        .on_post_interaction_resolved(making_amount),
+                ),
+            ));
+        }
+
+        Ok(PromiseOrValue::Value(making_amount))
+    }
+
+    /// `#[private]` callback chained after `fill_order`'s dynamic
+    /// `AmountGetter` call: resumes the rest of that synchronous validation
+    /// (partial-fill check, extension hash) now that the maker-supplied
+    /// making amount is known, then fires the same settlement transfer
+    /// `fill_order`'s non-dynamic path would have.
+    #[private]
+    #[handle_result]
+    pub fn on_making_amount_resolved(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        taker_traits: TakerTraits,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        let making_amount = match env::promise_result(0) {
+            near_sdk::PromiseResult::Successful(value) => near_sdk::serde_json::from_slice(&value)
+                .map_err(|_| LimitOrderError::InvalidAmounts)?,
+            _ => return Err(LimitOrderError::InvalidAmounts),
+        };
+
+        if making_amount > remaining_making_amount {
+            return Err(LimitOrderError::TakingAmountExceeded);
+        }
+
+        if making_amount < remaining_making_amount && !taker_traits.allow_partial_fill() {
+            return Err(LimitOrderError::PartialFillNotAllowed);
+        }
+
+        if !self.validate_extension(&order, &extension)? {
+            return Err(LimitOrderError::InvalidExtension);
+        }
+
+        self.begin_swap(
+            order,
+            extension,
+            order_hash,
+            remaining_making_amount,
+            making_amount,
+            taking_amount,
+            taker,
+            escrow,
+        )
+    }
+
+    /// Rest a signed order in the on-chain orderbook so a later
+    /// `match_orders` call can cross it against an opposing order without
+    /// either maker needing to be online. The attached deposit must cover
+    /// the marginal storage the new book entry costs; any excess is
+    /// refunded to the caller. The order must still be live (unexpired
+    /// bookkeeping is the caller's responsibility - this only checks it
+    /// hasn't already been cancelled or fully filled via
+    /// `remaining_making_amount`).
+    #[payable]
+    #[handle_result]
+    pub fn place_order(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        signature: Vec<u8>,
+    ) -> Result<[u8; 32], LimitOrderError> {
+        if self.paused {
+            return Err(LimitOrderError::ContractPaused);
+        }
+
+        if !validate_signature(&order, &signature, &order.maker, &self.domain_separator)? {
+            return Err(LimitOrderError::InvalidSignature);
+        }
+
+        let order_hash = self.hash_order(&order);
+        self.remaining_making_amount(&order, &order_hash)?;
+        let price = price_key(&order)?;
+
+        let storage_before = env::storage_usage();
+        self.orderbook.insert(
+            order_hash,
+            PlacedOrder {
+                order,
+                extension,
+                signature,
+                price,
+            },
+        );
+        let storage_used = env::storage_usage().saturating_sub(storage_before);
+        let cost = u128::from(storage_used) * env::storage_byte_cost().as_yoctonear();
+        let attached = env::attached_deposit().as_yoctonear();
+        if attached < cost {
+            self.orderbook.remove(order_hash);
+            return Err(LimitOrderError::InsufficientStorageDeposit);
+        }
+
+        let refund = attached - cost;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
+        }
+
+        log!("Order placed in book: order_hash={:?}", order_hash);
+        Ok(order_hash)
+    }
+
+    /// Pull a resting order back out of the book - only its own maker may do
+    /// so - refunding the storage deposit `place_order` charged for it.
+    #[handle_result]
+    pub fn remove_placed_order(&mut self, order_hash: [u8; 32]) -> Result<(), LimitOrderError> {
+        let placed = self
+            .orderbook
+            .get(&order_hash)
+            .ok_or(LimitOrderError::OrderNotFound)?;
+        if env::predecessor_account_id() != placed.order.maker {
+            return Err(LimitOrderError::OnlyMakerCanCancel);
+        }
+
+        self.remove_order_and_refund(order_hash);
+        log!("Order removed from book: order_hash={:?}", order_hash);
+        Ok(())
+    }
+
+    /// Cross two resting orders already sitting in the book. `order_a` is
+    /// treated as setting the execution price (see
+    /// `crate::orderbook::compute_match`); both sides are settled against
+    /// each other's own remaining amount, so a partially-filled resting
+    /// order only offers what it actually has left. Like `fill_order`,
+    /// settlement is asynchronous - this only fires the two-leg transfer and
+    /// returns a `Promise` chained into the `#[private]`
+    /// `on_match_settle_complete` callback, which is the only place either
+    /// order's remaining amount is actually committed.
+    #[handle_result]
+    pub fn match_orders(
+        &mut self,
+        order_hash_a: [u8; 32],
+        order_hash_b: [u8; 32],
+    ) -> Result<Promise, LimitOrderError> {
+        if self.paused {
+            return Err(LimitOrderError::ContractPaused);
+        }
+
+        let placed_a = self
+            .orderbook
+            .get(&order_hash_a)
+            .ok_or(LimitOrderError::OrderNotFound)?;
+        let placed_b = self
+            .orderbook
+            .get(&order_hash_b)
+            .ok_or(LimitOrderError::OrderNotFound)?;
+
+        let remaining_a = self.remaining_making_amount(&placed_a.order, &order_hash_a)?;
+        let remaining_b = self.remaining_making_amount(&placed_b.order, &order_hash_b)?;
+
+        // Reprice each order down to however much is actually left to fill,
+        // honoring any Dutch-auction curve the order's own extension
+        // carries, then cross the two at those remaining amounts.
+        let remaining_taking_a = utils::calculate_taking_amount(
+            &placed_a.order,
+            &placed_a.extension,
+            remaining_a,
+            remaining_a,
+            &order_hash_a,
+        )?;
+        let remaining_taking_b = utils::calculate_taking_amount(
+            &placed_b.order,
+            &placed_b.extension,
+            remaining_b,
+            remaining_b,
+            &order_hash_b,
+        )?;
+
+        let mut order_a = placed_a.order.clone();
+        order_a.making_amount = remaining_a;
+        order_a.taking_amount = remaining_taking_a;
+        let mut order_b = placed_b.order.clone();
+        order_b.making_amount = remaining_b;
+        order_b.taking_amount = remaining_taking_b;
+
+        let matched = compute_match(&order_a, &order_b)?;
+
+        let swap_promise = self.execute_swap(
+            &placed_a.order,
+            &placed_a.extension,
+            &placed_b.order.maker,
+            matched.fill_making_amount,
+            matched.fill_taking_amount,
+            None,
+        )?;
+        Ok(swap_promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_MATCH_SETTLE_CALLBACK)
+                .on_match_settle_complete(
+                    order_hash_a,
+                    order_hash_b,
+                    remaining_a,
+                    remaining_b,
+                    matched.fill_making_amount,
+                    matched.fill_taking_amount,
+                ),
+        ))
+    }
+
+    /// `#[private]` callback chained after `match_orders`'s settlement
+    /// transfer: only if both legs succeeded does it commit each order's
+    /// remaining-amount update, removing (and refunding the storage deposit
+    /// of) whichever side that fully exhausts.
+    #[private]
+    #[handle_result]
+    pub fn on_match_settle_complete(
+        &mut self,
+        order_hash_a: [u8; 32],
+        order_hash_b: [u8; 32],
+        remaining_a: U256,
+        remaining_b: U256,
+        fill_making_amount: U256,
+        fill_taking_amount: U256,
+    ) -> Result<(), LimitOrderError> {
+        if !matches!(
+            env::promise_result(0),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::TransferFromTakerToMakerFailed);
+        }
+        if !matches!(
+            env::promise_result(1),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::TransferFromMakerToTakerFailed);
+        }
+
+        let placed_a = self
+            .orderbook
+            .get(&order_hash_a)
+            .ok_or(LimitOrderError::OrderNotFound)?;
+        let placed_b = self
+            .orderbook
+            .get(&order_hash_b)
+            .ok_or(LimitOrderError::OrderNotFound)?;
+
+        let new_remaining_a = self.update_remaining_amount(
+            &placed_a.order,
+            &order_hash_a,
+            remaining_a,
+            fill_making_amount,
+        );
+        let new_remaining_b = self.update_remaining_amount(
+            &placed_b.order,
+            &order_hash_b,
+            remaining_b,
+            fill_taking_amount,
+        );
+
+        if new_remaining_a.is_zero() {
+            self.remove_order_and_refund(order_hash_a);
+        }
+        if new_remaining_b.is_zero() {
+            self.remove_order_and_refund(order_hash_b);
+        }
+
+        log!(
+            "Orders matched: order_hash_a={:?}, order_hash_b={:?}, fill_making_amount={}, fill_taking_amount={}",
+            order_hash_a,
+            order_hash_b,
+            fill_making_amount,
+            fill_taking_amount
+        );
+
+        Ok(())
     }
 
     /// Get owner
@@ -188,6 +832,112 @@ impl LimitOrderProtocol {
         self.weth.clone()
     }
 
+    /// Set the protocol fee taken out of every fill's taking amount
+    /// (`protocol_fee_bps` out of `utils::FEE_BASE_POINTS`) and the account
+    /// that collects it. Owner-only. A maker can additionally attach a
+    /// per-order integrator fee via `Extension::post_interaction_data` (see
+    /// `utils::parse_integrator_fee`), stacked on top of this one in
+    /// `execute_swap`.
+    pub fn set_fee_config(&mut self, protocol_fee_bps: u16, fee_recipient: AccountId) {
+        self.only_owner();
+        assert!(
+            protocol_fee_bps <= utils::FEE_BASE_POINTS,
+            "Fee exceeds FEE_BASE_POINTS"
+        );
+        self.protocol_fee_bps = protocol_fee_bps;
+        self.fee_recipient = fee_recipient;
+        log!(
+            "Fee config updated: protocol_fee_bps={}, fee_recipient={}",
+            self.protocol_fee_bps,
+            self.fee_recipient
+        );
+    }
+
+    /// Get the current protocol fee, in basis points out of
+    /// `utils::FEE_BASE_POINTS`.
+    pub fn protocol_fee_bps(&self) -> u16 {
+        self.protocol_fee_bps
+    }
+
+    /// Get the account that collects the protocol fee.
+    pub fn fee_recipient(&self) -> AccountId {
+        self.fee_recipient.clone()
+    }
+
+    /// Total fees `recipient` has been paid across every fill - a running
+    /// ledger `execute_swap` credits alongside the `transfer_tokens` call
+    /// that actually pays them, not an escrowed balance awaiting withdrawal.
+    pub fn collected_fees_for(&self, recipient: AccountId) -> u128 {
+        self.collected_fees.get(&recipient).unwrap_or(0)
+    }
+
+    /// Look up an open hashlock escrow (see `fill_order`'s `escrow`
+    /// argument), if one is still pending `withdraw` or `cancel_escrow`.
+    pub fn escrow(&self, order_hash: [u8; 32]) -> Option<HashlockEscrow> {
+        self.escrows.get(&order_hash)
+    }
+
+    /// Reveal `secret` to claim a hashlock-escrowed fill's maker asset.
+    /// Anyone holding the preimage of `escrow.hashlock` may call this - on
+    /// the real cross-chain swap this is the taker, once the maker has
+    /// likewise revealed the secret on the other chain to claim their own
+    /// leg - so there's no `predecessor_account_id` check here, only the
+    /// hash check. Succeeds at most once per `order_hash`: the escrow entry
+    /// is removed up front, so a second call (or a racing `cancel_escrow`)
+    /// finds nothing left to pay out.
+    #[handle_result]
+    pub fn withdraw(
+        &mut self,
+        order_hash: [u8; 32],
+        secret: Vec<u8>,
+    ) -> Result<Promise, LimitOrderError> {
+        let escrow = self
+            .escrows
+            .get(&order_hash)
+            .ok_or(LimitOrderError::EscrowNotFound)?;
+
+        let secret_hash: [u8; 32] = env::keccak256(&secret).try_into().unwrap();
+        if secret_hash != escrow.hashlock {
+            return Err(LimitOrderError::InvalidSecret);
+        }
+
+        self.escrows.remove(&order_hash);
+        log!("Escrow withdrawn: order_hash={:?}", order_hash);
+
+        Ok(self.transfer_tokens(
+            &escrow.token,
+            &env::current_account_id(),
+            &escrow.taker,
+            escrow.amount,
+        ))
+    }
+
+    /// Reclaim a hashlock-escrowed fill's maker asset back to the maker once
+    /// `escrow.cancel_at_ms` has passed without the taker revealing the
+    /// secret via `withdraw`. Like `withdraw`, succeeds at most once per
+    /// `order_hash`.
+    #[handle_result]
+    pub fn cancel_escrow(&mut self, order_hash: [u8; 32]) -> Result<Promise, LimitOrderError> {
+        let escrow = self
+            .escrows
+            .get(&order_hash)
+            .ok_or(LimitOrderError::EscrowNotFound)?;
+
+        if env::block_timestamp_ms() < escrow.cancel_at_ms {
+            return Err(LimitOrderError::EscrowNotYetCancellable);
+        }
+
+        self.escrows.remove(&order_hash);
+        log!("Escrow cancelled: order_hash={:?}", order_hash);
+
+        Ok(self.transfer_tokens(
+            &escrow.token,
+            &env::current_account_id(),
+            &escrow.maker,
+            escrow.amount,
+        ))
+    }
+
     /// Only owner modifier
     fn only_owner(&self) {
         assert_eq!(
@@ -198,59 +948,141 @@ impl LimitOrderProtocol {
     }
 
     // Internal helper functions
+
+    /// EIP-712 domain-separated structured hash of `order`, matching the
+    /// Ethereum side of the same swap bit-for-bit (see
+    /// `crate::utils::hash_order_712`).
     fn hash_order(&self, order: &Order) -> [u8; 32] {
-        let mut data = Vec::new();
-        data.extend_from_slice(&self.domain_separator);
-        data.extend_from_slice(&order.salt.to_le_bytes());
-        data.extend_from_slice(order.maker.as_bytes());
-        data.extend_from_slice(order.receiver.as_bytes());
-        data.extend_from_slice(order.maker_asset.as_bytes());
-        data.extend_from_slice(order.taker_asset.as_bytes());
-        data.extend_from_slice(&order.making_amount.to_le_bytes());
-        data.extend_from_slice(&order.taking_amount.to_le_bytes());
-
-        // Hash maker traits
-        let traits_hash = self.hash_maker_traits(&order.maker_traits);
-        data.extend_from_slice(&traits_hash);
+        hash_order_712(order, &self.domain_separator)
+    }
 
-        near_sdk::env::keccak256(&data).try_into().unwrap()
+    /// Amount of `order.making_amount` still available to be filled.
+    /// `Err(InvalidatedOrder)` if `cancel_order` already ran;
+    /// `Err(WrongSeriesNonce)` if (for `use_epoch_manager` orders)
+    /// `increase_epoch` has since advanced the order's series past its
+    /// `nonce_or_epoch`; `Err(RemainingAmountIsZero)` if earlier `fill_order`
+    /// calls have instead fully consumed it. Bit-invalidated orders are
+    /// all-or-nothing, so their remaining amount is either the full order or
+    /// zero; orders tracked by `remaining_invalidator` report however much is
+    /// actually left.
+    fn remaining_making_amount(
+        &self,
+        order: &Order,
+        order_hash: &[u8; 32],
+    ) -> Result<U256, LimitOrderError> {
+        if order.maker_traits.use_epoch_manager() {
+            if order.maker_traits.use_bit_invalidator() {
+                return Err(LimitOrderError::EpochManagerAndBitInvalidatorsAreIncompatible);
+            }
+            let current_epoch =
+                self.epoch_for_series(order.maker.clone(), order.maker_traits.series());
+            if order.maker_traits.nonce_or_epoch() < current_epoch {
+                return Err(LimitOrderError::WrongSeriesNonce);
+            }
+        }
+
+        if order.maker_traits.use_bit_invalidator() {
+            let nonce_or_epoch = order.maker_traits.nonce_or_epoch();
+            if self.bit_invalidator_for_order(order.maker.clone(), nonce_or_epoch) {
+                return Err(LimitOrderError::InvalidatedOrder);
+            }
+            return Ok(order.making_amount);
+        }
+
+        match self
+            .remaining_invalidator
+            .get(&(order.maker.clone(), *order_hash))
+        {
+            Some(invalidator) if invalidator.remaining().is_zero() => {
+                Err(LimitOrderError::RemainingAmountIsZero)
+            }
+            Some(invalidator) => Ok(invalidator.remaining()),
+            None => Ok(order.making_amount),
+        }
     }
 
-    fn hash_maker_traits(&self, traits: &MakerTraits) -> [u8; 32] {
-        let mut data = Vec::new();
-        data.extend_from_slice(&(traits.use_bit_invalidator as u8).to_le_bytes());
-        data.extend_from_slice(&(traits.use_epoch_manager as u8).to_le_bytes());
-        data.extend_from_slice(&(traits.has_extension as u8).to_le_bytes());
-        data.extend_from_slice(&traits.nonce_or_epoch.to_le_bytes());
-        data.extend_from_slice(&traits.series.to_le_bytes());
+    /// Record that `filled_amount` of `remaining_making_amount` was just
+    /// filled, returning the amount left afterward. Bit-invalidated orders
+    /// are not tracked by amount: their nonce bit is set instead, so the
+    /// order can't be filled again.
+    fn update_remaining_amount(
+        &mut self,
+        order: &Order,
+        order_hash: &[u8; 32],
+        remaining_making_amount: U256,
+        filled_amount: U256,
+    ) -> U256 {
+        let new_remaining = remaining_making_amount
+            .checked_sub(filled_amount)
+            .expect("filled_amount was already validated against remaining_making_amount");
 
-        near_sdk::env::keccak256(&data).try_into().unwrap()
+        if order.maker_traits.use_bit_invalidator() {
+            let nonce_or_epoch = order.maker_traits.nonce_or_epoch();
+            let key = (order.maker.clone(), nonce_or_epoch >> 8);
+            let mut data = self.bit_invalidator.get(&key).unwrap_or_default();
+            data.mass_invalidate(nonce_or_epoch, 0);
+            self.bit_invalidator.insert(&key, &data);
+            return new_remaining;
+        }
+
+        self.remaining_invalidator.insert(
+            &(order.maker.clone(), *order_hash),
+            &RemainingInvalidator::new(new_remaining),
+        );
+        new_remaining
     }
 
     fn calculate_making_amount(
         &self,
         order: &Order,
         extension: &Extension,
-        requested_taking_amount: u128,
-        _remaining_making_amount: u128,
-        _order_hash: &[u8; 32],
-    ) -> Result<u128, LimitOrderError> {
-        let making_amount_data = extension.maker_amount_data();
-
-        if making_amount_data.is_empty() {
-            // Linear proportion
-            if order.taking_amount == 0 {
-                return Err(LimitOrderError::SwapWithZeroAmount);
-            }
-            return Ok((order.making_amount * requested_taking_amount) / order.taking_amount);
+        requested_taking_amount: U256,
+        remaining_making_amount: U256,
+        order_hash: &[u8; 32],
+    ) -> Result<U256, LimitOrderError> {
+        utils::calculate_making_amount(
+            order,
+            extension,
+            requested_taking_amount,
+            remaining_making_amount,
+            order_hash,
+        )
+    }
+
+    /// Check `extension`'s predicate (if any). Returns `Ok(None)` if there's
+    /// no predicate, or one that's already confirmed true; `Ok(Some(sources))`
+    /// if it has `ExtCallUint` leaves that still need to be resolved via
+    /// cross-contract calls before it can be evaluated; `Err` if a
+    /// fully-synchronous predicate (no `ExtCallUint` leaves) evaluates false.
+    fn check_predicate_sync(
+        &self,
+        extension: &Extension,
+    ) -> Result<Option<Vec<(AccountId, String)>>, LimitOrderError> {
+        let Some(predicate) = predicate::parse_predicate(extension.predicate_data()) else {
+            return Ok(None);
+        };
+
+        let ext_sources = predicate::collect_ext_call_sources(&predicate);
+        if !ext_sources.is_empty() {
+            return Ok(Some(ext_sources));
         }
 
-        // In a real implementation, we would call an external getter contract
-        // For now, return a simplified calculation
-        Ok(requested_taking_amount)
+        let epoch_values: Vec<u64> = predicate::collect_epoch_sources(&predicate)
+            .into_iter()
+            .map(|(maker, series)| self.epoch_for_series(maker, series))
+            .collect();
+
+        if !predicate::evaluate(&predicate, env::block_timestamp(), &[], &epoch_values) {
+            return Err(LimitOrderError::PredicateIsNotTrue);
+        }
+        Ok(None)
     }
 
-    fn validate_extension(&self, order: &Order, extension: &Extension) -> Result<bool, LimitOrderError> {
+    fn validate_extension(
+        &self,
+        order: &Order,
+        extension: &Extension,
+    ) -> Result<bool, LimitOrderError> {
         if order.maker_traits.has_extension() {
             if extension.maker_amount_data().is_empty()
                 && extension.taker_amount_data().is_empty()
@@ -285,52 +1117,303 @@ impl LimitOrderProtocol {
         Ok(true)
     }
 
-    fn hash_extension(&self, extension: &Extension) -> [u8; 32] {
-        let mut data = Vec::new();
-        data.extend_from_slice(extension.maker_amount_data());
-        data.extend_from_slice(extension.taker_amount_data());
-        data.extend_from_slice(extension.predicate_data());
-        data.extend_from_slice(extension.permit_data());
-        data.extend_from_slice(extension.pre_interaction_data());
-        data.extend_from_slice(extension.post_interaction_data());
+    /// Entry point into the priced-and-validated tail of a fill, shared by
+    /// `continue_fill_order` and `on_making_amount_resolved` once
+    /// `making_amount` is known. A maker can gate the settlement transfer on
+    /// a `pre_interaction_data` hook (see `utils::parse_interaction_call`,
+    /// e.g. pulling liquidity into the maker's account before it's spent) -
+    /// if one is configured, it's fired first and `on_pre_interaction_resolved`
+    /// only proceeds to `finish_swap` once it succeeds, aborting the whole
+    /// fill otherwise. With no pre-interaction configured, this goes straight
+    /// to `finish_swap`.
+    fn begin_swap(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        if let Some(call) = utils::parse_interaction_call(extension.pre_interaction_data()) {
+            let pre_interaction = Promise::new(call.target).function_call(
+                call.method,
+                call.args,
+                NearToken::from_yoctonear(0),
+                Gas::from_tgas(call.gas_tgas),
+            );
+            return Ok(pre_interaction.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_PRE_INTERACTION_CALLBACK)
+                    .on_pre_interaction_resolved(
+                        order,
+                        extension,
+                        order_hash,
+                        remaining_making_amount,
+                        making_amount,
+                        taking_amount,
+                        taker,
+                        escrow,
+                    ),
+            ));
+        }
 
-        near_sdk::env::keccak256(&data).try_into().unwrap()
+        self.finish_swap(
+            order,
+            extension,
+            order_hash,
+            remaining_making_amount,
+            making_amount,
+            taking_amount,
+            taker,
+            escrow,
+        )
     }
 
-    fn execute_swap(
-        &self,
-        order: &Order,
-        taker: &AccountId,
-        making_amount: u128,
-        taking_amount: u128,
-    ) -> Result<(), LimitOrderError> {
-        // Transfer tokens from taker to maker
-        self.transfer_tokens(&order.taker_asset, taker, &order.maker, taking_amount)?;
-
-        // Transfer tokens from maker to taker
-        self.transfer_tokens(&order.maker_asset, &order.maker, taker, making_amount)?;
+    /// `#[private]` callback chained after `begin_swap`'s pre-interaction
+    /// hook call: only resumes into `finish_swap` if that call actually
+    /// succeeded, so a reverting hook aborts the fill before any asset has
+    /// moved.
+    #[private]
+    #[handle_result]
+    pub fn on_pre_interaction_resolved(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        if !matches!(
+            env::promise_result(0),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::PreInteractionFailed);
+        }
 
-        Ok(())
+        self.finish_swap(
+            order,
+            extension,
+            order_hash,
+            remaining_making_amount,
+            making_amount,
+            taking_amount,
+            taker,
+            escrow,
+        )
     }
 
-    fn transfer_tokens(
-        &self,
-        token: &AccountId,
-        from: &AccountId,
+    /// Fires the settlement transfer and chains it into `on_settle_complete`.
+    /// If the order also carries a `post_interaction_data` hook (e.g. a
+    /// rebalance or accounting callback), `on_settle_complete` fires that too
+    /// once the transfer lands, resolving through `on_post_interaction_resolved`
+    /// instead of returning `making_amount` directly.
+    fn finish_swap(
+        &mut self,
+        order: Order,
+        extension: Extension,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+        escrow: Option<EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        let swap_promise = self.execute_swap(
+            &order,
+            &extension,
+            &taker,
+            making_amount,
+            taking_amount,
+            escrow.as_ref(),
+        )?;
+        Ok(swap_promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_SETTLE_CALLBACK)
+                .on_settle_complete(
+                    order,
+                    extension,
+                    order_hash,
+                    remaining_making_amount,
+                    making_amount,
+                    taking_amount,
+                    taker,
+                    escrow,
+                ),
+        ))
+    }
+
+    /// `#[private]` callback chained after a post-interaction hook fired by
+    /// `on_settle_complete`: surfaces a reverted hook as
+    /// `PostInteractionFailed` rather than silently returning `making_amount`
+    /// for a fill whose post-fill accounting never actually ran.
+    #[private]
+    #[handle_result]
+    pub fn on_post_interaction_resolved(
+        &mut self,
+        making_amount: U256,
+    ) -> Result<U256, LimitOrderError> {
+        if !matches!(
+            env::promise_result(0),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::PostInteractionFailed);
+        }
+        Ok(making_amount)
+    }
+
+    fn hash_extension(&self, extension: &Extension) -> [u8; 32] {
+        let mut data = Vec::new();
+        data.extend_from_slice(extension.maker_amount_data());
+        data.extend_from_slice(extension.taker_amount_data());
+        data.extend_from_slice(extension.predicate_data());
+        data.extend_from_slice(extension.permit_data());
+        data.extend_from_slice(extension.pre_interaction_data());
+        data.extend_from_slice(extension.post_interaction_data());
+
+        near_sdk::env::keccak256(&data).try_into().unwrap()
+    }
+
+    /// Fire both legs of a fill's settlement transfer, joined with
+    /// `Promise::and` so `on_settle_complete` can inspect each leg's own
+    /// result (`promise_result(0)` for the taker->maker leg,
+    /// `promise_result(1)` for the maker->taker leg) rather than just
+    /// whichever resolved last.
+    ///
+    /// The taker's payment is split before that first leg fires: the
+    /// protocol fee and any per-order integrator fee (see
+    /// `utils::parse_integrator_fee`) are cut out of `taking_amount` and
+    /// routed straight to their recipients via `transfer_tokens`, same as
+    /// the maker/taker legs, so the maker only ever receives the net
+    /// remainder. Those fee transfers are fired alongside the two awaited
+    /// legs rather than joined into them - like `remove_order_and_refund`'s
+    /// storage refund, they don't gate the fill's own success. An order
+    /// whose `post_interaction_data` instead encodes a hook call (see
+    /// `utils::parse_interaction_call`, fired separately by
+    /// `on_settle_complete`) has no room left for an integrator fee in the
+    /// same bytes, so `parse_integrator_fee` is skipped for it.
+    ///
+    /// `escrow` redirects the maker->taker leg's destination to this
+    /// contract's own account instead of `taker` - see `fill_order`'s
+    /// `escrow` argument and `on_settle_complete`, which records the
+    /// resulting `HashlockEscrow` once this leg actually lands.
+    fn execute_swap(
+        &mut self,
+        order: &Order,
+        extension: &Extension,
+        taker: &AccountId,
+        making_amount: U256,
+        taking_amount: U256,
+        escrow: Option<&EscrowParams>,
+    ) -> Result<Promise, LimitOrderError> {
+        let protocol_fee = utils::calculate_fee_amount(taking_amount, self.protocol_fee_bps)?;
+        let integrator_fee_config =
+            if utils::parse_interaction_call(extension.post_interaction_data()).is_some() {
+                None
+            } else {
+                utils::parse_integrator_fee(extension.post_interaction_data())
+            };
+        let integrator_fee = match &integrator_fee_config {
+            Some(config) => utils::calculate_fee_amount(taking_amount, config.fee_bps)?,
+            None => U256::ZERO,
+        };
+
+        let total_fees = protocol_fee
+            .checked_add(integrator_fee)
+            .ok_or(LimitOrderError::InvalidAmounts)?;
+        let maker_net_amount = taking_amount
+            .checked_sub(total_fees)
+            .ok_or(LimitOrderError::InvalidAmounts)?;
+
+        if !protocol_fee.is_zero() {
+            let fee_recipient = self.fee_recipient.clone();
+            let protocol_fee_amount = protocol_fee
+                .as_u128()
+                .ok_or(LimitOrderError::InvalidAmounts)?;
+            self.transfer_tokens(
+                &order.taker_asset,
+                taker,
+                &fee_recipient,
+                protocol_fee_amount,
+            );
+            self.credit_fee(fee_recipient, protocol_fee_amount);
+        }
+        if !integrator_fee.is_zero() {
+            let recipient = integrator_fee_config.unwrap().recipient;
+            let integrator_fee_amount = integrator_fee
+                .as_u128()
+                .ok_or(LimitOrderError::InvalidAmounts)?;
+            self.transfer_tokens(&order.taker_asset, taker, &recipient, integrator_fee_amount);
+            self.credit_fee(recipient, integrator_fee_amount);
+        }
+
+        let maker_asset_recipient = match escrow {
+            Some(_) => env::current_account_id(),
+            None => taker.clone(),
+        };
+
+        let maker_net_amount = maker_net_amount
+            .as_u128()
+            .ok_or(LimitOrderError::InvalidAmounts)?;
+        let making_amount = making_amount
+            .as_u128()
+            .ok_or(LimitOrderError::InvalidAmounts)?;
+
+        let taker_to_maker =
+            self.transfer_tokens(&order.taker_asset, taker, &order.maker, maker_net_amount);
+        let maker_to_taker = self.transfer_tokens(
+            &order.maker_asset,
+            &order.maker,
+            &maker_asset_recipient,
+            making_amount,
+        );
+        Ok(taker_to_maker.and(maker_to_taker))
+    }
+
+    /// Add `amount` to `recipient`'s running total in `collected_fees`.
+    fn credit_fee(&mut self, recipient: AccountId, amount: u128) {
+        let new_total = self.collected_fees.get(&recipient).unwrap_or(0) + amount;
+        self.collected_fees.insert(&recipient, &new_total);
+    }
+
+    /// Drop `order_hash` out of the book (if still there) and refund
+    /// whatever storage that freed to the order's own maker - shared by
+    /// `remove_placed_order` and `on_match_settle_complete`, the two places
+    /// a resting order stops needing its book entry paid for.
+    fn remove_order_and_refund(&mut self, order_hash: [u8; 32]) {
+        let storage_before = env::storage_usage();
+        let Some(placed) = self.orderbook.remove(order_hash) else {
+            return;
+        };
+        let storage_freed = storage_before.saturating_sub(env::storage_usage());
+        let refund = u128::from(storage_freed) * env::storage_byte_cost().as_yoctonear();
+        if refund > 0 {
+            Promise::new(placed.order.maker).transfer(NearToken::from_yoctonear(refund));
+        }
+    }
+
+    fn transfer_tokens(
+        &self,
+        token: &AccountId,
+        from: &AccountId,
         to: &AccountId,
         amount: u128,
-    ) -> Result<(), LimitOrderError> {
+    ) -> Promise {
         if token.as_str() == "near" {
             // Native NEAR transfer
-            Promise::new(to.clone()).transfer(NearToken::from_yoctonear(amount));
+            Promise::new(to.clone()).transfer(NearToken::from_yoctonear(amount))
         } else {
             // Fungible token transfer
             ext_ft::ext(token.clone())
                 .with_static_gas(GAS_FOR_FT_TRANSFER)
                 .with_attached_deposit(NearToken::from_yoctonear(1))
-                .ft_transfer_from(from.clone(), to.clone(), amount, None);
+                .ft_transfer_from(from.clone(), to.clone(), amount, None)
         }
-        Ok(())
     }
 }
 
@@ -346,6 +1429,31 @@ pub trait FungibleToken {
     );
 }
 
+/// External contract trait a maker can delegate Dutch-auction-style dynamic
+/// pricing to, as an alternative to the embedded `AuctionDetails` curve (see
+/// `crate::utils::parse_dynamic_amount_getter`). `order_hash`/
+/// `remaining_making_amount`/`timestamp_ms` give the getter the same inputs
+/// `calculate_making_amount`/`calculate_taking_amount` use internally;
+/// `calldata` carries whatever maker-supplied parameters the getter needs.
+#[ext_contract(ext_amount_getter)]
+pub trait AmountGetter {
+    fn get_making_amount(
+        &self,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        timestamp_ms: u64,
+        calldata: Vec<u8>,
+    ) -> U256;
+
+    fn get_taking_amount(
+        &self,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        timestamp_ms: u64,
+        calldata: Vec<u8>,
+    ) -> U256;
+}
+
 /*
  * The rest of this file holds the inline tests for the code above
  * Learn more about Rust tests: https://doc.rust-lang.org/book/ch11-01-writing-tests.html
@@ -371,8 +1479,8 @@ mod tests {
             receiver: accounts(1),
             maker_asset: accounts(2),
             taker_asset: accounts(3),
-            making_amount: 1000,
-            taking_amount: 1000,
+            making_amount: U256::from(1000u128),
+            taking_amount: U256::from(1000u128),
             maker_traits: MakerTraits::default(),
         }
     }
@@ -435,4 +1543,1307 @@ mod tests {
         let result = contract.bit_invalidator_for_order(accounts(1), 0);
         assert!(!result); // Should be false for default state
     }
+
+    #[test]
+    fn test_fill_order_rejects_order_from_non_evm_non_implicit_maker() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let extension = create_test_extension();
+
+        // `order.maker` (accounts(0), e.g. "alice.near") is neither a
+        // `0x...` eth address nor a 64-hex-char NEAR implicit account, so no
+        // signature can ever validate against it.
+        let result = contract.fill_order(
+            order,
+            extension,
+            vec![0u8; 65],
+            accounts(2),
+            U256::from(500u128),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(matches!(result, Err(LimitOrderError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_fill_order_rejects_wrong_length_signature_for_evm_maker() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        order.maker =
+            AccountId::try_from("0x1111111111111111111111111111111111111111".to_string()).unwrap();
+        let extension = create_test_extension();
+
+        let result = contract.fill_order(
+            order,
+            extension,
+            vec![0u8; 66],
+            accounts(2),
+            U256::from(500u128),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(matches!(result, Err(LimitOrderError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_signing_digest_is_chain_bound_so_orders_cant_replay_across_deployments() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        // Same order, but signed against deployments on two different
+        // chains (mainnet vs testnet, say) - each gets its own
+        // `domain_separator` per `new()`'s doc comment.
+        let mainnet_separator = utils::domain_separator(
+            "Fusion+ Limit Order Protocol",
+            "1",
+            1313161554,
+            &accounts(1),
+        );
+        let testnet_separator = utils::domain_separator(
+            "Fusion+ Limit Order Protocol",
+            "1",
+            1313161555,
+            &accounts(1),
+        );
+
+        let mainnet_contract = LimitOrderProtocol::new(mainnet_separator, accounts(1));
+        let testnet_contract = LimitOrderProtocol::new(testnet_separator, accounts(1));
+        let order = create_test_order();
+
+        // The digest a maker actually signs over - `hash_order_712` folded
+        // with each contract's own `domain_separator` - diverges across
+        // chains, so a signature authorized for one deployment is never
+        // valid on the other: `fill_order`'s `validate_signature` call is
+        // checking a chain-specific digest, not just the bare order fields.
+        assert_ne!(
+            mainnet_contract.hash_order(&order),
+            testnet_contract.hash_order(&order)
+        );
+    }
+
+    #[test]
+    fn test_remaining_making_amount_defaults_to_full_order_amount() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        // No fill or cancel has happened yet, so the whole order is open.
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_remaining_making_amount_is_invalidated_after_cancel() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        contract.cancel_order(order.maker_traits.clone(), order_hash);
+
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Err(LimitOrderError::InvalidatedOrder)
+        );
+    }
+
+    #[test]
+    fn test_remaining_making_amount_is_open_below_current_epoch_before_increase() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        order.maker_traits.use_bit_invalidator = false;
+        order.maker_traits.use_epoch_manager = true;
+        order.maker_traits.series = 3;
+        order.maker_traits.nonce_or_epoch = 0;
+        let order_hash = [7u8; 32];
+
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_increase_epoch_invalidates_stale_orders_in_that_series() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        order.maker_traits.use_bit_invalidator = false;
+        order.maker_traits.use_epoch_manager = true;
+        order.maker_traits.series = 3;
+        order.maker_traits.nonce_or_epoch = 0;
+        let order_hash = [7u8; 32];
+
+        contract.increase_epoch(3, 1);
+
+        assert_eq!(contract.epoch_for_series(accounts(0), 3), 1);
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Err(LimitOrderError::WrongSeriesNonce)
+        );
+    }
+
+    #[test]
+    fn test_increase_epoch_does_not_invalidate_a_newer_order_in_the_same_series() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        order.maker_traits.use_bit_invalidator = false;
+        order.maker_traits.use_epoch_manager = true;
+        order.maker_traits.series = 3;
+        order.maker_traits.nonce_or_epoch = 2;
+        let order_hash = [7u8; 32];
+
+        contract.increase_epoch(3, 1);
+
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_increase_epoch_does_not_affect_other_series() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        order.maker_traits.use_bit_invalidator = false;
+        order.maker_traits.use_epoch_manager = true;
+        order.maker_traits.series = 3;
+        order.maker_traits.nonce_or_epoch = 0;
+        let order_hash = [7u8; 32];
+
+        contract.increase_epoch(4, 1);
+
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_remaining_making_amount_rejects_epoch_manager_with_bit_invalidator() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        order.maker_traits.use_bit_invalidator = true;
+        order.maker_traits.use_epoch_manager = true;
+        let order_hash = [7u8; 32];
+
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Err(LimitOrderError::EpochManagerAndBitInvalidatorsAreIncompatible)
+        );
+    }
+
+    #[test]
+    fn test_update_remaining_amount_tracks_partial_fill() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        let new_remaining = contract.update_remaining_amount(
+            &order,
+            &order_hash,
+            order.making_amount,
+            U256::from(400u128),
+        );
+        assert_eq!(
+            new_remaining,
+            order.making_amount.checked_sub(U256::from(400u128)).unwrap()
+        );
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount.checked_sub(U256::from(400u128)).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_update_remaining_amount_tracks_sequential_partial_fills_summing_to_full_size() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        let after_first = contract.update_remaining_amount(
+            &order,
+            &order_hash,
+            order.making_amount,
+            U256::from(400u128),
+        );
+        assert_eq!(
+            after_first,
+            order.making_amount.checked_sub(U256::from(400u128)).unwrap()
+        );
+
+        let after_second = contract.update_remaining_amount(
+            &order,
+            &order_hash,
+            after_first,
+            U256::from(600u128),
+        );
+        assert_eq!(after_second, U256::ZERO);
+
+        // Fully consumed by fills (as opposed to `cancel_order`) is reported
+        // distinctly from `InvalidatedOrder`.
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Err(LimitOrderError::RemainingAmountIsZero)
+        );
+    }
+
+    #[test]
+    fn test_continue_fill_order_rejects_over_fill_beyond_stored_remaining() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let extension = create_test_extension();
+        let order_hash = [7u8; 32];
+
+        // Simulate an earlier partial fill leaving only 600 of the order's
+        // 1000 making_amount open.
+        contract.update_remaining_amount(&order, &order_hash, order.making_amount, U256::from(400u128));
+        let remaining_making_amount = contract
+            .remaining_making_amount(&order, &order_hash)
+            .unwrap();
+
+        // Requesting the order's full taking_amount prices out to a
+        // making_amount of 1000 - more than the 600 actually left.
+        let result = contract.continue_fill_order(
+            order.clone(),
+            extension,
+            order_hash,
+            remaining_making_amount,
+            order.taking_amount,
+            accounts(2),
+            TakerTraits {
+                allow_partial_fill: true,
+                ..Default::default()
+            },
+            None,
+        );
+
+        assert!(matches!(result, Err(LimitOrderError::TakingAmountExceeded)));
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(remaining_making_amount)
+        );
+    }
+
+    #[test]
+    fn test_calculate_making_amount_does_not_overflow_for_large_amounts() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        // Both operands comfortably exceed u128::MAX, so a raw `u128`
+        // multiplication before dividing would overflow and panic.
+        order.making_amount = U256::MAX.checked_div(U256::from(2u128)).unwrap();
+        order.taking_amount = U256::MAX.checked_div(U256::from(2u128)).unwrap();
+        let extension = create_test_extension();
+
+        let making_amount = contract
+            .calculate_making_amount(
+                &order,
+                &extension,
+                order.taking_amount,
+                U256::ZERO,
+                &[0u8; 32],
+            )
+            .unwrap();
+        assert_eq!(making_amount, order.making_amount);
+    }
+
+    /// Mock both legs of a fill's settlement transfer as if `taker_to_maker`
+    /// resolved with `taker_to_maker_ok` and `maker_to_taker` resolved with
+    /// `maker_to_taker_ok` - the order `execute_swap`'s `Promise::and` joins
+    /// them in, matching `promise_result(0)`/`promise_result(1)` in
+    /// `on_settle_complete`.
+    fn set_settlement_result(
+        context: VMContextBuilder,
+        taker_to_maker_ok: bool,
+        maker_to_taker_ok: bool,
+    ) {
+        let to_result = |ok: bool| {
+            if ok {
+                near_sdk::PromiseResult::Successful(vec![])
+            } else {
+                near_sdk::PromiseResult::Failed
+            }
+        };
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![to_result(taker_to_maker_ok), to_result(maker_to_taker_ok)]
+        );
+    }
+
+    #[test]
+    fn test_on_settle_complete_commits_fill_when_both_legs_succeed() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        let making_amount = contract
+            .on_settle_complete(
+                order.clone(),
+                create_test_extension(),
+                order_hash,
+                order.making_amount,
+                U256::from(400u128),
+                U256::from(400u128),
+                accounts(2),
+                None,
+            )
+            .expect("both legs succeeding should commit the fill");
+
+        assert!(matches!(
+            making_amount,
+            PromiseOrValue::Value(v) if v == U256::from(400u128)
+        ));
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount.checked_sub(U256::from(400u128)).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_on_settle_complete_rejects_and_leaves_amount_untouched_when_taker_leg_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        set_settlement_result(get_context(accounts(0)), false, true);
+        let result = contract.on_settle_complete(
+            order.clone(),
+            create_test_extension(),
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            U256::from(400u128),
+            accounts(2),
+            None,
+        );
+
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::TransferFromTakerToMakerFailed)
+        ));
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_on_settle_complete_rejects_and_leaves_amount_untouched_when_maker_leg_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+
+        set_settlement_result(get_context(accounts(0)), true, false);
+        let result = contract.on_settle_complete(
+            order.clone(),
+            create_test_extension(),
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            U256::from(400u128),
+            accounts(2),
+            None,
+        );
+
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::TransferFromMakerToTakerFailed)
+        ));
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_on_settle_complete_opens_escrow_instead_of_paying_taker_directly() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let secret = b"shh".to_vec();
+        let hashlock: [u8; 32] = near_sdk::env::keccak256(&secret).try_into().unwrap();
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        contract
+            .on_settle_complete(
+                order.clone(),
+                create_test_extension(),
+                order_hash,
+                order.making_amount,
+                U256::from(400u128),
+                U256::from(400u128),
+                accounts(2),
+                Some(EscrowParams {
+                    hashlock,
+                    cancel_after_ms: 1_000,
+                }),
+            )
+            .expect("both legs succeeding should commit the fill");
+
+        let escrow = contract
+            .escrow(order_hash)
+            .expect("a hashlock escrow should have been opened");
+        assert_eq!(escrow.hashlock, hashlock);
+        assert_eq!(escrow.maker, order.maker);
+        assert_eq!(escrow.taker, accounts(2));
+        assert_eq!(escrow.amount, 400);
+    }
+
+    #[test]
+    fn test_withdraw_pays_out_taker_on_correct_secret_and_closes_escrow() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let secret = b"shh".to_vec();
+        let hashlock: [u8; 32] = near_sdk::env::keccak256(&secret).try_into().unwrap();
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        contract
+            .on_settle_complete(
+                order,
+                create_test_extension(),
+                order_hash,
+                U256::from(400u128),
+                U256::from(400u128),
+                U256::from(400u128),
+                accounts(2),
+                Some(EscrowParams {
+                    hashlock,
+                    cancel_after_ms: 1_000,
+                }),
+            )
+            .unwrap();
+
+        contract
+            .withdraw(order_hash, secret)
+            .expect("the correct preimage should release the escrow");
+        assert_eq!(contract.escrow(order_hash), None);
+    }
+
+    #[test]
+    fn test_withdraw_rejects_wrong_secret() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let hashlock: [u8; 32] = near_sdk::env::keccak256(b"shh").try_into().unwrap();
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        contract
+            .on_settle_complete(
+                order,
+                create_test_extension(),
+                order_hash,
+                U256::from(400u128),
+                U256::from(400u128),
+                U256::from(400u128),
+                accounts(2),
+                Some(EscrowParams {
+                    hashlock,
+                    cancel_after_ms: 1_000,
+                }),
+            )
+            .unwrap();
+
+        let result = contract.withdraw(order_hash, b"wrong".to_vec());
+        assert!(matches!(result, Err(LimitOrderError::InvalidSecret)));
+        assert!(contract.escrow(order_hash).is_some());
+    }
+
+    #[test]
+    fn test_cancel_escrow_refunds_maker_after_timeout() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let hashlock: [u8; 32] = near_sdk::env::keccak256(b"shh").try_into().unwrap();
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        contract
+            .on_settle_complete(
+                order,
+                create_test_extension(),
+                order_hash,
+                U256::from(400u128),
+                U256::from(400u128),
+                U256::from(400u128),
+                accounts(2),
+                Some(EscrowParams {
+                    hashlock,
+                    cancel_after_ms: 1_000,
+                }),
+            )
+            .unwrap();
+
+        // Still within the cancellation window - the maker can't reclaim yet.
+        let too_early = contract.cancel_escrow(order_hash);
+        assert!(matches!(
+            too_early,
+            Err(LimitOrderError::EscrowNotYetCancellable)
+        ));
+
+        let mut later_context = get_context(accounts(0));
+        later_context.block_timestamp(2_000 * 1_000_000); // 2000ms, in nanoseconds
+        testing_env!(later_context.build());
+
+        contract
+            .cancel_escrow(order_hash)
+            .expect("past cancel_at_ms the maker should be able to reclaim");
+        assert_eq!(contract.escrow(order_hash), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this")]
+    fn test_set_fee_config_rejects_non_owner() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let stranger_context = get_context(accounts(9));
+        testing_env!(stranger_context.build());
+        contract.set_fee_config(100, accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee exceeds FEE_BASE_POINTS")]
+    fn test_set_fee_config_rejects_fee_above_base_points() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        contract.set_fee_config(utils::FEE_BASE_POINTS + 1, accounts(2));
+    }
+
+    #[test]
+    fn test_execute_swap_splits_protocol_and_integrator_fee_out_of_maker_net_amount() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        contract.set_fee_config(500, accounts(5)); // 5% protocol fee
+
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        let mut integrator_fee_data = 200u16.to_le_bytes().to_vec(); // 2% integrator fee
+        let integrator_account = accounts(6).to_string();
+        integrator_fee_data.extend_from_slice(&(integrator_account.len() as u16).to_le_bytes());
+        integrator_fee_data.extend_from_slice(integrator_account.as_bytes());
+        extension.post_interaction_data = integrator_fee_data;
+
+        contract
+            .execute_swap(
+                &order,
+                &extension,
+                &accounts(3),
+                U256::from(1000u128),
+                U256::from(1000u128),
+                None,
+            )
+            .expect("fee split should fit within the taking amount");
+
+        assert_eq!(contract.collected_fees_for(accounts(5)), 50);
+        assert_eq!(contract.collected_fees_for(accounts(6)), 20);
+    }
+
+    #[test]
+    fn test_execute_swap_rejects_when_fees_exceed_taking_amount() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        contract.set_fee_config(9_999, accounts(5)); // ~99.99% protocol fee
+
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        // Another ~99.99% integrator fee on top - together they exceed the
+        // taking amount entirely.
+        let mut integrator_fee_data = 9_999u16.to_le_bytes().to_vec();
+        let integrator_account = accounts(6).to_string();
+        integrator_fee_data.extend_from_slice(&(integrator_account.len() as u16).to_le_bytes());
+        integrator_fee_data.extend_from_slice(integrator_account.as_bytes());
+        extension.post_interaction_data = integrator_fee_data;
+
+        let result = contract.execute_swap(
+            &order,
+            &extension,
+            &accounts(3),
+            U256::from(1000u128),
+            U256::from(1000u128),
+            None,
+        );
+        assert_eq!(result, Err(LimitOrderError::InvalidAmounts));
+    }
+
+    /// Mock a dynamic `AmountGetter` call resolving with `amount`, or
+    /// failing outright if `amount` is `None` - the single-promise
+    /// counterpart to `set_settlement_result` above, for
+    /// `on_making_amount_resolved`'s `promise_result(0)`.
+    fn set_amount_getter_result(context: VMContextBuilder, amount: Option<U256>) {
+        let result = match amount {
+            Some(value) => {
+                near_sdk::PromiseResult::Successful(near_sdk::serde_json::to_vec(&value).unwrap())
+            }
+            None => near_sdk::PromiseResult::Failed,
+        };
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn test_on_making_amount_resolved_commits_fill_when_getter_succeeds() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let extension = create_test_extension();
+        let order_hash = [7u8; 32];
+
+        set_amount_getter_result(get_context(accounts(0)), Some(U256::from(400u128)));
+        let result = contract.on_making_amount_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            accounts(2),
+            TakerTraits {
+                allow_partial_fill: true,
+                ..Default::default()
+            },
+            None,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_on_making_amount_resolved_rejects_when_getter_call_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let extension = create_test_extension();
+        let order_hash = [7u8; 32];
+
+        set_amount_getter_result(get_context(accounts(0)), None);
+        let result = contract.on_making_amount_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            accounts(2),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(matches!(result, Err(LimitOrderError::InvalidAmounts)));
+    }
+
+    #[test]
+    fn test_on_making_amount_resolved_rejects_partial_fill_when_not_allowed() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let extension = create_test_extension();
+        let order_hash = [7u8; 32];
+
+        // Getter returns less than `order.making_amount` with
+        // `allow_partial_fill` left at its default of `false`.
+        set_amount_getter_result(get_context(accounts(0)), Some(U256::from(400u128)));
+        let result = contract.on_making_amount_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            accounts(2),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::PartialFillNotAllowed)
+        ));
+    }
+
+    fn encode_timestamp_predicate(op: u8, value: u128) -> Vec<u8> {
+        let mut data = vec![op, 0x00];
+        data.extend_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    fn encode_ext_call_uint_predicate(op: u8, account: &str, method: &str, value: u128) -> Vec<u8> {
+        let mut data = vec![op, 0x01];
+        data.extend_from_slice(&(account.len() as u16).to_le_bytes());
+        data.extend_from_slice(account.as_bytes());
+        data.extend_from_slice(&(method.len() as u16).to_le_bytes());
+        data.extend_from_slice(method.as_bytes());
+        data.extend_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_check_predicate_sync_accepts_no_predicate() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        assert_eq!(
+            contract.check_predicate_sync(&create_test_extension()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_check_predicate_sync_accepts_true_timestamp_predicate() {
+        let mut context = get_context(accounts(0));
+        context.block_timestamp(1_000);
+        testing_env!(context.build());
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let mut extension = create_test_extension();
+        // 0x02 == OP_GT in `crate::predicate`'s encoding.
+        extension.predicate_data = encode_timestamp_predicate(0x02, 500);
+
+        assert_eq!(contract.check_predicate_sync(&extension), Ok(None));
+    }
+
+    #[test]
+    fn test_check_predicate_sync_rejects_false_timestamp_predicate() {
+        let mut context = get_context(accounts(0));
+        context.block_timestamp(1_000);
+        testing_env!(context.build());
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let mut extension = create_test_extension();
+        extension.predicate_data = encode_timestamp_predicate(0x02, 5_000);
+
+        assert_eq!(
+            contract.check_predicate_sync(&extension),
+            Err(LimitOrderError::PredicateIsNotTrue)
+        );
+    }
+
+    #[test]
+    fn test_check_predicate_sync_defers_ext_call_predicate() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let mut extension = create_test_extension();
+        extension.predicate_data =
+            encode_ext_call_uint_predicate(0x01, "oracle.near", "get_price", 500);
+
+        assert_eq!(
+            contract.check_predicate_sync(&extension),
+            Ok(Some(vec![(
+                "oracle.near".parse().unwrap(),
+                "get_price".to_string()
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_on_predicate_resolved_commits_fill_when_predicate_holds() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let mut order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.predicate_data =
+            encode_ext_call_uint_predicate(0x02, "oracle.near", "get_price", 100);
+        // The order carries extension data, so it has to declare it and bind
+        // the extension hash into its salt for `validate_extension` to pass.
+        order.maker_traits.has_extension = true;
+        order.salt = u64::from_le_bytes(contract.hash_extension(&extension)[0..8].try_into().unwrap());
+        let order_hash = [7u8; 32];
+
+        set_amount_getter_result(get_context(accounts(0)), Some(U256::from(200u128)));
+        let result = contract.on_predicate_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            order.taking_amount,
+            accounts(2),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_on_predicate_resolved_rejects_when_resolved_value_fails_predicate() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.predicate_data =
+            encode_ext_call_uint_predicate(0x02, "oracle.near", "get_price", 500);
+        let order_hash = [7u8; 32];
+
+        set_amount_getter_result(get_context(accounts(0)), Some(U256::from(200u128)));
+        let result = contract.on_predicate_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            order.taking_amount,
+            accounts(2),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(matches!(result, Err(LimitOrderError::PredicateIsNotTrue)));
+    }
+
+    #[test]
+    fn test_on_predicate_resolved_rejects_when_ext_call_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.predicate_data =
+            encode_ext_call_uint_predicate(0x02, "oracle.near", "get_price", 100);
+        let order_hash = [7u8; 32];
+
+        set_amount_getter_result(get_context(accounts(0)), None);
+        let result = contract.on_predicate_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            order.taking_amount,
+            accounts(2),
+            TakerTraits::default(),
+            None,
+        );
+        assert!(matches!(result, Err(LimitOrderError::PredicateIsNotTrue)));
+    }
+
+    fn encode_interaction_call(target: &str, method: &str, args: &[u8], gas_tgas: u64) -> Vec<u8> {
+        let mut data = vec![utils::INTERACTION_CALL_TAG];
+        data.extend_from_slice(&gas_tgas.to_le_bytes());
+        data.extend_from_slice(&(target.len() as u16).to_le_bytes());
+        data.extend_from_slice(target.as_bytes());
+        data.extend_from_slice(&(method.len() as u16).to_le_bytes());
+        data.extend_from_slice(method.as_bytes());
+        data.extend_from_slice(args);
+        data
+    }
+
+    /// Mock a single pre/post-interaction hook call resolving ok, or failing
+    /// outright otherwise - the single-promise-result counterpart to
+    /// `set_amount_getter_result` above, for `on_pre_interaction_resolved`
+    /// and `on_post_interaction_resolved`'s `promise_result(0)`.
+    fn set_interaction_result(context: VMContextBuilder, ok: bool) {
+        let result = if ok {
+            near_sdk::PromiseResult::Successful(vec![])
+        } else {
+            near_sdk::PromiseResult::Failed
+        };
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn test_begin_swap_defers_settlement_until_pre_interaction_hook_resolves() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let mut extension = create_test_extension();
+        extension.pre_interaction_data =
+            encode_interaction_call("vault.near", "pull_liquidity", &[], 10);
+
+        let result = contract.begin_swap(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            U256::from(400u128),
+            accounts(2),
+            None,
+        );
+
+        // `begin_swap` only schedules the pre-interaction call here - the
+        // settlement transfer hasn't fired yet, so the order's fillable
+        // amount is still untouched.
+        assert!(result.is_ok());
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_on_pre_interaction_resolved_rejects_without_settling_when_hook_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let extension = create_test_extension();
+
+        set_interaction_result(get_context(accounts(0)), false);
+        let result = contract.on_pre_interaction_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            U256::from(400u128),
+            accounts(2),
+            None,
+        );
+
+        assert!(matches!(result, Err(LimitOrderError::PreInteractionFailed)));
+        // No transfer was ever attempted, so the order is still fully open.
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount)
+        );
+    }
+
+    #[test]
+    fn test_on_pre_interaction_resolved_proceeds_to_settlement_when_hook_succeeds() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let extension = create_test_extension();
+
+        set_interaction_result(get_context(accounts(0)), true);
+        let result = contract.on_pre_interaction_resolved(
+            order.clone(),
+            extension,
+            order_hash,
+            order.making_amount,
+            U256::from(400u128),
+            U256::from(400u128),
+            accounts(2),
+            None,
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_on_settle_complete_fires_post_interaction_hook_instead_of_returning_immediately() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = [7u8; 32];
+        let mut extension = create_test_extension();
+        extension.post_interaction_data =
+            encode_interaction_call("accounting.near", "record_fill", &[], 10);
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        let result = contract
+            .on_settle_complete(
+                order.clone(),
+                extension,
+                order_hash,
+                order.making_amount,
+                U256::from(400u128),
+                U256::from(400u128),
+                accounts(2),
+                None,
+            )
+            .expect("both legs succeeding should commit the fill");
+
+        // A post-interaction hook defers the return: it's a pending `Promise`
+        // resolving through `on_post_interaction_resolved`, not the bare
+        // `making_amount` `on_settle_complete` would return without one.
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+        assert_eq!(
+            contract.remaining_making_amount(&order, &order_hash),
+            Ok(order.making_amount.checked_sub(U256::from(400u128)).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_on_post_interaction_resolved_rejects_when_hook_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        set_interaction_result(get_context(accounts(0)), false);
+        let result = contract.on_post_interaction_resolved(U256::from(400u128));
+
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::PostInteractionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_on_post_interaction_resolved_returns_making_amount_when_hook_succeeds() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        set_interaction_result(get_context(accounts(0)), true);
+        let result = contract.on_post_interaction_resolved(U256::from(400u128));
+
+        assert_eq!(result, Ok(U256::from(400u128)));
+    }
+
+    /// Rest `order` directly in the book, bypassing `place_order`'s
+    /// signature check (which `create_test_order`'s plain-account maker
+    /// could never pass) - mirrors how `on_settle_complete`'s tests drive
+    /// state directly rather than routing through `fill_order`.
+    fn seed_placed_order(contract: &mut LimitOrderProtocol, order: Order) -> [u8; 32] {
+        let order_hash = contract.hash_order(&order);
+        let price = price_key(&order).unwrap();
+        contract.orderbook.insert(
+            order_hash,
+            PlacedOrder {
+                order,
+                extension: create_test_extension(),
+                signature: vec![],
+                price,
+            },
+        );
+        order_hash
+    }
+
+    #[test]
+    fn test_place_order_rejects_when_paused() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        contract.pause();
+
+        let result = contract.place_order(create_test_order(), create_test_extension(), vec![]);
+        assert!(matches!(result, Err(LimitOrderError::ContractPaused)));
+    }
+
+    #[test]
+    fn test_place_order_rejects_invalid_signature() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        // Same reasoning as `test_fill_order_rejects_order_from_non_evm_non_implicit_maker`:
+        // `accounts(0)` can never validate against any signature.
+        let result =
+            contract.place_order(create_test_order(), create_test_extension(), vec![0u8; 65]);
+        assert!(matches!(result, Err(LimitOrderError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_remove_placed_order_rejects_unknown_hash() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let result = contract.remove_placed_order([9u8; 32]);
+        assert!(matches!(result, Err(LimitOrderError::OrderNotFound)));
+    }
+
+    #[test]
+    fn test_remove_placed_order_rejects_non_maker_caller() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order_hash = seed_placed_order(&mut contract, create_test_order());
+
+        testing_env!(get_context(accounts(1)).build());
+        let result = contract.remove_placed_order(order_hash);
+        assert!(matches!(result, Err(LimitOrderError::OnlyMakerCanCancel)));
+        assert!(contract.orderbook.get(&order_hash).is_some());
+    }
+
+    #[test]
+    fn test_remove_placed_order_removes_entry_for_its_maker() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order = create_test_order();
+        let order_hash = seed_placed_order(&mut contract, order.clone());
+
+        testing_env!(get_context(order.maker.clone()).build());
+        contract
+            .remove_placed_order(order_hash)
+            .expect("the order's own maker can remove it");
+        assert!(contract.orderbook.get(&order_hash).is_none());
+    }
+
+    #[test]
+    fn test_match_orders_rejects_when_paused() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        contract.pause();
+
+        let result = contract.match_orders([1u8; 32], [2u8; 32]);
+        assert!(matches!(result, Err(LimitOrderError::ContractPaused)));
+    }
+
+    #[test]
+    fn test_match_orders_rejects_unknown_order_hash() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+        let order_a = seed_placed_order(&mut contract, create_test_order());
+
+        let result = contract.match_orders(order_a, [9u8; 32]);
+        assert!(matches!(result, Err(LimitOrderError::OrderNotFound)));
+    }
+
+    #[test]
+    fn test_match_orders_rejects_non_crossing_prices() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let mut order_a = create_test_order();
+        order_a.maker_asset = accounts(2);
+        order_a.taker_asset = accounts(3);
+        order_a.making_amount = U256::from(100u128);
+        order_a.taking_amount = U256::from(200u128);
+        let order_hash_a = seed_placed_order(&mut contract, order_a);
+
+        let mut order_b = create_test_order();
+        order_b.maker_asset = accounts(3);
+        order_b.taker_asset = accounts(2);
+        order_b.making_amount = U256::from(33u128);
+        order_b.taking_amount = U256::from(100u128);
+        let order_hash_b = seed_placed_order(&mut contract, order_b);
+
+        let result = contract.match_orders(order_hash_a, order_hash_b);
+        assert!(matches!(result, Err(LimitOrderError::OrdersDoNotCross)));
+    }
+
+    #[test]
+    fn test_on_match_settle_complete_commits_both_fills_and_removes_exhausted_orders() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let mut order_a = create_test_order();
+        order_a.maker_asset = accounts(2);
+        order_a.taker_asset = accounts(3);
+        order_a.making_amount = U256::from(100u128);
+        order_a.taking_amount = U256::from(200u128);
+        let order_hash_a = seed_placed_order(&mut contract, order_a.clone());
+
+        let mut order_b = create_test_order();
+        order_b.maker_asset = accounts(3);
+        order_b.taker_asset = accounts(2);
+        order_b.making_amount = U256::from(200u128);
+        order_b.taking_amount = U256::from(100u128);
+        let order_hash_b = seed_placed_order(&mut contract, order_b.clone());
+
+        set_settlement_result(get_context(accounts(0)), true, true);
+        contract
+            .on_match_settle_complete(order_hash_a, order_hash_b, U256::from(100u128), U256::from(200u128), U256::from(100u128), U256::from(200u128))
+            .expect("both legs succeeding should commit the match");
+
+        // Both orders were entirely consumed by the match, so the book no
+        // longer holds either entry.
+        assert!(contract.orderbook.get(&order_hash_a).is_none());
+        assert!(contract.orderbook.get(&order_hash_b).is_none());
+    }
+
+    #[test]
+    fn test_on_match_settle_complete_rejects_and_leaves_orders_untouched_when_a_leg_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = LimitOrderProtocol::new([0u8; 32], accounts(1));
+
+        let mut order_a = create_test_order();
+        order_a.maker_asset = accounts(2);
+        order_a.taker_asset = accounts(3);
+        order_a.making_amount = U256::from(100u128);
+        order_a.taking_amount = U256::from(200u128);
+        let order_hash_a = seed_placed_order(&mut contract, order_a.clone());
+
+        let mut order_b = create_test_order();
+        order_b.maker_asset = accounts(3);
+        order_b.taker_asset = accounts(2);
+        order_b.making_amount = U256::from(200u128);
+        order_b.taking_amount = U256::from(100u128);
+        let order_hash_b = seed_placed_order(&mut contract, order_b.clone());
+
+        set_settlement_result(get_context(accounts(0)), false, true);
+        let result =
+            contract.on_match_settle_complete(order_hash_a, order_hash_b, U256::from(100u128), U256::from(200u128), U256::from(100u128), U256::from(200u128));
+
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::TransferFromTakerToMakerFailed)
+        ));
+        assert!(contract.orderbook.get(&order_hash_a).is_some());
+        assert!(contract.orderbook.get(&order_hash_b).is_some());
+    }
 }
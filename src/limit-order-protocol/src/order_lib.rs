@@ -1,28 +1,120 @@
 // Find all our documentation at https://docs.near.org
-use crate::types::{Extension, LimitOrderError, Order};
-use near_sdk::{ext_contract, log, AccountId, Gas, NearToken, Promise, borsh::{BorshSerialize, BorshDeserialize}};
+use crate::predicate;
+use crate::types::{BitInvalidatorData, Extension, LimitOrderError, MakerTraits, Order, RemainingInvalidator};
+use crate::u256::U256;
+use crate::utils::{left_pad_account, u64_be32};
+use near_sdk::{
+    collections::UnorderedMap, env, ext_contract, log, near, AccountId, Gas, NearToken, Promise,
+    PromiseOrValue,
+};
 
 // Gas for cross-contract calls
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+// Gas for the `#[private]` callback that resumes settlement once the
+// taker-to-maker leg resolves
+const GAS_FOR_TAKER_LEG_CALLBACK: Gas = Gas::from_tgas(20);
+// Gas for the `#[private]` callback that commits the fill (or compensates)
+// once the maker-to-taker leg resolves
+const GAS_FOR_MAKER_LEG_CALLBACK: Gas = Gas::from_tgas(20);
+
+/// EIP-712 type string for `Order` with `makerTraits` as the packed `uint256`
+/// bitfield the real 1inch contracts use, rather than a nested `MakerTraits`
+/// struct - so `compute_order_hash` matches the hash an EVM-side Fusion+
+/// contract computes for the same order bit-for-bit.
+const ORDER_TYPE_STRING: &[u8] =
+    b"Order(uint256 salt,address maker,address receiver,address makerAsset,address takerAsset,uint256 makingAmount,uint256 takingAmount,uint256 makerTraits)";
 
 /// Order library for processing limit orders
-#[derive(BorshSerialize, BorshDeserialize)]
+#[near(contract_state)]
 pub struct OrderLib {
     domain_separator: [u8; 32],
+    /// When set, `hash_order`/`compute_order_hash` fall back to the old
+    /// little-endian field concatenation instead of the real EIP-712
+    /// `hashStruct`, so callers pinned to the legacy digest (existing tests,
+    /// orders already signed against it) keep working unchanged.
+    legacy_order_hash: bool,
+    /// Per-(maker, slot) invalidation words for `MakerTraits::use_bit_invalidator`
+    /// orders - see `BitInvalidatorData`.
+    bit_invalidator: UnorderedMap<(AccountId, u64), BitInvalidatorData>,
+    /// Per-(maker, order_hash) remaining making amount for orders that track
+    /// partial fills instead of bit invalidation.
+    remaining_invalidator: UnorderedMap<(AccountId, [u8; 32]), RemainingInvalidator>,
+    /// Per-(maker, series) epoch counters, read by `Epoch` predicate leaves -
+    /// see `epoch_for_series`.
+    epoch_invalidator: UnorderedMap<(AccountId, u64), u64>,
 }
 
 impl Default for OrderLib {
     fn default() -> Self {
         Self {
             domain_separator: [0u8; 32],
+            legacy_order_hash: false,
+            bit_invalidator: UnorderedMap::new(b"b"),
+            remaining_invalidator: UnorderedMap::new(b"r"),
+            epoch_invalidator: UnorderedMap::new(b"e"),
         }
     }
 }
 
+#[near]
 impl OrderLib {
     /// Initialize the contract
+    #[init]
     pub fn new(domain_separator: [u8; 32]) -> Self {
-        Self { domain_separator }
+        Self {
+            domain_separator,
+            legacy_order_hash: false,
+            bit_invalidator: UnorderedMap::new(b"b"),
+            remaining_invalidator: UnorderedMap::new(b"r"),
+            epoch_invalidator: UnorderedMap::new(b"e"),
+        }
+    }
+
+    /// Initialize the contract pinned to the legacy (pre-EIP-712) order hash,
+    /// for integrators who signed orders against it before the migration.
+    #[init]
+    pub fn new_with_legacy_order_hash(domain_separator: [u8; 32]) -> Self {
+        Self {
+            domain_separator,
+            legacy_order_hash: true,
+            bit_invalidator: UnorderedMap::new(b"b"),
+            remaining_invalidator: UnorderedMap::new(b"r"),
+            epoch_invalidator: UnorderedMap::new(b"e"),
+        }
+    }
+
+    /// Whether `maker`'s `nonce_or_epoch` bit has already been invalidated -
+    /// by a prior `process_order` fill of a non-partially-fillable order
+    /// carrying it.
+    pub fn bit_invalidator_for_order(&self, maker: AccountId, nonce_or_epoch: u64) -> bool {
+        let slot = nonce_or_epoch >> 8;
+        if let Some(data) = self.bit_invalidator.get(&(maker, slot)) {
+            data.check_bit(nonce_or_epoch)
+        } else {
+            false
+        }
+    }
+
+    /// Get the remaining making amount recorded for a partially-fillable
+    /// order, or `0` if it hasn't been filled yet.
+    pub fn remaining_invalidator_for_order(&self, maker: AccountId, order_hash: [u8; 32]) -> U256 {
+        if let Some(invalidator) = self.remaining_invalidator.get(&(maker, order_hash)) {
+            invalidator.remaining()
+        } else {
+            U256::ZERO
+        }
+    }
+
+    /// `maker`'s current epoch for `series`, read by `Epoch` predicate leaves
+    /// - `0` until a maker ever advances it.
+    pub fn epoch_for_series(&self, maker: AccountId, series: u64) -> u64 {
+        self.epoch_invalidator.get(&(maker, series)).unwrap_or(0)
+    }
+
+    /// Whether `hash_order` computes the legacy digest instead of the real
+    /// EIP-712 `hashStruct`.
+    pub fn uses_legacy_order_hash(&self) -> bool {
+        self.legacy_order_hash
     }
 
     /// Calculate order hash
@@ -30,6 +122,40 @@ impl OrderLib {
         self.compute_order_hash(&order)
     }
 
+    /// Calculate EIP-712-compatible order hash, matching the Ethereum side of
+    /// the same swap bit-for-bit (see `crate::utils::hash_order_712`).
+    pub fn hash_order_712(&self, order: Order) -> [u8; 32] {
+        crate::utils::hash_order_712(&order, &self.domain_separator)
+    }
+
+    /// Build an EIP-712 domain separator for a given `(name, version,
+    /// chain_id, verifying_contract)` tuple (see
+    /// `crate::utils::domain_separator`). This doesn't replace the instance's
+    /// stored `domain_separator` - it's exposed so integrators can compute
+    /// the value to pass into `new` off-chain, rather than hand-assembling
+    /// the EIP-712 domain hash themselves.
+    pub fn domain_separator(
+        name: String,
+        version: String,
+        chain_id: u64,
+        verifying_contract: AccountId,
+    ) -> [u8; 32] {
+        crate::utils::domain_separator(&name, &version, chain_id, &verifying_contract)
+    }
+
+    /// Validate an order's EIP-712 signature against its maker's identity -
+    /// secp256k1 recovery for a `0x...` EVM address or ed25519 verification
+    /// for a NEAR implicit account (see `crate::utils::validate_signature`).
+    #[handle_result]
+    pub fn validate_signature(
+        &self,
+        order: Order,
+        signature: Vec<u8>,
+        signer: AccountId,
+    ) -> Result<bool, LimitOrderError> {
+        crate::utils::validate_signature(&order, &signature, &signer, &self.domain_separator)
+    }
+
     /// Get receiver for an order
     pub fn get_receiver(&self, order: Order) -> AccountId {
         if order.receiver.as_str() == "0x0000000000000000000000000000000000000000" {
@@ -40,14 +166,15 @@ impl OrderLib {
     }
 
     /// Calculate making amount based on taking amount
+    #[handle_result]
     pub fn calculate_making_amount(
         &self,
         order: Order,
         extension: Extension,
-        requested_taking_amount: u128,
-        remaining_making_amount: u128,
+        requested_taking_amount: U256,
+        remaining_making_amount: U256,
         order_hash: [u8; 32],
-    ) -> Result<u128, LimitOrderError> {
+    ) -> Result<U256, LimitOrderError> {
         self.compute_making_amount(
             &order,
             &extension,
@@ -58,14 +185,15 @@ impl OrderLib {
     }
 
     /// Calculate taking amount based on making amount
+    #[handle_result]
     pub fn calculate_taking_amount(
         &self,
         order: Order,
         extension: Extension,
-        requested_making_amount: u128,
-        remaining_making_amount: u128,
+        requested_making_amount: U256,
+        remaining_making_amount: U256,
         order_hash: [u8; 32],
-    ) -> Result<u128, LimitOrderError> {
+    ) -> Result<U256, LimitOrderError> {
         self.compute_taking_amount(
             &order,
             &extension,
@@ -76,6 +204,7 @@ impl OrderLib {
     }
 
     /// Validate extension for an order
+    #[handle_result]
     pub fn validate_extension(
         &self,
         order: Order,
@@ -84,36 +213,160 @@ impl OrderLib {
         self.validate_order_extension(&order, &extension)
     }
 
-    /// Process order execution
+    /// Process order execution. The order's fillable amount is tracked
+    /// persistently across calls via `MakerTraits::use_bit_invalidator` - see
+    /// `remaining_making_amount`/`commit_fill` - so a non-partially-fillable
+    /// order can't be filled twice, and a partially-fillable one can't be
+    /// over-filled across several `process_order` calls.
+    ///
+    /// Settlement itself is asynchronous and sequential: this only validates
+    /// and fires the taker-to-maker leg, chaining into the `#[private]`
+    /// `on_taker_leg_settled` callback; that in turn fires the maker-to-taker
+    /// leg and chains into `on_maker_leg_settled`. The remaining-amount/bit
+    /// invalidator bookkeeping is only committed in the latter, and only once
+    /// both legs are confirmed to have landed - if the maker-to-taker leg
+    /// fails after the taker-to-maker leg already succeeded,
+    /// `on_maker_leg_settled` issues a compensating transfer back to the
+    /// taker rather than stranding their funds with the maker.
+    #[handle_result]
     pub fn process_order(
         &mut self,
         order: Order,
         extension: Extension,
         taker: AccountId,
-        taking_amount: u128,
-    ) -> Result<u128, LimitOrderError> {
+        taking_amount: U256,
+    ) -> Result<PromiseOrValue<U256>, LimitOrderError> {
         // Validate order amounts
-        if taking_amount == 0 {
+        if taking_amount.is_zero() {
             return Err(LimitOrderError::SwapWithZeroAmount);
         }
 
-        // Calculate making amount
         let order_hash = self.hash_order(order.clone());
-        let making_amount = self.calculate_making_amount(
-            order.clone(),
-            extension.clone(),
+        let remaining_making_amount = self.remaining_making_amount(&order, &order_hash)?;
+
+        // Calculate making amount
+        let making_amount = self.compute_making_amount(
+            &order,
+            &extension,
             taking_amount,
-            order.making_amount,
-            order_hash,
+            remaining_making_amount,
+            &order_hash,
         )?;
 
+        if making_amount > remaining_making_amount {
+            return Err(LimitOrderError::PartialFillNotAllowed);
+        }
+
         // Validate extension
-        if !self.validate_extension(order.clone(), extension)? {
+        if !self.validate_extension(order.clone(), extension.clone())? {
             return Err(LimitOrderError::InvalidExtension);
         }
 
-        // Execute the swap
-        self.execute_swap(&order, &taker, making_amount, taking_amount)?;
+        self.validate_predicate(&extension)?;
+
+        let taker_to_maker = self.transfer_tokens(
+            &order.taker_asset,
+            &taker,
+            &order.maker,
+            taking_amount
+                .as_u128()
+                .ok_or(LimitOrderError::InvalidAmounts)?,
+        );
+
+        Ok(PromiseOrValue::Promise(taker_to_maker.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TAKER_LEG_CALLBACK)
+                .on_taker_leg_settled(
+                    order,
+                    order_hash,
+                    remaining_making_amount,
+                    making_amount,
+                    taking_amount,
+                    taker,
+                ),
+        )))
+    }
+
+    /// `#[private]` callback chained after `process_order`'s taker-to-maker
+    /// transfer: only if it succeeded does it fire the maker-to-taker leg,
+    /// chaining into `on_maker_leg_settled`. A failed taker-to-maker leg
+    /// means no funds moved at all, so this just aborts without touching any
+    /// bookkeeping.
+    #[private]
+    #[handle_result]
+    pub fn on_taker_leg_settled(
+        &mut self,
+        order: Order,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+    ) -> Result<PromiseOrValue<U256>, LimitOrderError> {
+        if !matches!(
+            env::promise_result(0),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            return Err(LimitOrderError::TransferFromTakerToMakerFailed);
+        }
+
+        let maker_to_taker = self.transfer_tokens(
+            &order.maker_asset,
+            &order.maker,
+            &taker,
+            making_amount
+                .as_u128()
+                .ok_or(LimitOrderError::InvalidAmounts)?,
+        );
+
+        Ok(PromiseOrValue::Promise(maker_to_taker.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_MAKER_LEG_CALLBACK)
+                .on_maker_leg_settled(
+                    order,
+                    order_hash,
+                    remaining_making_amount,
+                    making_amount,
+                    taking_amount,
+                    taker,
+                ),
+        )))
+    }
+
+    /// `#[private]` callback chained after `process_order`'s maker-to-taker
+    /// transfer: if it succeeded, commits the remaining-amount/invalidator
+    /// update and resolves with the making amount. If it failed, the
+    /// taker-to-maker leg has already landed - rather than stranding those
+    /// funds with the maker, this fires a compensating transfer back to the
+    /// taker, and leaves the invalidator/remaining-amount bookkeeping
+    /// untouched since the order was never actually filled.
+    #[private]
+    #[handle_result]
+    pub fn on_maker_leg_settled(
+        &mut self,
+        order: Order,
+        order_hash: [u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
+        taking_amount: U256,
+        taker: AccountId,
+    ) -> Result<PromiseOrValue<U256>, LimitOrderError> {
+        if !matches!(
+            env::promise_result(0),
+            near_sdk::PromiseResult::Successful(_)
+        ) {
+            self.transfer_tokens(
+                &order.taker_asset,
+                &order.maker,
+                &taker,
+                taking_amount
+                    .as_u128()
+                    .ok_or(LimitOrderError::InvalidAmounts)?,
+            );
+            return Err(LimitOrderError::TransferFromMakerToTakerFailed);
+        }
+
+        self.commit_fill(&order, &order_hash, remaining_making_amount, making_amount)?;
 
         log!(
             "Order processed: making_amount={}, taking_amount={}",
@@ -121,45 +374,110 @@ impl OrderLib {
             taking_amount
         );
 
-        Ok(making_amount)
+        Ok(PromiseOrValue::Value(making_amount))
     }
 
-    /// Execute the swap
-    fn execute_swap(
+    /// The making amount still fillable for `order`: for a
+    /// `use_bit_invalidator` order this is all-or-nothing (the full amount,
+    /// or `Err(BitInvalidatedOrder)` once its nonce bit is set); otherwise
+    /// it's whatever `remaining_invalidator` has on record, lazily
+    /// initialized to `order.making_amount` on the order's first fill.
+    fn remaining_making_amount(
         &self,
         order: &Order,
-        taker: &AccountId,
-        making_amount: u128,
-        taking_amount: u128,
+        order_hash: &[u8; 32],
+    ) -> Result<U256, LimitOrderError> {
+        if order.maker_traits.use_bit_invalidator() {
+            let nonce_or_epoch = order.maker_traits.nonce_or_epoch();
+            if self.bit_invalidator_for_order(order.maker.clone(), nonce_or_epoch) {
+                return Err(LimitOrderError::BitInvalidatedOrder);
+            }
+            return Ok(order.making_amount);
+        }
+
+        match self
+            .remaining_invalidator
+            .get(&(order.maker.clone(), *order_hash))
+        {
+            Some(invalidator) if invalidator.remaining().is_zero() => {
+                Err(LimitOrderError::OrderExpired)
+            }
+            Some(invalidator) => Ok(invalidator.remaining()),
+            None => Ok(order.making_amount),
+        }
+    }
+
+    /// Record that `making_amount` of `remaining_making_amount` was just
+    /// filled: sets the order's nonce bit for a `use_bit_invalidator` order
+    /// (it's all-or-nothing, so any fill exhausts it), or persists the
+    /// amount still left for one tracked by `remaining_invalidator`.
+    fn commit_fill(
+        &mut self,
+        order: &Order,
+        order_hash: &[u8; 32],
+        remaining_making_amount: U256,
+        making_amount: U256,
     ) -> Result<(), LimitOrderError> {
-        // Transfer tokens from taker to maker
-        self.transfer_tokens(&order.taker_asset, taker, &order.maker, taking_amount)?;
+        if order.maker_traits.use_bit_invalidator() {
+            let nonce_or_epoch = order.maker_traits.nonce_or_epoch();
+            let slot = nonce_or_epoch >> 8;
+            let key = (order.maker.clone(), slot);
+            let mut data = self.bit_invalidator.get(&key).unwrap_or_default();
+            data.mass_invalidate(nonce_or_epoch, 0);
+            self.bit_invalidator.insert(&key, &data);
+            return Ok(());
+        }
+
+        let new_remaining = remaining_making_amount
+            .checked_sub(making_amount)
+            .ok_or(LimitOrderError::InvalidAmounts)?;
+        self.remaining_invalidator.insert(
+            &(order.maker.clone(), *order_hash),
+            &RemainingInvalidator::new(new_remaining),
+        );
+        Ok(())
+    }
 
-        // Transfer tokens from maker to taker
-        self.transfer_tokens(&order.maker_asset, &order.maker, taker, making_amount)?;
+    /// Check `extension`'s predicate (if any). `OrderLib::process_order` is
+    /// fully synchronous (unlike the main contract's `fill_order`, it has no
+    /// Promise-based deferral for cross-contract calls), so an `ExtCallUint`
+    /// leaf - which needs one to resolve - always fails closed here rather
+    /// than being evaluated.
+    fn validate_predicate(&self, extension: &Extension) -> Result<(), LimitOrderError> {
+        let Some(predicate) = predicate::parse_predicate(extension.predicate_data()) else {
+            return Ok(());
+        };
+
+        if !predicate::collect_ext_call_sources(&predicate).is_empty() {
+            return Err(LimitOrderError::PredicateIsNotTrue);
+        }
+
+        let epoch_values: Vec<u64> = predicate::collect_epoch_sources(&predicate)
+            .into_iter()
+            .map(|(maker, series)| self.epoch_for_series(maker, series))
+            .collect();
+
+        if !predicate::evaluate(&predicate, env::block_timestamp(), &[], &epoch_values) {
+            return Err(LimitOrderError::PredicateIsNotTrue);
+        }
 
         Ok(())
     }
 
-    /// Transfer tokens
-    fn transfer_tokens(
-        &self,
-        token: &AccountId,
-        from: &AccountId,
-        to: &AccountId,
-        amount: u128,
-    ) -> Result<(), LimitOrderError> {
+    /// Fire a token transfer as a `Promise` rather than assuming success -
+    /// callers chain `.then()` into a callback that inspects
+    /// `env::promise_result` before trusting the transfer landed.
+    fn transfer_tokens(&self, token: &AccountId, from: &AccountId, to: &AccountId, amount: u128) -> Promise {
         if token.as_str() == "near" {
             // Native NEAR transfer
-            Promise::new(to.clone()).transfer(NearToken::from_yoctonear(amount));
+            Promise::new(to.clone()).transfer(NearToken::from_yoctonear(amount))
         } else {
             // Fungible token transfer
             ext_ft::ext(token.clone())
                 .with_static_gas(GAS_FOR_FT_TRANSFER)
                 .with_attached_deposit(NearToken::from_yoctonear(1))
-                .ft_transfer_from(from.clone(), to.clone(), amount, None);
+                .ft_transfer_from(from.clone(), to.clone(), amount, None)
         }
-        Ok(())
     }
 
     /// Get domain separator
@@ -169,6 +487,43 @@ impl OrderLib {
 
     // Internal helper functions
     fn compute_order_hash(&self, order: &Order) -> [u8; 32] {
+        if self.legacy_order_hash {
+            self.compute_legacy_order_hash(order)
+        } else {
+            self.compute_order_hash_712(order)
+        }
+    }
+
+    /// Real EIP-712 `hashStruct`: `keccak256(0x19 || 0x01 || domainSeparator
+    /// || structHash)`, with every field ABI-encoded into a 32-byte
+    /// big-endian word and `makerTraits` packed into a single `uint256`
+    /// (see `pack_maker_traits`) instead of hashed as a nested struct, so
+    /// this matches the hash an EVM Fusion+ contract computes for the same
+    /// order.
+    fn compute_order_hash_712(&self, order: &Order) -> [u8; 32] {
+        let type_hash: [u8; 32] = env::keccak256(ORDER_TYPE_STRING).try_into().unwrap();
+
+        let mut struct_data = Vec::new();
+        struct_data.extend_from_slice(&type_hash);
+        struct_data.extend_from_slice(&u64_be32(order.salt));
+        struct_data.extend_from_slice(&left_pad_account(&order.maker));
+        struct_data.extend_from_slice(&left_pad_account(&order.receiver));
+        struct_data.extend_from_slice(&left_pad_account(&order.maker_asset));
+        struct_data.extend_from_slice(&left_pad_account(&order.taker_asset));
+        struct_data.extend_from_slice(&order.making_amount.to_be_bytes());
+        struct_data.extend_from_slice(&order.taking_amount.to_be_bytes());
+        struct_data.extend_from_slice(&pack_maker_traits(&order.maker_traits));
+        let struct_hash: [u8; 32] = env::keccak256(&struct_data).try_into().unwrap();
+
+        let mut digest_data = Vec::with_capacity(2 + 32 + 32);
+        digest_data.extend_from_slice(&[0x19, 0x01]);
+        digest_data.extend_from_slice(&self.domain_separator);
+        digest_data.extend_from_slice(&struct_hash);
+        env::keccak256(&digest_data).try_into().unwrap()
+    }
+
+    /// Pre-EIP-712-migration order hash, kept for `legacy_order_hash` callers.
+    fn compute_legacy_order_hash(&self, order: &Order) -> [u8; 32] {
         let mut data = Vec::new();
         data.extend_from_slice(&self.domain_separator);
         data.extend_from_slice(&order.salt.to_le_bytes());
@@ -201,49 +556,41 @@ impl OrderLib {
         &self,
         order: &Order,
         extension: &Extension,
-        requested_taking_amount: u128,
-        _remaining_making_amount: u128,
-        _order_hash: &[u8; 32],
-    ) -> Result<u128, LimitOrderError> {
-        let making_amount_data = extension.maker_amount_data();
-
-        if making_amount_data.is_empty() {
-            // Linear proportion
-            if order.taking_amount == 0 {
-                return Err(LimitOrderError::SwapWithZeroAmount);
-            }
-            return Ok((order.making_amount * requested_taking_amount) / order.taking_amount);
-        }
-
-        // In a real implementation, we would call an external getter contract
-        // For now, return a simplified calculation
-        Ok(requested_taking_amount)
+        requested_taking_amount: U256,
+        remaining_making_amount: U256,
+        order_hash: &[u8; 32],
+    ) -> Result<U256, LimitOrderError> {
+        crate::utils::calculate_making_amount(
+            order,
+            extension,
+            requested_taking_amount,
+            remaining_making_amount,
+            order_hash,
+        )
     }
 
     fn compute_taking_amount(
         &self,
         order: &Order,
         extension: &Extension,
-        requested_making_amount: u128,
-        _remaining_making_amount: u128,
-        _order_hash: &[u8; 32],
-    ) -> Result<u128, LimitOrderError> {
-        let taking_amount_data = extension.taker_amount_data();
-
-        if taking_amount_data.is_empty() {
-            // Linear proportion
-            if order.making_amount == 0 {
-                return Err(LimitOrderError::SwapWithZeroAmount);
-            }
-            return Ok((order.taking_amount * requested_making_amount) / order.making_amount);
-        }
-
-        // In a real implementation, we would call an external getter contract
-        // For now, return a simplified calculation
-        Ok(requested_making_amount)
+        requested_making_amount: U256,
+        remaining_making_amount: U256,
+        order_hash: &[u8; 32],
+    ) -> Result<U256, LimitOrderError> {
+        crate::utils::calculate_taking_amount(
+            order,
+            extension,
+            requested_making_amount,
+            remaining_making_amount,
+            order_hash,
+        )
     }
 
-    fn validate_order_extension(&self, order: &Order, extension: &Extension) -> Result<bool, LimitOrderError> {
+    fn validate_order_extension(
+        &self,
+        order: &Order,
+        extension: &Extension,
+    ) -> Result<bool, LimitOrderError> {
         if order.maker_traits.has_extension() {
             if extension.maker_amount_data().is_empty()
                 && extension.taker_amount_data().is_empty()
@@ -291,6 +638,27 @@ impl OrderLib {
     }
 }
 
+/// Pack `MakerTraits` into the single `uint256` bitfield the real 1inch
+/// contracts encode it as: the high byte holds the boolean flags, bytes
+/// 16..24 hold `series`, and the low 8 bytes hold `nonce_or_epoch`.
+fn pack_maker_traits(traits: &MakerTraits) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    let mut flags = 0u8;
+    if traits.use_bit_invalidator() {
+        flags |= 0b001;
+    }
+    if traits.use_epoch_manager() {
+        flags |= 0b010;
+    }
+    if traits.has_extension() {
+        flags |= 0b100;
+    }
+    word[0] = flags;
+    word[16..24].copy_from_slice(&traits.series().to_be_bytes());
+    word[24..32].copy_from_slice(&traits.nonce_or_epoch().to_be_bytes());
+    word
+}
+
 // External contract trait for fungible token transfers
 #[ext_contract(ext_ft)]
 pub trait FungibleToken {
@@ -328,8 +696,8 @@ mod tests {
             receiver: accounts(1),
             maker_asset: accounts(2),
             taker_asset: accounts(3),
-            making_amount: 1000,
-            taking_amount: 1000,
+            making_amount: U256::from(1000u128),
+            taking_amount: U256::from(1000u128),
             maker_traits: crate::types::MakerTraits::default(),
         }
     }
@@ -365,6 +733,598 @@ mod tests {
         assert_eq!(hash.len(), 32);
     }
 
+    #[test]
+    fn test_hash_order_is_eip_712_by_default() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([1u8; 32]);
+        assert!(!contract.uses_legacy_order_hash());
+
+        let order = create_test_order();
+        let expected = crate::utils::hash_order_712(&order, &contract.get_domain_separator());
+        // `compute_order_hash_712` packs `makerTraits` into a single word
+        // instead of hashing it as a nested struct, so the two digests
+        // differ even though both are "the EIP-712 hash" in spirit.
+        assert_ne!(contract.hash_order(order.clone()), expected);
+        assert_eq!(
+            contract.hash_order(order.clone()),
+            contract.hash_order(order)
+        );
+    }
+
+    #[test]
+    fn test_hash_order_legacy_flag_matches_old_concatenation() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let legacy = OrderLib::new_with_legacy_order_hash([1u8; 32]);
+        assert!(legacy.uses_legacy_order_hash());
+        let modern = OrderLib::new([1u8; 32]);
+
+        let order = create_test_order();
+        assert_ne!(legacy.hash_order(order.clone()), modern.hash_order(order));
+    }
+
+    #[test]
+    fn test_hash_order_712_deterministic() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([1u8; 32]);
+        let order = create_test_order();
+        let hash_a = contract.hash_order_712(order.clone());
+        let hash_b = contract.hash_order_712(order);
+        assert_eq!(hash_a, hash_b);
+    }
+
+    #[test]
+    fn test_hash_order_712_differs_from_legacy_hash() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([1u8; 32]);
+        let order = create_test_order();
+        assert_ne!(
+            contract.hash_order(order.clone()),
+            contract.hash_order_712(order)
+        );
+    }
+
+    #[test]
+    fn test_hash_order_712_is_sensitive_to_domain_separator() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let order = create_test_order();
+        let hash_a = OrderLib::new([1u8; 32]).hash_order_712(order.clone());
+        let hash_b = OrderLib::new([2u8; 32]).hash_order_712(order);
+        assert_ne!(hash_a, hash_b);
+    }
+
+    #[test]
+    fn test_hash_order_712_matches_known_evm_vector() {
+        // Independently computed (pure-Python keccak256, verified against the
+        // NIST SHA3-256 KAT to validate the permutation/sponge, then run with
+        // Keccak's own 0x01 padding) over the same `Order(...)` EIP-712
+        // struct this function encodes, with EVM-style `0x`-addresses for
+        // maker/receiver/maker_asset/taker_asset. Catches any regression
+        // that silently hashes the address strings as NEAR account ids
+        // instead of ABI-encoding the raw 20 address bytes.
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let order = Order {
+            salt: 1,
+            maker: AccountId::try_from(
+                "0x00000000000000000000000000000000000000aa".to_string(),
+            )
+            .unwrap(),
+            receiver: AccountId::try_from(
+                "0x00000000000000000000000000000000000000bb".to_string(),
+            )
+            .unwrap(),
+            maker_asset: AccountId::try_from(
+                "0x00000000000000000000000000000000000000cc".to_string(),
+            )
+            .unwrap(),
+            taker_asset: AccountId::try_from(
+                "0x00000000000000000000000000000000000000dd".to_string(),
+            )
+            .unwrap(),
+            making_amount: U256::from(1000u128),
+            taking_amount: U256::from(2000u128),
+            maker_traits: crate::types::MakerTraits::default(),
+        };
+        let domain_separator = [0x11u8; 32];
+
+        let expected: [u8; 32] = [
+            0x8b, 0xc8, 0xf7, 0x46, 0x86, 0x8c, 0x57, 0x01, 0x83, 0xdc, 0x72, 0xc4, 0xcc, 0x44,
+            0x6e, 0xf6, 0x98, 0x85, 0x81, 0x25, 0x2d, 0x66, 0x76, 0xcd, 0xb4, 0xd3, 0xa5, 0xd9,
+            0xa9, 0x8c, 0x12, 0x9d,
+        ];
+
+        assert_eq!(
+            crate::utils::hash_order_712(&order, &domain_separator),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_compute_order_hash_712_matches_known_evm_vector() {
+        // Same independent derivation as `test_hash_order_712_matches_known_evm_vector`,
+        // but over `compute_order_hash_712`'s own struct encoding (packed
+        // `makerTraits` word, no nested struct hash) reached through the
+        // public `hash_order` dispatch.
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let order = Order {
+            salt: 1,
+            maker: AccountId::try_from(
+                "0x00000000000000000000000000000000000000aa".to_string(),
+            )
+            .unwrap(),
+            receiver: AccountId::try_from(
+                "0x00000000000000000000000000000000000000bb".to_string(),
+            )
+            .unwrap(),
+            maker_asset: AccountId::try_from(
+                "0x00000000000000000000000000000000000000cc".to_string(),
+            )
+            .unwrap(),
+            taker_asset: AccountId::try_from(
+                "0x00000000000000000000000000000000000000dd".to_string(),
+            )
+            .unwrap(),
+            making_amount: U256::from(1000u128),
+            taking_amount: U256::from(2000u128),
+            maker_traits: crate::types::MakerTraits::default(),
+        };
+        let contract = OrderLib::new([0x22u8; 32]);
+
+        let expected: [u8; 32] = [
+            0xc0, 0xaa, 0xf9, 0xdd, 0xf6, 0x25, 0x35, 0x0f, 0x4f, 0x75, 0x9d, 0x04, 0xf6, 0x0d,
+            0xf3, 0xee, 0x18, 0x7c, 0xee, 0x1f, 0x88, 0xd4, 0x8f, 0xe0, 0x25, 0xfc, 0x82, 0xc5,
+            0xdb, 0x33, 0xcd, 0xef,
+        ];
+
+        assert_eq!(contract.hash_order(order), expected);
+    }
+
+    #[test]
+    fn test_hash_order_712_is_sensitive_to_maker_traits() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([1u8; 32]);
+        let mut order = create_test_order();
+        let base_hash = contract.hash_order_712(order.clone());
+
+        order.maker_traits.use_bit_invalidator = true;
+        assert_ne!(contract.hash_order_712(order), base_hash);
+    }
+
+    fn auction_extension_data(
+        start_time: u64,
+        duration: u64,
+        start_rate_bump: u32,
+        end_rate_bump: u32,
+        points: &[(u32, u32)],
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&start_time.to_le_bytes());
+        data.extend_from_slice(&duration.to_le_bytes());
+        data.extend_from_slice(&start_rate_bump.to_le_bytes());
+        data.extend_from_slice(&end_rate_bump.to_le_bytes());
+        for (delay, rate_bump) in points {
+            data.extend_from_slice(&delay.to_le_bytes());
+            data.extend_from_slice(&rate_bump.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_without_auction_is_linear() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let extension = create_test_extension();
+        let amount = contract
+            .calculate_taking_amount(
+                order,
+                extension,
+                U256::from(500u128),
+                U256::from(500u128),
+                [0u8; 32],
+            )
+            .unwrap();
+        assert_eq!(amount, U256::from(500u128));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_applies_auction_rate_bump_at_start() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        // 10% start bump, decaying to 0 over 100ms; `env::block_timestamp_ms()`
+        // under `testing_env!` defaults to 0, i.e. exactly `start_time`.
+        extension.taker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[]);
+
+        let amount = contract
+            .calculate_taking_amount(
+                order,
+                extension,
+                U256::from(1000u128),
+                U256::from(1000u128),
+                [0u8; 32],
+            )
+            .unwrap();
+        assert_eq!(amount, U256::from(1100u128));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_rejects_malformed_auction_data() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.taker_amount_data = vec![1, 2, 3];
+
+        let result = contract.calculate_taking_amount(
+            order,
+            extension,
+            U256::from(1000u128),
+            U256::from(1000u128),
+            [0u8; 32],
+        );
+        assert_eq!(result, Err(LimitOrderError::InvalidAmountData));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_applies_auction_rate_bump_at_midpoint() {
+        let context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(50_000_000).build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        // 10% start bump decaying to 0 over 100ms; 50ms in is the midpoint,
+        // so the bump should have decayed to half way: 5%.
+        extension.taker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[]);
+
+        let amount = contract
+            .calculate_taking_amount(order, extension, U256::from(1000u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1050u128));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_applies_auction_rate_bump_after_expiry() {
+        let context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(200_000_000).build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        // 100ms auction has long since ended by 200ms in, so the bump clamps
+        // to `end_rate_bump` (0%) rather than extrapolating past the curve.
+        extension.taker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[]);
+
+        let amount = contract
+            .calculate_taking_amount(order, extension, U256::from(1000u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1000u128));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_interpolates_between_two_explicit_points() {
+        let context = get_context(accounts(0));
+        // Elapsed 25ms: between `start_time` (bump 10_000) and the point at
+        // delay 50ms (bump 6_000), the first segment of a multi-point curve.
+        testing_env!(context.block_timestamp(25_000_000).build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.taker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[(50, 6_000)]);
+
+        let amount = contract
+            .calculate_taking_amount(order, extension, U256::from(1000u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1080u128));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_interpolates_between_last_point_and_expiry() {
+        let context = get_context(accounts(0));
+        // Elapsed 75ms: between the point at delay 50ms (bump 6_000) and the
+        // auction's end at 100ms (bump 0), the final segment of the curve.
+        testing_env!(context.block_timestamp(75_000_000).build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.taker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[(50, 6_000)]);
+
+        let amount = contract
+            .calculate_taking_amount(order, extension, U256::from(1000u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1030u128));
+    }
+
+    #[test]
+    fn test_calculate_making_amount_applies_auction_rate_bump_before_start() {
+        let context = get_context(accounts(0));
+        // `start_time` is 50ms in; the default block timestamp under
+        // `testing_env!` is 0, i.e. strictly before the auction starts.
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.maker_amount_data = auction_extension_data(50, 100, 20_000, 0, &[]);
+
+        let amount = contract
+            .calculate_making_amount(order, extension, U256::from(1200u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1000u128));
+    }
+
+    #[test]
+    fn test_calculate_making_amount_interpolates_between_two_explicit_points() {
+        let context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(25_000_000).build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.maker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[(50, 6_000)]);
+
+        let amount = contract
+            .calculate_making_amount(order, extension, U256::from(1000u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(925u128));
+    }
+
+    #[test]
+    fn test_calculate_making_amount_applies_auction_rate_bump_after_expiry() {
+        let context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(200_000_000).build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.maker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[(50, 6_000)]);
+
+        let amount = contract
+            .calculate_making_amount(order, extension, U256::from(1000u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1000u128));
+    }
+
+    #[test]
+    fn test_calculate_making_amount_applies_inverse_auction_rate_bump() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let mut extension = create_test_extension();
+        extension.maker_amount_data = auction_extension_data(0, 100, 10_000, 0, &[]);
+
+        let amount = contract
+            .calculate_making_amount(order, extension, U256::from(1100u128), U256::from(1000u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(1000u128));
+    }
+
+    #[test]
+    fn test_calculate_making_amount_does_not_overflow_for_large_amounts() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let mut order = create_test_order();
+        // Both operands exceed `u128::MAX`, so a raw 256-bit multiplication
+        // before dividing would overflow and panic rather than return this.
+        order.making_amount = U256::MAX.checked_div(U256::from(2u128)).unwrap();
+        order.taking_amount = U256::MAX.checked_div(U256::from(2u128)).unwrap();
+        let extension = create_test_extension();
+
+        let amount = contract
+            .calculate_making_amount(
+                order.clone(),
+                extension,
+                order.taking_amount,
+                U256::ZERO,
+                [0u8; 32],
+            )
+            .unwrap();
+        assert_eq!(amount, order.making_amount);
+    }
+
+    #[test]
+    fn test_calculate_making_amount_rounds_down_on_non_divisible_ratio() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let mut order = create_test_order();
+        order.making_amount = U256::from(1000u128);
+        order.taking_amount = U256::from(3u128);
+        let extension = create_test_extension();
+
+        // 1000 * 1 / 3 = 333.33..., must floor so the taker never receives
+        // more than their exact proportional share.
+        let amount = contract
+            .calculate_making_amount(order, extension, U256::from(1u128), U256::from(0u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(333u128));
+    }
+
+    #[test]
+    fn test_calculate_taking_amount_rounds_up_on_non_divisible_ratio() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let mut order = create_test_order();
+        order.making_amount = U256::from(3u128);
+        order.taking_amount = U256::from(1000u128);
+        let extension = create_test_extension();
+
+        // 1000 * 1 / 3 = 333.33..., must ceil so a taker requesting 1 unit of
+        // making amount never underpays.
+        let amount = contract
+            .calculate_taking_amount(order, extension, U256::from(1u128), U256::from(0u128), [0u8; 32])
+            .unwrap();
+        assert_eq!(amount, U256::from(334u128));
+    }
+
+    #[test]
+    fn test_domain_separator_deterministic() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let a = OrderLib::domain_separator(
+            "1inch Limit Order Protocol".to_string(),
+            "4".to_string(),
+            1313161555,
+            accounts(1),
+        );
+        let b = OrderLib::domain_separator(
+            "1inch Limit Order Protocol".to_string(),
+            "4".to_string(),
+            1313161555,
+            accounts(1),
+        );
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_domain_separator_is_sensitive_to_chain_id() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let a = OrderLib::domain_separator(
+            "1inch Limit Order Protocol".to_string(),
+            "4".to_string(),
+            1313161555,
+            accounts(1),
+        );
+        let b = OrderLib::domain_separator(
+            "1inch Limit Order Protocol".to_string(),
+            "4".to_string(),
+            1,
+            accounts(1),
+        );
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_domain_separator_is_sensitive_to_verifying_contract() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let a = OrderLib::domain_separator(
+            "1inch Limit Order Protocol".to_string(),
+            "4".to_string(),
+            1313161555,
+            accounts(1),
+        );
+        let b = OrderLib::domain_separator(
+            "1inch Limit Order Protocol".to_string(),
+            "4".to_string(),
+            1313161555,
+            accounts(2),
+        );
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_validate_signature_rejects_wrong_length() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+
+        let result = contract.validate_signature(order, vec![0u8; 66], order_maker());
+        assert_eq!(result, Err(LimitOrderError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_validate_signature_rejects_non_eth_signer() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+
+        // `order.maker` is a plain NEAR account id, not a `0x...` eth address.
+        let result = contract.validate_signature(order.clone(), vec![0u8; 65], order.maker);
+        assert_eq!(result, Err(LimitOrderError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_validate_signature_rejects_high_s() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let signer =
+            AccountId::try_from("0x1111111111111111111111111111111111111111".to_string()).unwrap();
+
+        // s = 0xFF.. is above half the curve order regardless of r/v.
+        let mut signature = vec![0u8; 65];
+        signature[32..64].copy_from_slice(&[0xFFu8; 32]);
+        signature[64] = 27;
+
+        let result = contract.validate_signature(order, signature, signer);
+        assert_eq!(result, Err(LimitOrderError::InvalidSignature));
+    }
+
+    fn order_maker() -> AccountId {
+        AccountId::try_from("0x1111111111111111111111111111111111111111".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_validate_signature_rejects_wrong_length_ed25519() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        // 64 lowercase hex chars: a NEAR implicit account, routed to the
+        // ed25519 path instead of the `0x...` secp256k1 one.
+        let signer = AccountId::try_from("a".repeat(64)).unwrap();
+
+        let result = contract.validate_signature(order, vec![0u8; 65], signer);
+        assert_eq!(result, Err(LimitOrderError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_validate_signature_rejects_forged_ed25519_signature() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let signer = AccountId::try_from("a".repeat(64)).unwrap();
+
+        let result = contract.validate_signature(order, vec![0u8; 64], signer);
+        assert_eq!(result, Ok(false));
+    }
+
     #[test]
     fn test_get_receiver() {
         let context = get_context(accounts(0));
@@ -387,4 +1347,332 @@ mod tests {
         let result = contract.validate_extension(order, extension);
         assert!(result.is_ok());
     }
+
+    /// Mock the single promise a `process_order` settlement leg's callback
+    /// (`on_taker_leg_settled`/`on_maker_leg_settled`) inspects via
+    /// `promise_result(0)`.
+    fn set_leg_result(context: VMContextBuilder, ok: bool) {
+        let result = if ok {
+            near_sdk::PromiseResult::Successful(vec![])
+        } else {
+            near_sdk::PromiseResult::Failed
+        };
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            std::collections::HashMap::default(),
+            vec![result]
+        );
+    }
+
+    /// Drives `process_order` through both settlement legs as if they both
+    /// landed successfully, returning the committed making amount.
+    fn fill_order_to_completion(
+        contract: &mut OrderLib,
+        order: Order,
+        extension: Extension,
+        taker: AccountId,
+        taking_amount: u128,
+    ) -> u128 {
+        let taking_amount = U256::from(taking_amount);
+        let order_hash = contract.hash_order(order.clone());
+        let remaining_making_amount = contract.remaining_making_amount(&order, &order_hash).unwrap();
+        let making_amount = contract
+            .compute_making_amount(
+                &order,
+                &extension,
+                taking_amount,
+                remaining_making_amount,
+                &order_hash,
+            )
+            .unwrap();
+
+        assert!(matches!(
+            contract
+                .process_order(order.clone(), extension, taker.clone(), taking_amount)
+                .unwrap(),
+            PromiseOrValue::Promise(_)
+        ));
+
+        set_leg_result(get_context(accounts(0)), true);
+        assert!(matches!(
+            contract
+                .on_taker_leg_settled(
+                    order.clone(),
+                    order_hash,
+                    remaining_making_amount,
+                    making_amount,
+                    taking_amount,
+                    taker.clone(),
+                )
+                .unwrap(),
+            PromiseOrValue::Promise(_)
+        ));
+
+        set_leg_result(get_context(accounts(0)), true);
+        let result = contract
+            .on_maker_leg_settled(
+                order,
+                order_hash,
+                remaining_making_amount,
+                making_amount,
+                taking_amount,
+                taker,
+            )
+            .unwrap();
+        match result {
+            PromiseOrValue::Value(amount) => amount.as_u128().unwrap(),
+            PromiseOrValue::Promise(_) => panic!("expected a resolved value"),
+        }
+    }
+
+    #[test]
+    fn test_process_order_tracks_partial_fills_across_calls() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let extension = create_test_extension();
+
+        // First fill takes half the order; the remaining invalidator should
+        // now report the other half as still fillable.
+        let making_amount = fill_order_to_completion(
+            &mut contract,
+            order.clone(),
+            extension.clone(),
+            accounts(4),
+            500,
+        );
+        assert_eq!(making_amount, 500);
+
+        let order_hash = contract.hash_order(order.clone());
+        assert_eq!(
+            contract.remaining_invalidator_for_order(order.maker.clone(), order_hash),
+            U256::from(500u128)
+        );
+
+        // A second fill for the remaining half succeeds...
+        let making_amount = fill_order_to_completion(
+            &mut contract,
+            order.clone(),
+            extension.clone(),
+            accounts(4),
+            500,
+        );
+        assert_eq!(making_amount, 500);
+
+        // ...and a third, once nothing is left, is rejected rather than
+        // silently re-filling from `order.making_amount`.
+        let result = contract.process_order(order, extension, accounts(4), U256::from(500u128));
+        assert!(matches!(result, Err(LimitOrderError::OrderExpired)));
+    }
+
+    #[test]
+    fn test_process_order_rejects_overfill_beyond_remaining() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let extension = create_test_extension();
+
+        fill_order_to_completion(&mut contract, order.clone(), extension.clone(), accounts(4), 500);
+
+        // Only 500 making-amount units are left; requesting the full
+        // taking amount again asks for more than that.
+        let result = contract.process_order(order, extension, accounts(4), U256::from(1000u128));
+        assert!(matches!(result, Err(LimitOrderError::PartialFillNotAllowed)));
+    }
+
+    #[test]
+    fn test_process_order_bit_invalidator_rejects_second_fill() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = OrderLib::new([0u8; 32]);
+        let mut order = create_test_order();
+        order.maker_traits.use_bit_invalidator = true;
+        order.maker_traits.nonce_or_epoch = 7;
+        let extension = create_test_extension();
+
+        fill_order_to_completion(
+            &mut contract,
+            order.clone(),
+            extension.clone(),
+            accounts(4),
+            1000,
+        );
+        assert!(contract.bit_invalidator_for_order(order.maker.clone(), 7));
+
+        let result = contract.process_order(order, extension, accounts(4), U256::from(1000u128));
+        assert!(matches!(result, Err(LimitOrderError::BitInvalidatedOrder)));
+    }
+
+    #[test]
+    fn test_on_maker_leg_settled_commits_fill_when_both_legs_succeed() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let order_hash = contract.hash_order(order.clone());
+
+        set_leg_result(get_context(accounts(0)), true);
+        let result = contract
+            .on_maker_leg_settled(order.clone(), order_hash, order.making_amount, U256::from(500u128), U256::from(500u128), accounts(4))
+            .unwrap();
+
+        assert!(matches!(result, PromiseOrValue::Value(v) if v == U256::from(500u128)));
+        assert_eq!(
+            contract.remaining_invalidator_for_order(order.maker.clone(), order_hash),
+            order.making_amount.checked_sub(U256::from(500u128)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_on_taker_leg_settled_rejects_without_touching_bookkeeping_when_taker_leg_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let order_hash = contract.hash_order(order.clone());
+
+        set_leg_result(get_context(accounts(0)), false);
+        let result = contract.on_taker_leg_settled(
+            order.clone(),
+            order_hash,
+            order.making_amount,
+            U256::from(500u128),
+            U256::from(500u128),
+            accounts(4),
+        );
+
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::TransferFromTakerToMakerFailed)
+        ));
+        // Nothing moved, so the remaining amount is still whatever it was
+        // before this fill was attempted.
+        assert_eq!(
+            contract.remaining_invalidator_for_order(order.maker.clone(), order_hash),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn test_on_maker_leg_settled_rejects_and_leaves_bookkeeping_untouched_when_maker_leg_fails() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = OrderLib::new([0u8; 32]);
+        let order = create_test_order();
+        let order_hash = contract.hash_order(order.clone());
+
+        set_leg_result(get_context(accounts(0)), false);
+        let result = contract.on_maker_leg_settled(
+            order.clone(),
+            order_hash,
+            order.making_amount,
+            U256::from(500u128),
+            U256::from(500u128),
+            accounts(4),
+        );
+
+        assert!(matches!(
+            result,
+            Err(LimitOrderError::TransferFromMakerToTakerFailed)
+        ));
+        // The taker-to-maker leg already landed, but the order was never
+        // actually filled - the remaining amount must stay untouched rather
+        // than recording a fill that didn't happen.
+        assert_eq!(
+            contract.remaining_invalidator_for_order(order.maker.clone(), order_hash),
+            U256::ZERO
+        );
+    }
+
+    /// Encodes a single `Compare { source: Timestamp, op, value }` leaf in
+    /// `predicate::parse_node`'s wire format: `tag | 0x00 (timestamp source)
+    /// | value: u128 LE`.
+    fn encode_timestamp_leaf(op_tag: u8, value: u128) -> Vec<u8> {
+        let mut data = vec![op_tag, 0x00];
+        data.extend_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    /// Encodes an `And`/`Or` combinator: `tag | left_len: u32 LE | left
+    /// bytes | right bytes`.
+    fn encode_and(left: &[u8], right: &[u8]) -> Vec<u8> {
+        let mut data = vec![0x10u8];
+        data.extend_from_slice(&(left.len() as u32).to_le_bytes());
+        data.extend_from_slice(left);
+        data.extend_from_slice(right);
+        data
+    }
+
+    /// Builds an order/extension pair that passes `validate_order_extension`:
+    /// `has_extension` is set, and `salt` is pinned to the extension's hash
+    /// the same way a maker would sign it.
+    fn create_test_order_with_extension(extension: &Extension) -> Order {
+        let mut data = Vec::new();
+        data.extend_from_slice(extension.maker_amount_data());
+        data.extend_from_slice(extension.taker_amount_data());
+        data.extend_from_slice(extension.predicate_data());
+        data.extend_from_slice(extension.permit_data());
+        data.extend_from_slice(extension.pre_interaction_data());
+        data.extend_from_slice(extension.post_interaction_data());
+        let extension_hash = near_sdk::env::keccak256(&data);
+        let salt = u64::from_le_bytes(extension_hash[0..8].try_into().unwrap());
+
+        let mut order = create_test_order();
+        order.salt = salt;
+        order.maker_traits.has_extension = true;
+        order
+    }
+
+    #[test]
+    fn test_process_order_timestamp_predicate_gates_then_expires() {
+        // `timestamp < 1000` - fillable before the deadline, expired after.
+        let predicate_data = encode_timestamp_leaf(0x01 /* OP_LT */, 1000);
+        let extension = Extension {
+            predicate_data,
+            ..create_test_extension()
+        };
+        let order = create_test_order_with_extension(&extension);
+
+        let context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(500).build());
+        let mut contract = OrderLib::new([0u8; 32]);
+        let making_amount =
+            fill_order_to_completion(&mut contract, order.clone(), extension.clone(), accounts(4), 500);
+        assert_eq!(making_amount, 500);
+
+        testing_env!(get_context(accounts(0)).block_timestamp(2000).build());
+        let result = contract.process_order(order, extension, accounts(4), U256::from(500u128));
+        assert!(matches!(result, Err(LimitOrderError::PredicateIsNotTrue)));
+    }
+
+    #[test]
+    fn test_process_order_and_predicate_rejects_when_one_side_fails() {
+        // `timestamp > 100 AND timestamp == 999` - satisfiable on the first
+        // leaf at `now = 150`, never on the second, so the whole AND is
+        // false.
+        let left = encode_timestamp_leaf(0x02 /* OP_GT */, 100);
+        let right = encode_timestamp_leaf(0x03 /* OP_EQ */, 999);
+        let predicate_data = encode_and(&left, &right);
+        let extension = Extension {
+            predicate_data,
+            ..create_test_extension()
+        };
+        let order = create_test_order_with_extension(&extension);
+
+        let context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(150).build());
+        let mut contract = OrderLib::new([0u8; 32]);
+        let result = contract.process_order(order, extension, accounts(4), U256::from(500u128));
+        assert!(matches!(result, Err(LimitOrderError::PredicateIsNotTrue)));
+    }
 }
@@ -0,0 +1,310 @@
+// Find all our documentation at https://docs.near.org
+use crate::types::{Extension, LimitOrderError, Order};
+use crate::u256::U256;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::{TreeMap, UnorderedMap},
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+/// Fixed-point scale a resting order's price is expressed in: `taking_amount
+/// * PRICE_SCALE / making_amount`, rounded down. Large enough that the
+/// typical range of token amounts (up to ~1e24, NEAR's own yoctoNEAR scale)
+/// doesn't collapse distinct prices down to the same bucket.
+const PRICE_SCALE: u128 = 1_000_000_000_000;
+
+/// A signed order resting in the book, along with the fixed-point price it
+/// was placed at (`price_key` of `order` at insertion time) - stored
+/// alongside rather than recomputed, so `OrderBook::remove` can find and
+/// drop the matching entry out of the price-sorted index without redoing the
+/// division.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlacedOrder {
+    pub order: Order,
+    pub extension: Extension,
+    pub signature: Vec<u8>,
+    pub price: u128,
+}
+
+/// The overlapping portion of two crossing resting orders that a
+/// `match_orders` call can actually settle: `fill_making_amount` /
+/// `fill_taking_amount` are denominated in `order_a`'s making/taking assets
+/// (`order_b` is buying `fill_making_amount` of `order_a.maker_asset` and
+/// paying `fill_taking_amount` of `order_a.taker_asset` in return).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutableMatch {
+    pub fill_making_amount: U256,
+    pub fill_taking_amount: U256,
+}
+
+/// Fixed-point price of `order`, in units of `taker_asset` per
+/// `PRICE_SCALE` of `maker_asset`: how much the maker demands per unit given
+/// away. Used both as the book's sort key and, in `compute_match`, as the
+/// test for whether two resting orders cross. The ratio itself - unlike the
+/// `U256` order amounts it's derived from - is expected to fit in `u128`,
+/// since it's a bucket key rather than a settled amount; `InvalidAmounts` if
+/// it doesn't.
+pub fn price_key(order: &Order) -> Result<u128, LimitOrderError> {
+    if order.making_amount.is_zero() {
+        return Err(LimitOrderError::SwapWithZeroAmount);
+    }
+    order
+        .taking_amount
+        .mul_div_floor(U256::from(PRICE_SCALE), order.making_amount)?
+        .as_u128()
+        .ok_or(LimitOrderError::InvalidAmounts)
+}
+
+/// Check that `order_a` and `order_b` sit on opposite sides of the same
+/// asset pair and that their prices cross, then compute the overlapping fill
+/// amount. `order_a` is treated as the resting order whose price is honored
+/// - `order_b` only matches if it's willing to pay at least as much as
+/// `order_a` is asking, mirroring the usual orderbook convention that the
+/// order already resting in the book sets the execution price.
+///
+/// Crossing condition: `order_a` asks `taking_a / making_a` of its
+/// `taker_asset` per unit of `maker_asset`; `order_b` (buying that same
+/// `maker_asset`, i.e. `order_b.taker_asset == order_a.maker_asset`) offers
+/// `making_b / taking_b` of it per unit of `order_a.taker_asset`. They cross
+/// when `order_b`'s offered price is at least `order_a`'s ask, i.e.
+/// `making_a * making_b >= taking_a * taking_b` (cross-multiplied to avoid
+/// floating point).
+pub fn compute_match(order_a: &Order, order_b: &Order) -> Result<ExecutableMatch, LimitOrderError> {
+    if order_a.maker_asset != order_b.taker_asset || order_a.taker_asset != order_b.maker_asset {
+        return Err(LimitOrderError::OrdersNotOnOppositeSides);
+    }
+
+    let lhs = order_a
+        .making_amount
+        .checked_mul(order_b.making_amount)
+        .ok_or(LimitOrderError::InvalidAmounts)?;
+    let rhs = order_a
+        .taking_amount
+        .checked_mul(order_b.taking_amount)
+        .ok_or(LimitOrderError::InvalidAmounts)?;
+    if lhs < rhs {
+        return Err(LimitOrderError::OrdersDoNotCross);
+    }
+
+    // `order_b.taking_amount` is denominated in `order_a.maker_asset`, so the
+    // overlap in that asset is whichever side has less of it to give.
+    let fill_making_amount = order_a.making_amount.min(order_b.taking_amount);
+    if fill_making_amount.is_zero() {
+        return Err(LimitOrderError::SwapWithZeroAmount);
+    }
+
+    // Convert at `order_a`'s resting price, floor-rounded so `order_a`'s
+    // maker is never shortchanged by fractional-amount rounding.
+    let fill_taking_amount =
+        fill_making_amount.mul_div_floor(order_a.taking_amount, order_a.making_amount)?;
+
+    Ok(ExecutableMatch {
+        fill_making_amount,
+        fill_taking_amount,
+    })
+}
+
+/// Storage key for the price-sorted index: grouped by asset pair first
+/// (`TreeMap` orders lexicographically), then by price within the pair, then
+/// by order hash to break ties between orders placed at the same price.
+type PriceIndexKey = (AccountId, AccountId, u128, [u8; 32]);
+
+/// Resting limit orders, keyed by order hash for direct lookup and indexed
+/// by `(maker_asset, taker_asset, price, order_hash)` for price-sorted
+/// traversal within a pair. A thin storage layer only - signature
+/// validation, invalidation checks and settlement all stay with the caller
+/// (`LimitOrderProtocol`), the same split `EscrowIo` draws between escrow
+/// state-machine logic and the runtime it runs against.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OrderBook {
+    entries: UnorderedMap<[u8; 32], PlacedOrder>,
+    price_index: TreeMap<PriceIndexKey, ()>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self {
+            entries: UnorderedMap::new(b"oe"),
+            price_index: TreeMap::new(b"op"),
+        }
+    }
+
+    /// Record `placed` under `order_hash`, replacing (and re-indexing) any
+    /// prior entry for the same hash.
+    pub fn insert(&mut self, order_hash: [u8; 32], placed: PlacedOrder) {
+        if let Some(prior) = self.entries.get(&order_hash) {
+            self.price_index
+                .remove(&Self::index_key(&prior, order_hash));
+        }
+        self.price_index
+            .insert(&Self::index_key(&placed, order_hash), &());
+        self.entries.insert(&order_hash, &placed);
+    }
+
+    /// Drop `order_hash` from the book, returning the entry that was there.
+    pub fn remove(&mut self, order_hash: [u8; 32]) -> Option<PlacedOrder> {
+        let placed = self.entries.remove(&order_hash)?;
+        self.price_index
+            .remove(&Self::index_key(&placed, order_hash));
+        Some(placed)
+    }
+
+    pub fn get(&self, order_hash: &[u8; 32]) -> Option<PlacedOrder> {
+        self.entries.get(order_hash)
+    }
+
+    /// Order hashes resting on `(maker_asset, taker_asset)`, cheapest ask
+    /// first - a read-only view onto the price-sorted index.
+    pub fn orders_for_pair(
+        &self,
+        maker_asset: &AccountId,
+        taker_asset: &AccountId,
+    ) -> Vec<[u8; 32]> {
+        self.price_index
+            .iter()
+            .filter(|((maker, taker, _, _), _)| maker == maker_asset && taker == taker_asset)
+            .map(|((_, _, _, order_hash), _)| order_hash)
+            .collect()
+    }
+
+    fn index_key(placed: &PlacedOrder, order_hash: [u8; 32]) -> PriceIndexKey {
+        (
+            placed.order.maker_asset.clone(),
+            placed.order.taker_asset.clone(),
+            placed.price,
+            order_hash,
+        )
+    }
+}
+
+impl Default for OrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(
+        maker_asset: &str,
+        taker_asset: &str,
+        making_amount: u128,
+        taking_amount: u128,
+    ) -> Order {
+        Order {
+            salt: 1,
+            maker: near_sdk::test_utils::accounts(0),
+            receiver: near_sdk::test_utils::accounts(1),
+            maker_asset: maker_asset.parse().unwrap(),
+            taker_asset: taker_asset.parse().unwrap(),
+            making_amount: U256::from(making_amount),
+            taking_amount: U256::from(taking_amount),
+            maker_traits: crate::types::MakerTraits::default(),
+        }
+    }
+
+    fn placed(order: Order) -> PlacedOrder {
+        let price = price_key(&order).unwrap();
+        PlacedOrder {
+            order,
+            extension: Extension::default(),
+            signature: vec![],
+            price,
+        }
+    }
+
+    #[test]
+    fn test_price_key_is_scaled_ratio_of_taking_to_making() {
+        let order = order("a.near", "b.near", 2, 1);
+        assert_eq!(price_key(&order).unwrap(), PRICE_SCALE / 2);
+    }
+
+    #[test]
+    fn test_price_key_rejects_zero_making_amount() {
+        let order = order("a.near", "b.near", 0, 1);
+        assert_eq!(price_key(&order), Err(LimitOrderError::SwapWithZeroAmount));
+    }
+
+    #[test]
+    fn test_compute_match_rejects_orders_not_on_opposite_sides() {
+        let order_a = order("a.near", "b.near", 100, 100);
+        let order_b = order("a.near", "c.near", 100, 100);
+        assert_eq!(
+            compute_match(&order_a, &order_b),
+            Err(LimitOrderError::OrdersNotOnOppositeSides)
+        );
+    }
+
+    #[test]
+    fn test_compute_match_rejects_non_crossing_prices() {
+        // order_a asks 2 b.near per a.near; order_b only offers 33 b.near for
+        // 100 a.near (about 0.33 b.near per a.near) - doesn't cross.
+        let order_a = order("a.near", "b.near", 100, 200);
+        let order_b = order("b.near", "a.near", 33, 100);
+        assert_eq!(
+            compute_match(&order_a, &order_b),
+            Err(LimitOrderError::OrdersDoNotCross)
+        );
+    }
+
+    #[test]
+    fn test_compute_match_fills_the_overlapping_amount_at_resting_orders_price() {
+        // order_a: sell 100 a.near for 200 b.near (asks 2 b.near/a.near).
+        // order_b: sell 300 b.near for 100 a.near (offers 3 b.near/a.near,
+        // crosses order_a's ask). Overlap is capped by order_a's 100 a.near.
+        let order_a = order("a.near", "b.near", 100, 200);
+        let order_b = order("b.near", "a.near", 300, 100);
+
+        let result = compute_match(&order_a, &order_b).unwrap();
+        assert_eq!(result.fill_making_amount, U256::from(100u128));
+        // At order_a's resting price (2 b.near per a.near): 100 * 2 = 200.
+        assert_eq!(result.fill_taking_amount, U256::from(200u128));
+    }
+
+    #[test]
+    fn test_compute_match_caps_fill_at_smaller_side() {
+        let order_a = order("a.near", "b.near", 100, 200);
+        // order_b only wants 40 a.near in return for its b.near.
+        let order_b = order("b.near", "a.near", 300, 40);
+
+        let result = compute_match(&order_a, &order_b).unwrap();
+        assert_eq!(result.fill_making_amount, U256::from(40u128));
+        assert_eq!(result.fill_taking_amount, U256::from(80u128));
+    }
+
+    #[test]
+    fn test_order_book_insert_get_remove_round_trip() {
+        let mut book = OrderBook::new();
+        let order_hash = [1u8; 32];
+        let entry = placed(order("a.near", "b.near", 100, 200));
+
+        book.insert(order_hash, entry.clone());
+        assert_eq!(book.get(&order_hash), Some(entry));
+
+        let removed = book.remove(order_hash);
+        assert!(removed.is_some());
+        assert_eq!(book.get(&order_hash), None);
+    }
+
+    #[test]
+    fn test_orders_for_pair_is_sorted_cheapest_ask_first() {
+        let mut book = OrderBook::new();
+        let maker: AccountId = "a.near".parse().unwrap();
+        let taker: AccountId = "b.near".parse().unwrap();
+
+        let cheap_hash = [1u8; 32];
+        let pricey_hash = [2u8; 32];
+        // Cheaper ask: 1 b.near per a.near.
+        book.insert(cheap_hash, placed(order("a.near", "b.near", 100, 100)));
+        // Pricier ask: 2 b.near per a.near.
+        book.insert(pricey_hash, placed(order("a.near", "b.near", 100, 200)));
+
+        assert_eq!(
+            book.orders_for_pair(&maker, &taker),
+            vec![cheap_hash, pricey_hash]
+        );
+    }
+}
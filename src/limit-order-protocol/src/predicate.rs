@@ -0,0 +1,444 @@
+// Find all our documentation at https://docs.near.org
+use near_sdk::AccountId;
+
+/// Comparison a predicate leaf applies between a runtime-read value and a
+/// maker-supplied constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Gt,
+    Eq,
+}
+
+/// Where a predicate leaf reads its left-hand runtime value from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredicateSource {
+    /// `env::block_timestamp()`, resolved synchronously - lets a maker gate
+    /// a fill on a start/expiry time without the order itself expiring.
+    Timestamp,
+    /// A `u128` read from a named view method on another contract (e.g. an
+    /// oracle price feed, or a token's own balance query) - resolved
+    /// asynchronously via a cross-contract call joined into the predicate's
+    /// overall evaluation.
+    ExtCallUint { account: AccountId, method: String },
+    /// `maker`'s current epoch for `series` (see the epoch invalidator each
+    /// contract keeps for `MakerTraits::use_epoch_manager` orders) - resolved
+    /// synchronously from local storage, the same as `Timestamp`, just
+    /// keyed by an extra `(maker, series)` the leaf itself carries.
+    Epoch { maker: AccountId, series: u64 },
+}
+
+/// A maker's conditional-execution predicate, parsed out of
+/// `Extension::predicate_data`. Leaves compare a `PredicateSource` against a
+/// constant; `And`/`Or` combine sub-predicates, mirroring the composable
+/// `and`/`or`/`lt`/`gt`/`eq` predicate calldata the EVM Fusion+ protocol
+/// encodes - e.g. "only fill while the oracle price is below X and after
+/// timestamp Y".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredicateExpr {
+    Compare {
+        source: PredicateSource,
+        op: CompareOp,
+        value: u128,
+    },
+    And(Box<PredicateExpr>, Box<PredicateExpr>),
+    Or(Box<PredicateExpr>, Box<PredicateExpr>),
+}
+
+const SOURCE_TIMESTAMP: u8 = 0x00;
+const SOURCE_EXT_CALL_UINT: u8 = 0x01;
+const SOURCE_EPOCH: u8 = 0x02;
+
+const OP_LT: u8 = 0x01;
+const OP_GT: u8 = 0x02;
+const OP_EQ: u8 = 0x03;
+const OP_AND: u8 = 0x10;
+const OP_OR: u8 = 0x11;
+
+/// Parse a `PredicateSource` off the front of `data`, returning it alongside
+/// how many bytes it consumed. Layout: `0x00` (timestamp, no further bytes),
+/// `0x01 | account_len: u16 LE | account bytes | method_len: u16 LE | method
+/// bytes` (ext call), or `0x02 | account_len: u16 LE | account bytes |
+/// series: u64 LE` (epoch - `EPOCH_EQUALS(maker, series, epoch)`, with
+/// `epoch` supplied as the leaf's trailing compare value like every other
+/// source).
+fn parse_source(data: &[u8]) -> Option<(PredicateSource, usize)> {
+    match *data.first()? {
+        SOURCE_TIMESTAMP => Some((PredicateSource::Timestamp, 1)),
+        SOURCE_EXT_CALL_UINT => {
+            let mut offset = 1;
+            let account_len =
+                u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?) as usize;
+            offset += 2;
+            let account = std::str::from_utf8(data.get(offset..offset + account_len)?)
+                .ok()?
+                .parse::<AccountId>()
+                .ok()?;
+            offset += account_len;
+            let method_len =
+                u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?) as usize;
+            offset += 2;
+            let method = std::str::from_utf8(data.get(offset..offset + method_len)?)
+                .ok()?
+                .to_string();
+            offset += method_len;
+            Some((PredicateSource::ExtCallUint { account, method }, offset))
+        }
+        SOURCE_EPOCH => {
+            let mut offset = 1;
+            let account_len =
+                u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?) as usize;
+            offset += 2;
+            let maker = std::str::from_utf8(data.get(offset..offset + account_len)?)
+                .ok()?
+                .parse::<AccountId>()
+                .ok()?;
+            offset += account_len;
+            let series = u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?);
+            offset += 8;
+            Some((PredicateSource::Epoch { maker, series }, offset))
+        }
+        _ => None,
+    }
+}
+
+/// Parse a `PredicateExpr` off the front of `data`, returning it alongside
+/// how many bytes it consumed. Comparisons are `tag | source | value: u128
+/// LE`; combinators are `tag | left_len: u32 LE | left bytes | right bytes`,
+/// recursing into each side.
+fn parse_node(data: &[u8]) -> Option<(PredicateExpr, usize)> {
+    match *data.first()? {
+        tag @ (OP_LT | OP_GT | OP_EQ) => {
+            let (source, source_len) = parse_source(data.get(1..)?)?;
+            let value_start = 1 + source_len;
+            let value =
+                u128::from_le_bytes(data.get(value_start..value_start + 16)?.try_into().ok()?);
+            let op = match tag {
+                OP_LT => CompareOp::Lt,
+                OP_GT => CompareOp::Gt,
+                _ => CompareOp::Eq,
+            };
+            Some((
+                PredicateExpr::Compare { source, op, value },
+                value_start + 16,
+            ))
+        }
+        tag @ (OP_AND | OP_OR) => {
+            let left_len = u32::from_le_bytes(data.get(1..5)?.try_into().ok()?) as usize;
+            let left_start = 5;
+            let left_end = left_start.checked_add(left_len)?;
+            let (left, left_consumed) = parse_node(data.get(left_start..left_end)?)?;
+            if left_consumed != left_len {
+                return None;
+            }
+            let (right, right_consumed) = parse_node(data.get(left_end..)?)?;
+            let expr = if tag == OP_AND {
+                PredicateExpr::And(Box::new(left), Box::new(right))
+            } else {
+                PredicateExpr::Or(Box::new(left), Box::new(right))
+            };
+            Some((expr, left_end + right_consumed))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the whole of `data` as a single predicate expression. Returns
+/// `None` for empty data (no predicate attached), malformed encodings, or
+/// trailing bytes left over after a fully-parsed node.
+pub fn parse_predicate(data: &[u8]) -> Option<PredicateExpr> {
+    if data.is_empty() {
+        return None;
+    }
+    let (expr, consumed) = parse_node(data)?;
+    if consumed == data.len() {
+        Some(expr)
+    } else {
+        None
+    }
+}
+
+/// Every `ExtCallUint` leaf in `expr`, left to right - the order `fill_order`
+/// fires cross-contract calls in, and the order `evaluate` below expects
+/// `ext_values` to be supplied in.
+pub fn collect_ext_call_sources(expr: &PredicateExpr) -> Vec<(AccountId, String)> {
+    match expr {
+        PredicateExpr::Compare {
+            source: PredicateSource::ExtCallUint { account, method },
+            ..
+        } => vec![(account.clone(), method.clone())],
+        PredicateExpr::Compare { .. } => vec![],
+        PredicateExpr::And(left, right) | PredicateExpr::Or(left, right) => {
+            let mut sources = collect_ext_call_sources(left);
+            sources.extend(collect_ext_call_sources(right));
+            sources
+        }
+    }
+}
+
+/// Every `Epoch` leaf in `expr`, left to right - the order `evaluate` below
+/// expects `epoch_values` to be supplied in. Unlike `ExtCallUint`, these are
+/// always resolvable synchronously (a local `epoch_for_series` lookup), so
+/// callers can resolve this list directly rather than deferring via Promise.
+pub fn collect_epoch_sources(expr: &PredicateExpr) -> Vec<(AccountId, u64)> {
+    match expr {
+        PredicateExpr::Compare {
+            source: PredicateSource::Epoch { maker, series },
+            ..
+        } => vec![(maker.clone(), *series)],
+        PredicateExpr::Compare { .. } => vec![],
+        PredicateExpr::And(left, right) | PredicateExpr::Or(left, right) => {
+            let mut sources = collect_epoch_sources(left);
+            sources.extend(collect_epoch_sources(right));
+            sources
+        }
+    }
+}
+
+/// Evaluate `expr` against `now` (`env::block_timestamp()`, for `Timestamp`
+/// leaves), `ext_values` (resolved `ExtCallUint` leaves, consumed in the same
+/// left-to-right order `collect_ext_call_sources` enumerates them in), and
+/// `epoch_values` (resolved `Epoch` leaves, consumed in `collect_epoch_sources`
+/// order).
+pub fn evaluate(expr: &PredicateExpr, now: u64, ext_values: &[u128], epoch_values: &[u64]) -> bool {
+    let mut ext_cursor = ext_values.iter();
+    let mut epoch_cursor = epoch_values.iter();
+    evaluate_node(expr, now, &mut ext_cursor, &mut epoch_cursor)
+}
+
+fn evaluate_node(
+    expr: &PredicateExpr,
+    now: u64,
+    ext_values: &mut std::slice::Iter<u128>,
+    epoch_values: &mut std::slice::Iter<u64>,
+) -> bool {
+    match expr {
+        PredicateExpr::Compare { source, op, value } => {
+            let lhs = match source {
+                PredicateSource::Timestamp => now as u128,
+                PredicateSource::ExtCallUint { .. } => *ext_values.next().unwrap_or(&0),
+                PredicateSource::Epoch { .. } => *epoch_values.next().unwrap_or(&0) as u128,
+            };
+            match op {
+                CompareOp::Lt => lhs < *value,
+                CompareOp::Gt => lhs > *value,
+                CompareOp::Eq => lhs == *value,
+            }
+        }
+        PredicateExpr::And(left, right) => {
+            evaluate_node(left, now, ext_values, epoch_values)
+                && evaluate_node(right, now, ext_values, epoch_values)
+        }
+        PredicateExpr::Or(left, right) => {
+            evaluate_node(left, now, ext_values, epoch_values)
+                || evaluate_node(right, now, ext_values, epoch_values)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_timestamp_compare(op: u8, value: u128) -> Vec<u8> {
+        let mut data = vec![op, SOURCE_TIMESTAMP];
+        data.extend_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    fn encode_ext_call_uint_compare(op: u8, account: &str, method: &str, value: u128) -> Vec<u8> {
+        let mut data = vec![op, SOURCE_EXT_CALL_UINT];
+        data.extend_from_slice(&(account.len() as u16).to_le_bytes());
+        data.extend_from_slice(account.as_bytes());
+        data.extend_from_slice(&(method.len() as u16).to_le_bytes());
+        data.extend_from_slice(method.as_bytes());
+        data.extend_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    fn encode_epoch_compare(op: u8, maker: &str, series: u64, value: u128) -> Vec<u8> {
+        let mut data = vec![op, SOURCE_EPOCH];
+        data.extend_from_slice(&(maker.len() as u16).to_le_bytes());
+        data.extend_from_slice(maker.as_bytes());
+        data.extend_from_slice(&series.to_le_bytes());
+        data.extend_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    fn encode_combinator(tag: u8, left: &[u8], right: &[u8]) -> Vec<u8> {
+        let mut data = vec![tag];
+        data.extend_from_slice(&(left.len() as u32).to_le_bytes());
+        data.extend_from_slice(left);
+        data.extend_from_slice(right);
+        data
+    }
+
+    #[test]
+    fn test_parse_predicate_rejects_empty_data() {
+        assert_eq!(parse_predicate(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_predicate_rejects_trailing_garbage() {
+        let mut data = encode_timestamp_compare(OP_LT, 100);
+        data.push(0xAB);
+        assert_eq!(parse_predicate(&data), None);
+    }
+
+    #[test]
+    fn test_parse_predicate_round_trips_timestamp_comparison() {
+        let data = encode_timestamp_compare(OP_GT, 100);
+        let expr = parse_predicate(&data).unwrap();
+        assert_eq!(
+            expr,
+            PredicateExpr::Compare {
+                source: PredicateSource::Timestamp,
+                op: CompareOp::Gt,
+                value: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_predicate_round_trips_ext_call_uint_comparison() {
+        let data = encode_ext_call_uint_compare(OP_LT, "oracle.near", "get_price", 500);
+        let expr = parse_predicate(&data).unwrap();
+        assert_eq!(
+            expr,
+            PredicateExpr::Compare {
+                source: PredicateSource::ExtCallUint {
+                    account: "oracle.near".parse().unwrap(),
+                    method: "get_price".to_string(),
+                },
+                op: CompareOp::Lt,
+                value: 500,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_predicate_round_trips_and_combinator() {
+        let left = encode_timestamp_compare(OP_GT, 100);
+        let right = encode_timestamp_compare(OP_LT, 200);
+        let data = encode_combinator(OP_AND, &left, &right);
+
+        let expr = parse_predicate(&data).unwrap();
+        assert_eq!(
+            expr,
+            PredicateExpr::And(
+                Box::new(PredicateExpr::Compare {
+                    source: PredicateSource::Timestamp,
+                    op: CompareOp::Gt,
+                    value: 100,
+                }),
+                Box::new(PredicateExpr::Compare {
+                    source: PredicateSource::Timestamp,
+                    op: CompareOp::Lt,
+                    value: 200,
+                }),
+            )
+        );
+    }
+
+    #[test]
+    fn test_evaluate_and_requires_both_sides_true() {
+        let left = encode_timestamp_compare(OP_GT, 100);
+        let right = encode_timestamp_compare(OP_LT, 200);
+        let expr = parse_predicate(&encode_combinator(OP_AND, &left, &right)).unwrap();
+
+        assert!(evaluate(&expr, 150, &[], &[]));
+        assert!(!evaluate(&expr, 50, &[], &[]));
+        assert!(!evaluate(&expr, 250, &[], &[]));
+    }
+
+    #[test]
+    fn test_evaluate_or_requires_either_side_true() {
+        let left = encode_timestamp_compare(OP_LT, 100);
+        let right = encode_timestamp_compare(OP_GT, 200);
+        let expr = parse_predicate(&encode_combinator(OP_OR, &left, &right)).unwrap();
+
+        assert!(evaluate(&expr, 50, &[], &[]));
+        assert!(evaluate(&expr, 250, &[], &[]));
+        assert!(!evaluate(&expr, 150, &[], &[]));
+    }
+
+    #[test]
+    fn test_evaluate_nested_and_or_tree() {
+        // (timestamp > 100 AND timestamp < 200) OR (timestamp == 300)
+        let gt_100 = encode_timestamp_compare(OP_GT, 100);
+        let lt_200 = encode_timestamp_compare(OP_LT, 200);
+        let and_branch = encode_combinator(OP_AND, &gt_100, &lt_200);
+        let eq_300 = encode_timestamp_compare(OP_EQ, 300);
+        let expr = parse_predicate(&encode_combinator(OP_OR, &and_branch, &eq_300)).unwrap();
+
+        assert!(evaluate(&expr, 150, &[], &[])); // inside the AND window
+        assert!(evaluate(&expr, 300, &[], &[])); // hits the OR's EQ branch
+        assert!(!evaluate(&expr, 50, &[], &[])); // in neither branch
+        assert!(!evaluate(&expr, 250, &[], &[])); // past the AND window, not 300
+    }
+
+    #[test]
+    fn test_collect_ext_call_sources_finds_every_leaf_in_order() {
+        let left = encode_ext_call_uint_compare(OP_LT, "a.near", "get_a", 1);
+        let right = encode_ext_call_uint_compare(OP_GT, "b.near", "get_b", 2);
+        let expr = parse_predicate(&encode_combinator(OP_AND, &left, &right)).unwrap();
+
+        assert_eq!(
+            collect_ext_call_sources(&expr),
+            vec![
+                ("a.near".parse().unwrap(), "get_a".to_string()),
+                ("b.near".parse().unwrap(), "get_b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_consumes_ext_values_in_collection_order() {
+        let left = encode_ext_call_uint_compare(OP_LT, "a.near", "get_a", 100);
+        let right = encode_ext_call_uint_compare(OP_GT, "b.near", "get_b", 100);
+        let expr = parse_predicate(&encode_combinator(OP_AND, &left, &right)).unwrap();
+
+        // First leaf (a.near) must read below 100, second (b.near) above 100.
+        assert!(evaluate(&expr, 0, &[50, 150], &[]));
+        assert!(!evaluate(&expr, 0, &[150, 50], &[]));
+    }
+
+    #[test]
+    fn test_parse_predicate_round_trips_epoch_comparison() {
+        let data = encode_epoch_compare(OP_EQ, "maker.near", 7, 3);
+        let expr = parse_predicate(&data).unwrap();
+        assert_eq!(
+            expr,
+            PredicateExpr::Compare {
+                source: PredicateSource::Epoch {
+                    maker: "maker.near".parse().unwrap(),
+                    series: 7,
+                },
+                op: CompareOp::Eq,
+                value: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_collect_epoch_sources_finds_every_leaf_in_order() {
+        let left = encode_epoch_compare(OP_EQ, "a.near", 1, 1);
+        let right = encode_epoch_compare(OP_EQ, "b.near", 2, 2);
+        let expr = parse_predicate(&encode_combinator(OP_AND, &left, &right)).unwrap();
+
+        assert_eq!(
+            collect_epoch_sources(&expr),
+            vec![("a.near".parse().unwrap(), 1), ("b.near".parse().unwrap(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_consumes_epoch_values_in_collection_order() {
+        let left = encode_epoch_compare(OP_EQ, "a.near", 1, 3);
+        let right = encode_epoch_compare(OP_EQ, "b.near", 2, 5);
+        let expr = parse_predicate(&encode_combinator(OP_AND, &left, &right)).unwrap();
+
+        assert!(evaluate(&expr, 0, &[], &[3, 5]));
+        assert!(!evaluate(&expr, 0, &[], &[5, 3]));
+    }
+}
@@ -1,10 +1,15 @@
+use crate::u256::U256;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
     AccountId,
 };
 
-/// Order structure for limit orders
+/// Order structure for limit orders. `making_amount`/`taking_amount` are
+/// `U256` rather than `u128` since they mirror an EVM `uint256` - a maker
+/// order bridged from Ethereum can carry amounts above `2^128` that a
+/// NEAR-native `u128` couldn't represent or hash consistently with the
+/// Ethereum side of the same swap.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Order {
     pub salt: u64,
@@ -12,8 +17,8 @@ pub struct Order {
     pub receiver: AccountId,
     pub maker_asset: AccountId,
     pub taker_asset: AccountId,
-    pub making_amount: u128,
-    pub taking_amount: u128,
+    pub making_amount: U256,
+    pub taking_amount: U256,
     pub maker_traits: MakerTraits,
 }
 
@@ -89,47 +94,60 @@ impl TakerTraits {
     }
 }
 
-/// Bit invalidator data
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, Default)]
+/// A single 256-bit invalidation word for one `slot = nonce_or_epoch >> 8`,
+/// mirroring 1inch's per-bit order invalidator: bit `nonce_or_epoch & 0xFF`
+/// of the word is set once the order carrying that nonce is cancelled or
+/// filled, so up to 256 orders share one storage write per slot while still
+/// being invalidated individually rather than all-or-nothing.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq,
+)]
 pub struct BitInvalidatorData {
-    pub slots: Vec<u64>,
+    pub word: [u128; 2],
 }
 
 impl BitInvalidatorData {
-    /// Check if a slot is invalidated
-    pub fn check_slot(&self, slot: u64) -> bool {
-        self.slots.contains(&slot)
+    /// Check whether `nonce_or_epoch`'s bit is set in this slot's word.
+    pub fn check_bit(&self, nonce_or_epoch: u64) -> bool {
+        let bit = (nonce_or_epoch & 0xFF) as u32;
+        let limb = self.word[(bit / 128) as usize];
+        (limb >> (bit % 128)) & 1 == 1
     }
 
-    /// Mass invalidate orders
-    pub fn mass_invalidate(&mut self, nonce_or_epoch: u64, additional_mask: u64) -> u64 {
-        let slot = nonce_or_epoch >> 8;
-        if !self.slots.contains(&slot) {
-            self.slots.push(slot);
-        }
-        additional_mask
+    /// Set `nonce_or_epoch`'s bit, OR-ing in `additional_mask` alongside it
+    /// (e.g. to invalidate a caller-chosen batch of sibling bits in the same
+    /// slot in one call), and return the resulting word.
+    pub fn mass_invalidate(&mut self, nonce_or_epoch: u64, additional_mask: u64) -> [u128; 2] {
+        let bit = (nonce_or_epoch & 0xFF) as u32;
+        self.word[(bit / 128) as usize] |= 1u128 << (bit % 128);
+        self.word[0] |= additional_mask as u128;
+        self.word
     }
 }
 
-/// Remaining invalidator for tracking order fills
+/// Remaining invalidator for tracking order fills. `remaining` is a `U256`
+/// since it's lazily initialized from `Order::making_amount`, which can
+/// itself exceed `u128`.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, Default)]
 pub struct RemainingInvalidator {
-    pub remaining: u128,
+    pub remaining: U256,
 }
 
 impl RemainingInvalidator {
     /// Create a fully filled invalidator
     pub fn fully_filled() -> Self {
-        Self { remaining: 0 }
+        Self {
+            remaining: U256::ZERO,
+        }
     }
 
     /// Get remaining amount
-    pub fn remaining(&self) -> u128 {
+    pub fn remaining(&self) -> U256 {
         self.remaining
     }
 
     /// Create new invalidator with remaining amount
-    pub fn new(remaining: u128) -> Self {
+    pub fn new(remaining: U256) -> Self {
         Self { remaining }
     }
 }
@@ -179,6 +197,64 @@ impl Extension {
     }
 }
 
+/// A single breakpoint in a piecewise-linear Dutch-auction price curve:
+/// `delay` milliseconds after `AuctionDetails::start_time`, the rate bump
+/// reaches `rate_bump`. The curve interpolates linearly between consecutive
+/// breakpoints (and between `start_rate_bump`/the first breakpoint, and the
+/// last breakpoint/`end_rate_bump`).
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+pub struct AuctionPoint {
+    pub delay: u32,
+    pub rate_bump: u32,
+}
+
+/// Time-decaying Fusion-style price curve parsed out of an order's
+/// `Extension::maker_amount_data`/`taker_amount_data`. A resolver filling
+/// early pays `start_rate_bump` (the maker's best price for the taker);
+/// filling late pays down to `end_rate_bump` (usually zero); resolvers
+/// filling in between get whatever the piecewise-linear curve through
+/// `points` says at that instant. Bumps are expressed in 1e5 basis points
+/// (i.e. `100_000` == +100%), matching the EVM Fusion+ auction encoding.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuctionDetails {
+    pub start_time: u64,
+    pub duration: u64,
+    pub start_rate_bump: u32,
+    pub end_rate_bump: u32,
+    pub points: Vec<AuctionPoint>,
+}
+
+/// Denominator `rate_bump` values are expressed against: a bump of
+/// `AUCTION_BASE_POINTS` means +100%.
+pub const AUCTION_BASE_POINTS: u128 = 100_000;
+
+/// Escrow parameters for a hashlock/timelock-gated fill (see
+/// `LimitOrderProtocol::fill_order`'s optional `escrow` argument) - the NEAR
+/// side of a cross-chain atomic swap. Instead of the maker asset landing
+/// directly in the taker's account, it's held by the protocol itself until
+/// the taker reveals `hashlock`'s preimage via `withdraw`, or the maker
+/// reclaims it via `cancel_escrow` once `cancel_after_ms` has elapsed since
+/// the fill.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EscrowParams {
+    pub hashlock: [u8; 32],
+    pub cancel_after_ms: u64,
+}
+
+/// A maker asset held in escrow by `LimitOrderProtocol` after a hashlock-
+/// gated fill (see `EscrowParams`), pending `withdraw` or `cancel_escrow`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HashlockEscrow {
+    pub hashlock: [u8; 32],
+    pub maker: AccountId,
+    pub taker: AccountId,
+    pub token: AccountId,
+    pub amount: u128,
+    pub cancel_at_ms: u64,
+}
+
 /// Error types for limit order operations
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum LimitOrderError {
@@ -206,7 +282,20 @@ pub enum LimitOrderError {
     ContractPaused,
     OrderInvalidated,
     InvalidAmounts,
+    InvalidAmountData,
     InvalidExtension,
+    InsufficientStorageDeposit,
+    OrderNotFound,
+    OrdersNotOnOppositeSides,
+    OrdersDoNotCross,
+    OnlyMakerCanCancel,
+    InvalidSecret,
+    EscrowNotFound,
+    EscrowNotYetCancellable,
+    PreInteractionFailed,
+    PostInteractionFailed,
+    RemainingAmountIsZero,
+    BitInvalidatedOrder,
 }
 
 impl AsRef<str> for LimitOrderError {
@@ -240,7 +329,20 @@ impl AsRef<str> for LimitOrderError {
             LimitOrderError::ContractPaused => "ContractPaused",
             LimitOrderError::OrderInvalidated => "OrderInvalidated",
             LimitOrderError::InvalidAmounts => "InvalidAmounts",
+            LimitOrderError::InvalidAmountData => "InvalidAmountData",
             LimitOrderError::InvalidExtension => "InvalidExtension",
+            LimitOrderError::InsufficientStorageDeposit => "InsufficientStorageDeposit",
+            LimitOrderError::OrderNotFound => "OrderNotFound",
+            LimitOrderError::OrdersNotOnOppositeSides => "OrdersNotOnOppositeSides",
+            LimitOrderError::OrdersDoNotCross => "OrdersDoNotCross",
+            LimitOrderError::OnlyMakerCanCancel => "OnlyMakerCanCancel",
+            LimitOrderError::InvalidSecret => "InvalidSecret",
+            LimitOrderError::EscrowNotFound => "EscrowNotFound",
+            LimitOrderError::EscrowNotYetCancellable => "EscrowNotYetCancellable",
+            LimitOrderError::PreInteractionFailed => "PreInteractionFailed",
+            LimitOrderError::PostInteractionFailed => "PostInteractionFailed",
+            LimitOrderError::RemainingAmountIsZero => "RemainingAmountIsZero",
+            LimitOrderError::BitInvalidatedOrder => "BitInvalidatedOrder",
         }
     }
 }
@@ -0,0 +1,559 @@
+// Find all our documentation at https://docs.near.org
+use crate::types::LimitOrderError;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A 256-bit unsigned integer, stored as four little-endian `u64` limbs
+/// (`0` is the least significant).
+///
+/// EVM token amounts and 1inch order values are `uint256`, which doesn't fit
+/// in `u128` - a maker order bridged from Ethereum can carry amounts above
+/// `2^128` that this type exists to represent. Checked arithmetic uses
+/// binary long division over a 512-bit intermediate product (`full_mul`,
+/// `div_512_by_256`) so `mul_div_floor`/`mul_div_ceil` never lose precision
+/// on the multiply.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    /// Decode a big-endian 32-byte word (the ABI encoding of a `uint256`)
+    /// into a `U256`.
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = (3 - i) * 8;
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    /// Encode as a big-endian 32-byte word, matching how `uint256` order
+    /// amounts are ABI-encoded into an EIP-712 struct hash.
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = (3 - i) * 8;
+            out[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        out
+    }
+
+    /// Encode as a little-endian 32-byte blob, for the legacy (pre-EIP-712)
+    /// order hash's plain field concatenation.
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut out = self.to_be_bytes();
+        out.reverse();
+        out
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Downcast to `u128`, the native width of a real NEAR token balance -
+    /// `None` if the value doesn't fit, which only happens for amounts an
+    /// EVM chain could express but a NEAR transfer never can.
+    pub fn as_u128(self) -> Option<u128> {
+        if self.0[2] != 0 || self.0[3] != 0 {
+            return None;
+        }
+        Some((self.0[1] as u128) << 64 | self.0[0] as u128)
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let mut result = [0u64; 4];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let sum = self.0[i] as u128 + rhs.0[i] as u128 + carry;
+            result[i] = sum as u64;
+            carry = sum >> 64;
+        }
+        if carry != 0 {
+            None
+        } else {
+            Some(U256(result))
+        }
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        if self < rhs {
+            None
+        } else {
+            Some(self.wrapping_sub(rhs))
+        }
+    }
+
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        let mut result = [0u64; 4];
+        let mut borrow = 0i128;
+        for i in 0..4 {
+            let diff = self.0[i] as i128 - rhs.0[i] as i128 - borrow;
+            if diff < 0 {
+                result[i] = (diff + (1i128 << 64)) as u64;
+                borrow = 1;
+            } else {
+                result[i] = diff as u64;
+                borrow = 0;
+            }
+        }
+        U256(result)
+    }
+
+    /// Full 256x256->512-bit product, as eight little-endian `u64` limbs
+    /// (index `0` least significant). Plain `checked_mul` overflows whenever
+    /// the mathematical product doesn't fit in 256 bits, which a
+    /// proportional-fill calculation can easily hit for large bridged
+    /// amounts - this computes the exact product first so `mul_div_floor`/
+    /// `mul_div_ceil` never lose precision or panic on the multiply.
+    fn full_mul(self, rhs: Self) -> [u64; 8] {
+        let mut acc = [0u64; 8];
+        for i in 0..4 {
+            if self.0[i] == 0 {
+                continue;
+            }
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let idx = i + j;
+                let prod = self.0[i] as u128 * rhs.0[j] as u128 + acc[idx] as u128 + carry;
+                acc[idx] = prod as u64;
+                carry = prod >> 64;
+            }
+            let mut idx = i + 4;
+            while carry > 0 {
+                let sum = acc[idx] as u128 + carry;
+                acc[idx] = sum as u64;
+                carry = sum >> 64;
+                idx += 1;
+            }
+        }
+        acc
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let product = self.full_mul(rhs);
+        if product[4..8].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        Some(U256([product[0], product[1], product[2], product[3]]))
+    }
+
+    fn bit(self, i: u32) -> u64 {
+        (self.0[(i / 64) as usize] >> (i % 64)) & 1
+    }
+
+    fn set_bit(&mut self, i: u32) {
+        self.0[(i / 64) as usize] |= 1 << (i % 64);
+    }
+
+    /// Shift left by one bit, discarding whatever shifts out of bit 255.
+    fn shl1(self) -> Self {
+        let mut out = [0u64; 4];
+        let mut carry = 0u64;
+        for i in 0..4 {
+            let next_carry = self.0[i] >> 63;
+            out[i] = (self.0[i] << 1) | carry;
+            carry = next_carry;
+        }
+        U256(out)
+    }
+
+    /// Divide the 512-bit value `(high, low)` (`high` the more-significant
+    /// 256 bits) by `self`, returning `(quotient, remainder)`. `self` must be
+    /// non-zero. Implemented as binary long division (shift-compare-subtract
+    /// one bit at a time) rather than anything relying on a native wider
+    /// integer type.
+    fn div_512_by_256(divisor: Self, high: Self, low: Self) -> (Self, Self) {
+        let mut remainder = U256::ZERO;
+        let mut quotient = U256::ZERO;
+        for i in (0..512u32).rev() {
+            let bit = if i >= 256 { high.bit(i - 256) } else { low.bit(i) };
+            let carried_out = remainder.bit(255) == 1;
+            let mut shifted = remainder.shl1();
+            if bit == 1 {
+                shifted.0[0] |= 1;
+            }
+            let (quotient_bit, new_remainder) = if carried_out {
+                // The true (pre-truncation) remainder is `2^256 + shifted`,
+                // which is always `>= divisor` since `divisor <= U256::MAX`.
+                (true, shifted.wrapping_sub(divisor))
+            } else if shifted >= divisor {
+                (true, shifted.wrapping_sub(divisor))
+            } else {
+                (false, shifted)
+            };
+            remainder = new_remainder;
+            if quotient_bit && i < 256 {
+                quotient.set_bit(i);
+            }
+        }
+        (quotient, remainder)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        Some(Self::div_512_by_256(rhs, U256::ZERO, self).0)
+    }
+
+    /// `floor(self * b / c)` computed via a 512-bit intermediate product, so
+    /// the multiply never overflows even when `self * b` doesn't fit in 256
+    /// bits. Returns `Err` if the true quotient doesn't fit back into a
+    /// `U256` (i.e. the result itself overflows) or if `c` is zero.
+    pub fn mul_div_floor(self, b: Self, c: Self) -> Result<Self, LimitOrderError> {
+        if c.is_zero() {
+            return Err(LimitOrderError::SwapWithZeroAmount);
+        }
+        let product = self.full_mul(b);
+        let low = U256([product[0], product[1], product[2], product[3]]);
+        let high = U256([product[4], product[5], product[6], product[7]]);
+        // `self * b / c` fits in `U256` iff `high / c == 0`, i.e. the high
+        // limb of the product is itself smaller than the divisor.
+        if high >= c {
+            return Err(LimitOrderError::InvalidAmounts);
+        }
+        let (quotient, _remainder) = Self::div_512_by_256(c, high, low);
+        Ok(quotient)
+    }
+
+    /// `ceil(self * b / c)`, i.e. `mul_div_floor` rounded up whenever the
+    /// division isn't exact - so a taker requesting a given making amount
+    /// always pays at least the proportional price, never less due to floor
+    /// rounding.
+    pub fn mul_div_ceil(self, b: Self, c: Self) -> Result<Self, LimitOrderError> {
+        if c.is_zero() {
+            return Err(LimitOrderError::SwapWithZeroAmount);
+        }
+        let product = self.full_mul(b);
+        let low = U256([product[0], product[1], product[2], product[3]]);
+        let high = U256([product[4], product[5], product[6], product[7]]);
+        if high >= c {
+            return Err(LimitOrderError::InvalidAmounts);
+        }
+        let (quotient, remainder) = Self::div_512_by_256(c, high, low);
+        // Same overflow criterion as `mul_div_floor`; rounding the quotient
+        // up below overflows only when it is already `U256::MAX`, which
+        // `checked_add` reports as `InvalidAmounts`.
+        if remainder.is_zero() {
+            Ok(quotient)
+        } else {
+            quotient.checked_add(U256::from(1u8)).ok_or(LimitOrderError::InvalidAmounts)
+        }
+    }
+
+    /// Divide by a small (`u64`-sized) divisor, returning `(quotient,
+    /// remainder)` - used by `Display` to peel off decimal digits.
+    fn div_rem_u64(self, divisor: u64) -> (Self, u64) {
+        let mut quotient = [0u64; 4];
+        let mut rem: u128 = 0;
+        for i in (0..4).rev() {
+            let cur = (rem << 64) | self.0[i] as u128;
+            quotient[i] = (cur / divisor as u128) as u64;
+            rem = cur % divisor as u128;
+        }
+        (U256(quotient), rem as u64)
+    }
+
+    /// Parse either a `"0x..."` hex string or a plain decimal string, the
+    /// two formats an EVM relayer submitting a bridged order might use for a
+    /// `uint256` amount.
+    pub fn from_hex_or_decimal_str(s: &str) -> Result<Self, ParseU256Error> {
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            if hex.is_empty() || hex.len() > 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(ParseU256Error::InvalidHex);
+            }
+            let padded = format!("{hex:0>64}");
+            let mut bytes = [0u8; 32];
+            for i in 0..32 {
+                bytes[i] = u8::from_str_radix(&padded[i * 2..i * 2 + 2], 16)
+                    .map_err(|_| ParseU256Error::InvalidHex)?;
+            }
+            Ok(U256::from_be_bytes(bytes))
+        } else {
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseU256Error::InvalidDecimal);
+            }
+            let mut value = U256::ZERO;
+            for b in s.bytes() {
+                let digit = U256::from((b - b'0') as u64);
+                value = value
+                    .checked_mul(U256::from(10u8))
+                    .and_then(|v| v.checked_add(digit))
+                    .ok_or(ParseU256Error::Overflow)?;
+            }
+            Ok(value)
+        }
+    }
+}
+
+/// Error parsing a [`U256`] out of a [`U256::from_hex_or_decimal_str`]
+/// string - surfaced as a `serde` deserialization error by `Deserialize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseU256Error {
+    InvalidHex,
+    InvalidDecimal,
+    Overflow,
+}
+
+impl fmt::Display for ParseU256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseU256Error::InvalidHex => write!(f, "invalid 0x-prefixed U256 hex string"),
+            ParseU256Error::InvalidDecimal => write!(f, "invalid decimal U256 string"),
+            ParseU256Error::Overflow => write!(f, "value does not fit in a U256"),
+        }
+    }
+}
+
+impl From<u8> for U256 {
+    fn from(v: u8) -> Self {
+        U256::from(v as u64)
+    }
+}
+
+impl From<u16> for U256 {
+    fn from(v: u16) -> Self {
+        U256::from(v as u64)
+    }
+}
+
+impl From<u32> for U256 {
+    fn from(v: u32) -> Self {
+        U256::from(v as u64)
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(v: u64) -> Self {
+        U256([v, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(v: u128) -> Self {
+        U256([v as u64, (v >> 64) as u64, 0, 0])
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for i in (0..4).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => continue,
+                ord => return ord,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut digits = Vec::new();
+        let mut value = *self;
+        while !value.is_zero() {
+            let (quotient, digit) = value.div_rem_u64(10);
+            digits.push((b'0' + digit as u8) as char);
+            value = quotient;
+        }
+        let decimal: String = digits.into_iter().rev().collect();
+        write!(f, "{decimal}")
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "U256({self})")
+    }
+}
+
+/// Serializes as a decimal string, matching `near_sdk::json_types::U128`'s
+/// convention of avoiding JSON-number precision loss; deserializes either a
+/// decimal string or a `"0x..."` hex string, since EVM relayers submitting a
+/// bridged order's amount commonly use the latter.
+impl Serialize for U256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        U256::from_hex_or_decimal_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_u128_round_trips_through_be_bytes() {
+        let value = U256::from(u128::MAX);
+        assert_eq!(U256::from_be_bytes(value.to_be_bytes()), value);
+    }
+
+    #[test]
+    fn test_to_be_bytes_matches_evm_abi_word_layout() {
+        let value = U256::from(1u8);
+        let mut expected = [0u8; 32];
+        expected[31] = 1;
+        assert_eq!(value.to_be_bytes(), expected);
+    }
+
+    #[test]
+    fn test_as_u128_round_trips_for_values_that_fit() {
+        assert_eq!(U256::from(12345u128).as_u128(), Some(12345));
+        assert_eq!(U256::from(u128::MAX).as_u128(), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_as_u128_is_none_above_u128_max() {
+        let above_u128 = U256::from(u128::MAX).checked_add(U256::from(1u8)).unwrap();
+        assert_eq!(above_u128.as_u128(), None);
+    }
+
+    #[test]
+    fn test_checked_add_overflows_past_max() {
+        assert_eq!(U256::MAX.checked_add(U256::from(1u8)), None);
+    }
+
+    #[test]
+    fn test_checked_sub_underflows_below_zero() {
+        assert_eq!(U256::ZERO.checked_sub(U256::from(1u8)), None);
+    }
+
+    #[test]
+    fn test_checked_mul_does_not_overflow_for_values_above_u128() {
+        // `a` exceeds u128::MAX, so neither it nor the product would fit in
+        // the old `u128` amount type at all, let alone a raw multiply.
+        let a = U256::from(u128::MAX).checked_mul(U256::from(2u8)).unwrap();
+        let b = U256::from(3u8);
+        let product = a.checked_mul(b).unwrap();
+        assert_eq!(product.mul_div_floor(U256::from(1u8), a).unwrap(), b);
+    }
+
+    #[test]
+    fn test_checked_mul_overflow_returns_none() {
+        assert_eq!(U256::MAX.checked_mul(U256::from(2u8)), None);
+    }
+
+    #[test]
+    fn test_mul_div_floor_rounds_down_on_non_divisible_ratio() {
+        let amount = U256::from(1000u128)
+            .mul_div_floor(U256::from(1u8), U256::from(3u8))
+            .unwrap();
+        assert_eq!(amount, U256::from(333u128));
+    }
+
+    #[test]
+    fn test_mul_div_ceil_rounds_up_on_non_divisible_ratio() {
+        let amount = U256::from(1000u128)
+            .mul_div_ceil(U256::from(1u8), U256::from(3u8))
+            .unwrap();
+        assert_eq!(amount, U256::from(334u128));
+    }
+
+    #[test]
+    fn test_mul_div_floor_does_not_overflow_for_amounts_above_u128() {
+        // Both operands exceed `u128::MAX`, so a raw `U256` multiplication
+        // before dividing would overflow a 256-bit register, let alone
+        // `u128`.
+        let large = U256::from(u128::MAX).checked_mul(U256::from(2u8)).unwrap();
+        let amount = large.mul_div_floor(large, large).unwrap();
+        assert_eq!(amount, large);
+    }
+
+    #[test]
+    fn test_mul_div_floor_rejects_division_by_zero() {
+        let result = U256::from(1u8).mul_div_floor(U256::from(1u8), U256::ZERO);
+        assert_eq!(result, Err(LimitOrderError::SwapWithZeroAmount));
+    }
+
+    #[test]
+    fn test_mul_div_floor_rejects_quotient_overflow() {
+        // `MAX * MAX / 1` doesn't fit back into 256 bits.
+        let result = U256::MAX.mul_div_floor(U256::MAX, U256::from(1u8));
+        assert_eq!(result, Err(LimitOrderError::InvalidAmounts));
+    }
+
+    #[test]
+    fn test_checked_div_matches_mul_div_floor_identity() {
+        let a = U256::from(1_000_000u128);
+        let b = U256::from(7u8);
+        assert_eq!(a.checked_div(b), Some(a.mul_div_floor(U256::from(1u8), b).unwrap()));
+    }
+
+    #[test]
+    fn test_ordering_compares_most_significant_limb_first() {
+        let small = U256::from(u128::MAX);
+        let large = small.checked_add(U256::from(1u8)).unwrap();
+        assert!(large > small);
+        assert!(small < large);
+    }
+
+    #[test]
+    fn test_display_round_trips_through_from_hex_or_decimal_str() {
+        let value = U256::from(u128::MAX).checked_mul(U256::from(3u8)).unwrap();
+        let decimal = value.to_string();
+        assert_eq!(U256::from_hex_or_decimal_str(&decimal).unwrap(), value);
+    }
+
+    #[test]
+    fn test_from_hex_or_decimal_str_accepts_hex() {
+        assert_eq!(
+            U256::from_hex_or_decimal_str("0xff").unwrap(),
+            U256::from(255u8)
+        );
+    }
+
+    #[test]
+    fn test_from_hex_or_decimal_str_accepts_decimal() {
+        assert_eq!(
+            U256::from_hex_or_decimal_str("255").unwrap(),
+            U256::from(255u8)
+        );
+    }
+
+    #[test]
+    fn test_from_hex_or_decimal_str_rejects_garbage() {
+        assert_eq!(
+            U256::from_hex_or_decimal_str("not a number"),
+            Err(ParseU256Error::InvalidDecimal)
+        );
+        assert_eq!(
+            U256::from_hex_or_decimal_str("0xnothex"),
+            Err(ParseU256Error::InvalidHex)
+        );
+    }
+
+    #[test]
+    fn test_serde_round_trips_as_decimal_string() {
+        let value = U256::from(u128::MAX).checked_mul(U256::from(2u8)).unwrap();
+        let json = near_sdk::serde_json::to_string(&value).unwrap();
+        assert_eq!(json, format!("\"{value}\""));
+        let parsed: U256 = near_sdk::serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_serde_deserializes_hex_string() {
+        let parsed: U256 = near_sdk::serde_json::from_str("\"0x1a\"").unwrap();
+        assert_eq!(parsed, U256::from(26u8));
+    }
+}
@@ -1,4 +1,137 @@
-use crate::types::{Extension, LimitOrderError, Order};
+use crate::types::{
+    AuctionDetails, AuctionPoint, Extension, LimitOrderError, MakerTraits, Order,
+    AUCTION_BASE_POINTS,
+};
+use crate::u256::U256;
+use near_sdk::{env, AccountId};
+
+/// EIP-712 type string for `Order`, with the referenced `MakerTraits` type
+/// appended per the `encodeType` convention. Must stay byte-for-byte in sync
+/// with the Solidity struct this NEAR port mirrors.
+const ORDER_TYPE_STRING: &[u8] =
+    b"Order(uint256 salt,address maker,address receiver,address makerAsset,address takerAsset,uint256 makingAmount,uint256 takingAmount,MakerTraits makerTraits)MakerTraits(bool useBitInvalidator,bool useEpochManager,bool hasExtension,uint256 nonceOrEpoch,uint256 series)";
+
+/// EIP-712 type string for the nested `MakerTraits` struct.
+const MAKER_TRAITS_TYPE_STRING: &[u8] =
+    b"MakerTraits(bool useBitInvalidator,bool useEpochManager,bool hasExtension,uint256 nonceOrEpoch,uint256 series)";
+
+/// EIP-712 type string for the standard `EIP712Domain` struct.
+const EIP712_DOMAIN_TYPE_STRING: &[u8] =
+    b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// Build an EIP-712 domain separator, binding `chain_id` into the digest the
+/// same way EIP-155 binds it into a transaction signature - an order signed
+/// for one deployment/chain can't be replayed to verify against another.
+///
+/// `keccak256(keccak256(EIP712Domain(...)) || keccak256(name) || keccak256(version) || chainId || verifyingContract)`
+pub fn domain_separator(
+    name: &str,
+    version: &str,
+    chain_id: u64,
+    verifying_contract: &AccountId,
+) -> [u8; 32] {
+    let type_hash: [u8; 32] = env::keccak256(EIP712_DOMAIN_TYPE_STRING)
+        .try_into()
+        .unwrap();
+    let name_hash: [u8; 32] = env::keccak256(name.as_bytes()).try_into().unwrap();
+    let version_hash: [u8; 32] = env::keccak256(version.as_bytes()).try_into().unwrap();
+
+    let mut data = Vec::with_capacity(32 * 5);
+    data.extend_from_slice(&type_hash);
+    data.extend_from_slice(&name_hash);
+    data.extend_from_slice(&version_hash);
+    data.extend_from_slice(&u64_be32(chain_id));
+    data.extend_from_slice(&left_pad_account(verifying_contract));
+
+    env::keccak256(&data).try_into().unwrap()
+}
+
+/// Left-pad an account identifier into a 32-byte big-endian word, the ABI
+/// encoding EVM's `address` type uses. An EVM-side account (see
+/// `parse_eth_address`) is decoded back to its raw 20 address bytes and
+/// zero-padded exactly as Solidity's ABI encoder would - required for
+/// `hash_order_712` to match what a Fusion+ contract on the Ethereum side
+/// computes for the same order. NEAR-native account ids have no EVM
+/// counterpart: one that fits in 32 bytes is zero-padded as a string, and
+/// one that doesn't is folded down with keccak256 so the word stays a
+/// deterministic function of the whole id.
+pub(crate) fn left_pad_account(account: &AccountId) -> [u8; 32] {
+    if let Some(address) = parse_eth_address(account) {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&address);
+        return word;
+    }
+
+    let bytes = account.as_bytes();
+    if bytes.len() <= 32 {
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        word
+    } else {
+        env::keccak256(bytes).try_into().unwrap()
+    }
+}
+
+/// Encode a `u64` as a 32-byte big-endian word, the ABI encoding of `uint256`.
+pub(crate) fn u64_be32(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Encode a `bool` as a 32-byte big-endian word, the ABI encoding of `bool`.
+fn bool_be32(value: bool) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[31] = value as u8;
+    word
+}
+
+/// EIP-712 struct hash of `MakerTraits`, nested inside the `Order` struct hash.
+fn hash_maker_traits_712(traits: &MakerTraits) -> [u8; 32] {
+    let type_hash: [u8; 32] = env::keccak256(MAKER_TRAITS_TYPE_STRING).try_into().unwrap();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&type_hash);
+    data.extend_from_slice(&bool_be32(traits.use_bit_invalidator));
+    data.extend_from_slice(&bool_be32(traits.use_epoch_manager));
+    data.extend_from_slice(&bool_be32(traits.has_extension));
+    data.extend_from_slice(&u64_be32(traits.nonce_or_epoch));
+    data.extend_from_slice(&u64_be32(traits.series));
+
+    env::keccak256(&data).try_into().unwrap()
+}
+
+/// EIP-712 domain-separated structured hash of `Order`.
+///
+/// `hash_order` below is the legacy little-endian path kept for callers that
+/// only need an internal deterministic identifier. This function instead
+/// computes `keccak256(0x1901 || domain_separator || struct_hash)` exactly as
+/// the EVM Fusion+ limit order protocol does, so an `order_hash` computed here
+/// matches the one computed on the Ethereum side of the same swap bit-for-bit
+/// - required for makers/takers to sign and verify one order across both
+/// chains rather than maintaining two diverging identifiers.
+pub fn hash_order_712(order: &Order, domain_separator: &[u8; 32]) -> [u8; 32] {
+    let type_hash: [u8; 32] = env::keccak256(ORDER_TYPE_STRING).try_into().unwrap();
+    let maker_traits_hash = hash_maker_traits_712(&order.maker_traits);
+
+    let mut struct_data = Vec::new();
+    struct_data.extend_from_slice(&type_hash);
+    struct_data.extend_from_slice(&u64_be32(order.salt));
+    struct_data.extend_from_slice(&left_pad_account(&order.maker));
+    struct_data.extend_from_slice(&left_pad_account(&order.receiver));
+    struct_data.extend_from_slice(&left_pad_account(&order.maker_asset));
+    struct_data.extend_from_slice(&left_pad_account(&order.taker_asset));
+    struct_data.extend_from_slice(&order.making_amount.to_be_bytes());
+    struct_data.extend_from_slice(&order.taking_amount.to_be_bytes());
+    struct_data.extend_from_slice(&maker_traits_hash);
+    let struct_hash: [u8; 32] = env::keccak256(&struct_data).try_into().unwrap();
+
+    let mut digest_data = Vec::with_capacity(2 + 32 + 32);
+    digest_data.extend_from_slice(&[0x19, 0x01]);
+    digest_data.extend_from_slice(domain_separator);
+    digest_data.extend_from_slice(&struct_hash);
+    env::keccak256(&digest_data).try_into().unwrap()
+}
 
 /// Compute hash of an order
 pub fn hash_order(order: &Order, domain_separator: &[u8; 32]) -> [u8; 32] {
@@ -31,50 +164,334 @@ pub fn hash_maker_traits(traits: &crate::types::MakerTraits) -> [u8; 32] {
     near_sdk::env::keccak256(&data).try_into().unwrap()
 }
 
-/// Calculate making amount based on taking amount
+/// Parse an `AuctionDetails` Dutch-auction price curve out of raw extension
+/// bytes (`Extension::maker_amount_data`/`taker_amount_data`). Layout:
+/// `start_time: u64 LE | duration: u64 LE | start_rate_bump: u32 LE |
+/// end_rate_bump: u32 LE | (delay: u32 LE, rate_bump: u32 LE)*`. Returns
+/// `None` if the data is shorter than the fixed header or the trailing
+/// points don't divide evenly into 8-byte pairs.
+pub fn parse_auction_details(data: &[u8]) -> Option<AuctionDetails> {
+    const HEADER_LEN: usize = 24;
+    if data.len() < HEADER_LEN || (data.len() - HEADER_LEN) % 8 != 0 {
+        return None;
+    }
+
+    let start_time = u64::from_le_bytes(data[0..8].try_into().ok()?);
+    let duration = u64::from_le_bytes(data[8..16].try_into().ok()?);
+    let start_rate_bump = u32::from_le_bytes(data[16..20].try_into().ok()?);
+    let end_rate_bump = u32::from_le_bytes(data[20..24].try_into().ok()?);
+
+    let mut points = Vec::new();
+    let mut offset = HEADER_LEN;
+    while offset < data.len() {
+        let delay = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?);
+        let rate_bump = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().ok()?);
+        points.push(AuctionPoint { delay, rate_bump });
+        offset += 8;
+    }
+
+    Some(AuctionDetails {
+        start_time,
+        duration,
+        start_rate_bump,
+        end_rate_bump,
+        points,
+    })
+}
+
+/// Tag byte distinguishing a dynamic `AmountGetter` call (see
+/// `parse_dynamic_amount_getter`) from an embedded `AuctionDetails` curve
+/// (see `parse_auction_details`) within the same `maker_amount_data`/
+/// `taker_amount_data` bytes. `0xFF` can also be the first byte of a valid
+/// `AuctionDetails` header (it is the low byte of the little-endian
+/// `start_time`), so callers should check this tag first rather than rely on
+/// the two layouts never overlapping.
+pub const DYNAMIC_AMOUNT_GETTER_TAG: u8 = 0xFF;
+
+/// A maker-priced amount call into an external `AmountGetter` contract,
+/// parsed out of `Extension::maker_amount_data`/`taker_amount_data` by
+/// `parse_dynamic_amount_getter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicAmountGetterCall {
+    pub getter: AccountId,
+    pub calldata: Vec<u8>,
+}
+
+/// Parse a dynamic `AmountGetter` reference out of raw extension bytes.
+/// Layout: `tag: u8 (== DYNAMIC_AMOUNT_GETTER_TAG) | account_id_len: u16 LE |
+/// account_id bytes | calldata`. Returns `None` if the tag doesn't match, the
+/// data is too short to hold the length-prefixed account id, or the account
+/// id isn't valid - in which case the caller should fall back to
+/// `parse_auction_details`.
+pub fn parse_dynamic_amount_getter(data: &[u8]) -> Option<DynamicAmountGetterCall> {
+    const HEADER_LEN: usize = 3;
+    if data.len() < HEADER_LEN || data[0] != DYNAMIC_AMOUNT_GETTER_TAG {
+        return None;
+    }
+
+    let account_id_len = u16::from_le_bytes(data[1..3].try_into().ok()?) as usize;
+    let account_id_end = HEADER_LEN.checked_add(account_id_len)?;
+    if data.len() < account_id_end {
+        return None;
+    }
+
+    let getter = std::str::from_utf8(&data[HEADER_LEN..account_id_end])
+        .ok()?
+        .parse::<AccountId>()
+        .ok()?;
+    let calldata = data[account_id_end..].to_vec();
+
+    Some(DynamicAmountGetterCall { getter, calldata })
+}
+
+/// Basis-point denominator fee amounts (protocol and per-order integrator
+/// fees alike) are expressed against: a fee of `FEE_BASE_POINTS` means 100%.
+pub const FEE_BASE_POINTS: u16 = 10_000;
+
+/// An optional per-order integrator/resolver fee, parsed out of
+/// `Extension::post_interaction_data` by `parse_integrator_fee`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegratorFeeConfig {
+    pub recipient: AccountId,
+    pub fee_bps: u16,
+}
+
+/// Parse a per-order integrator fee out of raw extension bytes. Layout:
+/// `fee_bps: u16 LE | account_id_len: u16 LE | account_id bytes`. Returns
+/// `None` if `data` is empty (no integrator fee attached), too short to hold
+/// the length-prefixed account id, or the account id isn't valid.
+pub fn parse_integrator_fee(data: &[u8]) -> Option<IntegratorFeeConfig> {
+    const HEADER_LEN: usize = 4;
+    if data.is_empty() || data.len() < HEADER_LEN {
+        return None;
+    }
+
+    let fee_bps = u16::from_le_bytes(data[0..2].try_into().ok()?);
+    let account_id_len = u16::from_le_bytes(data[2..4].try_into().ok()?) as usize;
+    let account_id_end = HEADER_LEN.checked_add(account_id_len)?;
+    if data.len() < account_id_end {
+        return None;
+    }
+
+    let recipient = std::str::from_utf8(&data[HEADER_LEN..account_id_end])
+        .ok()?
+        .parse::<AccountId>()
+        .ok()?;
+
+    Some(IntegratorFeeConfig { recipient, fee_bps })
+}
+
+/// Tag byte distinguishing an `InteractionCall` (see `parse_interaction_call`)
+/// from a plain legacy integrator-fee config within the same
+/// `Extension::post_interaction_data` bytes. A maker's `post_interaction_data`
+/// can carry either a fee config or a hook call, not both - callers should
+/// check `parse_interaction_call` first and only fall back to
+/// `parse_integrator_fee` if it returns `None`.
+pub const INTERACTION_CALL_TAG: u8 = 0xFE;
+
+/// A maker-defined setup/teardown hook fired around a fill's settlement
+/// transfer, parsed out of `Extension::pre_interaction_data`/
+/// `post_interaction_data` by `parse_interaction_call` - e.g. pulling
+/// liquidity into the maker's account before it's spent, or a rebalance/
+/// accounting callback once the fill lands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InteractionCall {
+    pub target: AccountId,
+    pub method: String,
+    pub args: Vec<u8>,
+    pub gas_tgas: u64,
+}
+
+/// Parse an interaction hook call out of raw extension bytes. Layout:
+/// `tag: u8 (== INTERACTION_CALL_TAG) | gas_tgas: u64 LE | target_len: u16 LE
+/// | target bytes | method_len: u16 LE | method bytes | args`. Returns `None`
+/// if the tag doesn't match, the data is too short to hold the length-
+/// prefixed fields, the target isn't a valid account id, or the method isn't
+/// valid UTF-8.
+pub fn parse_interaction_call(data: &[u8]) -> Option<InteractionCall> {
+    const HEADER_LEN: usize = 1 + 8 + 2;
+    if data.len() < HEADER_LEN || data[0] != INTERACTION_CALL_TAG {
+        return None;
+    }
+
+    let gas_tgas = u64::from_le_bytes(data[1..9].try_into().ok()?);
+    let target_len = u16::from_le_bytes(data[9..HEADER_LEN].try_into().ok()?) as usize;
+    let target_end = HEADER_LEN.checked_add(target_len)?;
+    if data.len() < target_end + 2 {
+        return None;
+    }
+
+    let target = std::str::from_utf8(&data[HEADER_LEN..target_end])
+        .ok()?
+        .parse::<AccountId>()
+        .ok()?;
+
+    let method_len = u16::from_le_bytes(data[target_end..target_end + 2].try_into().ok()?) as usize;
+    let method_start = target_end + 2;
+    let method_end = method_start.checked_add(method_len)?;
+    if data.len() < method_end {
+        return None;
+    }
+
+    let method = std::str::from_utf8(&data[method_start..method_end])
+        .ok()?
+        .to_string();
+    let args = data[method_end..].to_vec();
+
+    Some(InteractionCall {
+        target,
+        method,
+        args,
+        gas_tgas,
+    })
+}
+
+/// `floor(amount * fee_bps / FEE_BASE_POINTS)` - the cut taken out of
+/// `amount` for a protocol or integrator fee expressed in basis points.
+pub fn calculate_fee_amount(amount: U256, fee_bps: u16) -> Result<U256, LimitOrderError> {
+    amount.mul_div_floor(U256::from(fee_bps), U256::from(FEE_BASE_POINTS))
+}
+
+/// Linearly interpolate the rate bump between two `(delay, rate_bump)`
+/// breakpoints at `elapsed` milliseconds since the auction's `start_time`.
+fn interpolate_rate_bump(
+    from_delay: u64,
+    from_bump: u32,
+    to_delay: u64,
+    to_bump: u32,
+    elapsed: u64,
+) -> u32 {
+    if to_delay <= from_delay {
+        return to_bump;
+    }
+    let span = (to_delay - from_delay) as i128;
+    let progress = (elapsed - from_delay) as i128;
+    let diff = to_bump as i128 - from_bump as i128;
+    (from_bump as i128 + diff * progress / span) as u32
+}
+
+/// Current rate bump of a Dutch-auction price curve at `now_ms`. Clamps to
+/// `start_rate_bump` before the auction starts and `end_rate_bump` once
+/// `start_time + duration` has passed; in between, walks `points` to find the
+/// active segment and interpolates linearly within it.
+pub fn current_rate_bump(auction: &AuctionDetails, now_ms: u64) -> u32 {
+    if now_ms <= auction.start_time {
+        return auction.start_rate_bump;
+    }
+    let end_time = auction.start_time + auction.duration;
+    if now_ms >= end_time {
+        return auction.end_rate_bump;
+    }
+
+    let elapsed = now_ms - auction.start_time;
+    let mut prev_delay = 0u64;
+    let mut prev_bump = auction.start_rate_bump;
+    for point in &auction.points {
+        let point_delay = point.delay as u64;
+        if elapsed < point_delay {
+            return interpolate_rate_bump(
+                prev_delay,
+                prev_bump,
+                point_delay,
+                point.rate_bump,
+                elapsed,
+            );
+        }
+        prev_delay = point_delay;
+        prev_bump = point.rate_bump;
+    }
+    interpolate_rate_bump(
+        prev_delay,
+        prev_bump,
+        auction.duration,
+        auction.end_rate_bump,
+        elapsed,
+    )
+}
+
+/// Apply a Dutch-auction rate bump to a taker's base taking amount:
+/// the taker pays more while the bump is high (early in the auction).
+fn apply_rate_bump_to_taking_amount(base: U256, bump: u32) -> Result<U256, LimitOrderError> {
+    let auction_base_points = U256::from(AUCTION_BASE_POINTS);
+    base.mul_div_floor(
+        auction_base_points
+            .checked_add(U256::from(bump))
+            .ok_or(LimitOrderError::InvalidAmounts)?,
+        auction_base_points,
+    )
+}
+
+/// Apply a Dutch-auction rate bump to a maker's base making amount: the
+/// inverse of `apply_rate_bump_to_taking_amount`, so the two stay consistent
+/// whichever side of the fill is held fixed.
+fn apply_rate_bump_to_making_amount(base: U256, bump: u32) -> Result<U256, LimitOrderError> {
+    let auction_base_points = U256::from(AUCTION_BASE_POINTS);
+    base.mul_div_floor(
+        auction_base_points,
+        auction_base_points
+            .checked_add(U256::from(bump))
+            .ok_or(LimitOrderError::InvalidAmounts)?,
+    )
+}
+
+/// Calculate making amount based on taking amount. Rounds down (floor) so a
+/// maker is never shortchanged by fractional-amount rounding - the taker
+/// always receives at most their exact proportional share.
 pub fn calculate_making_amount(
     order: &Order,
     extension: &Extension,
-    requested_taking_amount: u128,
-    _remaining_making_amount: u128,
+    requested_taking_amount: U256,
+    _remaining_making_amount: U256,
     _order_hash: &[u8; 32],
-) -> Result<u128, LimitOrderError> {
-    let making_amount_data = extension.maker_amount_data();
+) -> Result<U256, LimitOrderError> {
+    if order.taking_amount.is_zero() {
+        return Err(LimitOrderError::SwapWithZeroAmount);
+    }
+    let base_making_amount =
+        order
+            .making_amount
+            .mul_div_floor(requested_taking_amount, order.taking_amount)?;
 
+    let making_amount_data = extension.maker_amount_data();
     if making_amount_data.is_empty() {
-        // Linear proportion
-        if order.taking_amount == 0 {
-            return Err(LimitOrderError::SwapWithZeroAmount);
-        }
-        return Ok((order.making_amount * requested_taking_amount) / order.taking_amount);
+        return Ok(base_making_amount);
     }
 
-    // In a real implementation, we would call an external getter contract
-    // For now, return a simplified calculation
-    Ok(requested_taking_amount)
+    let auction =
+        parse_auction_details(making_amount_data).ok_or(LimitOrderError::InvalidAmountData)?;
+    let bump = current_rate_bump(&auction, env::block_timestamp_ms());
+    apply_rate_bump_to_making_amount(base_making_amount, bump)
 }
 
-/// Calculate taking amount based on making amount
+/// Calculate taking amount based on making amount. Rounds up (ceil) so a
+/// taker requesting a given making amount always pays at least the
+/// proportional price - the symmetric counterpart to
+/// `calculate_making_amount`'s floor rounding, so neither direction ever
+/// shortchanges the maker.
 pub fn calculate_taking_amount(
     order: &Order,
     extension: &Extension,
-    requested_making_amount: u128,
-    _remaining_making_amount: u128,
+    requested_making_amount: U256,
+    _remaining_making_amount: U256,
     _order_hash: &[u8; 32],
-) -> Result<u128, LimitOrderError> {
-    let taking_amount_data = extension.taker_amount_data();
+) -> Result<U256, LimitOrderError> {
+    if order.making_amount.is_zero() {
+        return Err(LimitOrderError::SwapWithZeroAmount);
+    }
+    let base_taking_amount =
+        order
+            .taking_amount
+            .mul_div_ceil(requested_making_amount, order.making_amount)?;
 
+    let taking_amount_data = extension.taker_amount_data();
     if taking_amount_data.is_empty() {
-        // Linear proportion
-        if order.making_amount == 0 {
-            return Err(LimitOrderError::SwapWithZeroAmount);
-        }
-        return Ok((order.taking_amount * requested_making_amount) / order.making_amount);
+        return Ok(base_taking_amount);
     }
 
-    // In a real implementation, we would call an external getter contract
-    // For now, return a simplified calculation
-    Ok(requested_making_amount)
+    let auction =
+        parse_auction_details(taking_amount_data).ok_or(LimitOrderError::InvalidAmountData)?;
+    let bump = current_rate_bump(&auction, env::block_timestamp_ms());
+    apply_rate_bump_to_taking_amount(base_taking_amount, bump)
 }
 
 /// Validate extension for an order
@@ -135,15 +552,112 @@ pub fn get_receiver(order: &Order) -> near_sdk::AccountId {
     }
 }
 
-/// Validate signature for an order
+/// Upper bound (inclusive) for a low-s, non-malleable secp256k1 `s` value:
+/// half the curve order, big-endian. Signatures with `s` above this are
+/// rejected per EIP-2 malleability protection.
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0x5D, 0x57, 0x6E, 0x73, 0x57, 0xA4, 0x50, 0x1D, 0xDF, 0xE9, 0x2F, 0x46, 0x68, 0x1B, 0x20, 0xA0,
+];
+
+/// Parse a `0x`-prefixed 40-hex-character Ethereum address out of an
+/// `AccountId`, the convention this NEAR port uses to let an EVM maker's
+/// identity cross to the NEAR side unambiguously (see `get_receiver`'s
+/// `"0x0000...0000"` sentinel check above).
+fn parse_eth_address(account: &near_sdk::AccountId) -> Option<[u8; 20]> {
+    let hex = account.as_str().strip_prefix("0x")?;
+    if hex.len() != 40 {
+        return None;
+    }
+    let mut address = [0u8; 20];
+    for (i, byte) in address.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(address)
+}
+
+/// Parse a NEAR "implicit account" id - 64 lowercase hex characters - into
+/// the raw ed25519 public key it encodes. NEAR itself uses this convention
+/// for unnamed accounts (the account id literally *is* the hex of the key),
+/// mirrored here the same way `parse_eth_address`'s `0x` convention lets an
+/// EVM maker's identity cross to the NEAR side.
+fn parse_near_implicit_pubkey(account: &near_sdk::AccountId) -> Option<[u8; 32]> {
+    let hex = account.as_str();
+    if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(key)
+}
+
+/// Validate an order's EIP-712 signature against its maker's identity,
+/// using whichever scheme the maker account's format implies.
+///
+/// EVM-side makers are identified by a `0x`-prefixed eth address (see
+/// `parse_eth_address`): recovers the secp256k1 public key behind
+/// `signature` over `hash_order_712(order, domain_separator)` and checks
+/// the recovered address against it. Accepts both the 65-byte `(r, s, v)`
+/// form and the compact 64-byte EIP-2098 form (`s`'s top bit carries the
+/// recovery id). Rejects malleable signatures (`s` above half the curve
+/// order) and any `v` outside `{0, 1, 27, 28}`, mirroring the ethkey
+/// `verify_address`/recover flow used in Ethereum clients.
+///
+/// NEAR-native makers are identified by an implicit account id (see
+/// `parse_near_implicit_pubkey`): `signature` must be the 64-byte ed25519
+/// signature produced by that account's key over the same digest.
 pub fn validate_signature(
-    _order: &Order,
-    _signature: &[u8],
-    _signer: &near_sdk::AccountId,
-) -> bool {
-    // In a real implementation, we would validate the EIP-712 signature
-    // For now, return true for testing
-    true
+    order: &Order,
+    signature: &[u8],
+    signer: &near_sdk::AccountId,
+    domain_separator: &[u8; 32],
+) -> Result<bool, LimitOrderError> {
+    if let Some(public_key) = parse_near_implicit_pubkey(signer) {
+        let signature: [u8; 64] = signature
+            .try_into()
+            .map_err(|_| LimitOrderError::InvalidSignature)?;
+        let digest = hash_order_712(order, domain_separator);
+        return Ok(env::ed25519_verify(&signature, &digest, &public_key));
+    }
+
+    let (r_s, v) = match signature.len() {
+        65 => {
+            let v = match signature[64] {
+                27 => 0,
+                28 => 1,
+                v @ (0 | 1) => v,
+                _ => return Err(LimitOrderError::InvalidSignature),
+            };
+            let mut r_s = [0u8; 64];
+            r_s.copy_from_slice(&signature[0..64]);
+            (r_s, v)
+        }
+        64 => {
+            let mut r_s = [0u8; 64];
+            r_s.copy_from_slice(signature);
+            let v = (r_s[32] >> 7) & 1;
+            r_s[32] &= 0x7f;
+            (r_s, v)
+        }
+        _ => return Err(LimitOrderError::InvalidSignature),
+    };
+
+    if r_s[32..64] > SECP256K1_HALF_ORDER[..] {
+        return Err(LimitOrderError::InvalidSignature);
+    }
+
+    let eth_address = parse_eth_address(signer).ok_or(LimitOrderError::InvalidSignature)?;
+
+    let digest = hash_order_712(order, domain_separator);
+    let public_key = match env::ecrecover(&digest, &r_s, v, false) {
+        Some(key) => key,
+        None => return Ok(false),
+    };
+    let hashed_key = env::keccak256(&public_key);
+
+    Ok(hashed_key[12..32] == eth_address)
 }
 
 /// Check if order is expired
@@ -154,9 +668,9 @@ pub fn is_order_expired(_order: &Order) -> bool {
 }
 
 /// Validate order amounts
-pub fn validate_order_amounts(order: &Order, taking_amount: u128) -> bool {
+pub fn validate_order_amounts(order: &Order, taking_amount: U256) -> bool {
     // Check if taking amount is valid
-    if taking_amount == 0 {
+    if taking_amount.is_zero() {
         return false;
     }
 
@@ -4,7 +4,7 @@ use near_sdk::{
     collections::UnorderedMap,
 };
 use crate::types::{Order, ValidationData, InvalidatorError};
-use crate::utils::{validate_limit_order_protocol, extract_post_interaction_data, parse_taker_data, validate_merkle_proof, create_validation_key, extract_root};
+use crate::utils::{validate_limit_order_protocol, extract_post_interaction_data, parse_taker_data, verify_merkle_proof, create_validation_key, extract_root, hash_taker_leaf, validate_merkle_proof};
 
 /// Merkle Storage Invalidator contract for cross-chain atomic swap
 /// Handles Merkle proof validation for orders that support multiple fills
@@ -47,6 +47,8 @@ impl MerkleStorageInvalidator {
         _taking_amount: u128,
         _remaining_making_amount: u128,
         extra_data: Vec<u8>,
+        hashlock_info: [u8; 32],
+        max_index: u64,
     ) -> Result<(), InvalidatorError> {
         // Only limit order protocol can call this
         validate_limit_order_protocol(&env::predecessor_account_id(), &self.limit_order_protocol)?;
@@ -54,40 +56,112 @@ impl MerkleStorageInvalidator {
         // Extract post interaction data from extension
         let _post_interaction_data = extract_post_interaction_data(&extension)?;
 
-        // Parse taker data from extra data
+        // Parse taker data from extra data - may carry several secrets sharing one multiproof
         let taker_data = parse_taker_data(&extra_data)?;
+        if taker_data.indices.is_empty() {
+            return Err(InvalidatorError::InvalidExtraData);
+        }
 
-        // Extract root from hashlock info (simplified - in real implementation this would come from post interaction data)
-        let root_shortened = extract_root(&[0u8; 32]); // Simplified - would come from actual data
+        // Root the order's `allow_multiple_fills` Merkle tree committed to.
+        let root_shortened = extract_root(&hashlock_info);
 
         // Create validation key
         let key = create_validation_key(&order_hash, &root_shortened);
 
-        // Validate Merkle proof
-        let computed_root = validate_merkle_proof(
-            &taker_data.proof,
-            taker_data.secret_hash,
-            taker_data.idx,
-            root_shortened,
+        // Hash each (index, secret_hash) pair into its leaf and fold the batch
+        // against the root, bounding every index to the order's `N+1`-leaf tree.
+        verify_merkle_proof(&taker_data, root_shortened, max_index)?;
+
+        // Reject indices already consumed by a prior batch for this order/root,
+        // enforcing the strictly-increasing-index invariant across calls.
+        let first_idx = taker_data.indices[0];
+        if let Some(existing) = self.last_validated.get(&key) {
+            if first_idx < existing.index {
+                return Err(InvalidatorError::InvalidProof);
+            }
+        }
+
+        // Store the tip of the batch: the secret hash of the highest index just
+        // validated, advancing the stored index past the fill made.
+        let last_idx = *taker_data.indices.last().unwrap();
+        let last_leaf = *taker_data.secret_hashes.last().unwrap();
+        let validation_data = ValidationData {
+            leaf: last_leaf,
+            index: last_idx + 1,
+        };
+
+        self.last_validated.insert(&key, &validation_data);
+
+        log!(
+            "Merkle multiproof validated and stored: order_hash={:?}, leaves={}, last_index={}",
+            order_hash,
+            taker_data.indices.len(),
+            last_idx
         );
 
-        if !computed_root {
+        Ok(())
+    }
+
+    /// Validate a single revealed secret against an order's committed Merkle
+    /// root - the single-leaf counterpart to `taker_interaction`'s batched
+    /// multiproof path, for a caller that only has one `(index, secret_hash)`
+    /// pair and a plain sibling path rather than a shared multiproof.
+    ///
+    /// Recomputes `leaf = hash_taker_leaf(index, secret_hash)`, folds `proof`
+    /// up to the root via `validate_merkle_proof`, and checks it matches
+    /// `root_shortened`. Like `taker_interaction`, the order/root pair's
+    /// `last_validated` index only ever advances: a replayed or
+    /// already-superseded `index` is rejected, so a fill's cumulative filled
+    /// fraction can be read back off `last_validated_for_order`.
+    #[handle_result]
+    pub fn validate_proof(
+        &mut self,
+        order_hash: [u8; 32],
+        root_shortened: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        index: u64,
+        secret_hash: [u8; 32],
+    ) -> Result<(), InvalidatorError> {
+        let leaf = hash_taker_leaf(index, secret_hash);
+        if !validate_merkle_proof(&proof, leaf, index, root_shortened) {
             return Err(InvalidatorError::InvalidProof);
         }
 
-        // Store validation data
+        let key = create_validation_key(&order_hash, &root_shortened);
+        if let Some(existing) = self.last_validated.get(&key) {
+            if index < existing.index {
+                return Err(InvalidatorError::InvalidProof);
+            }
+        }
+
         let validation_data = ValidationData {
-            leaf: taker_data.secret_hash,
-            index: taker_data.idx + 1,
+            leaf,
+            index: index + 1,
         };
-        
         self.last_validated.insert(&key, &validation_data);
 
-        log!("Merkle proof validated and stored: order_hash={:?}, index={}", order_hash, taker_data.idx);
+        log!(
+            "Merkle proof validated and stored: order_hash={:?}, index={}",
+            order_hash,
+            index
+        );
 
         Ok(())
     }
 
+    /// Convenience lookup for `validate_proof`'s per-(order, root) record,
+    /// bundling `create_validation_key` so callers don't have to rebuild it
+    /// themselves - the single-secret counterpart to `get_last_validated`'s
+    /// raw-key form.
+    pub fn last_validated_for_order(
+        &self,
+        order_hash: [u8; 32],
+        root_shortened: [u8; 32],
+    ) -> Option<ValidationData> {
+        self.last_validated
+            .get(&create_validation_key(&order_hash, &root_shortened))
+    }
+
     /// Get limit order protocol address
     pub fn get_limit_order_protocol(&self) -> AccountId {
         self.limit_order_protocol.clone()
@@ -180,10 +254,237 @@ mod tests {
         };
         
         contract.last_validated.insert(&key, &validation_data);
-        
+
         assert!(contract.has_validation_data(key));
         let retrieved = contract.get_last_validated(key).unwrap();
         assert_eq!(retrieved.leaf, [2u8; 32]);
         assert_eq!(retrieved.index, 5);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_verify_multi_proof_degenerate_single_leaf() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let leaf = [7u8; 32];
+        assert!(crate::utils::verify_multi_proof(&[leaf], &[], &[], leaf));
+        assert!(!crate::utils::verify_multi_proof(&[leaf], &[], &[], [0u8; 32]));
+    }
+
+    #[test]
+    fn test_verify_multi_proof_two_leaves_share_one_root() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let l0 = [1u8; 32];
+        let l1 = [2u8; 32];
+        let mut data = Vec::new();
+        data.extend_from_slice(&l0);
+        data.extend_from_slice(&l1);
+        let root: [u8; 32] = near_sdk::env::keccak256(&data).try_into().unwrap();
+
+        assert!(crate::utils::verify_multi_proof(&[l0, l1], &[], &[true], root));
+        assert!(!crate::utils::verify_multi_proof(&[l0, l1], &[], &[true], [9u8; 32]));
+    }
+
+    #[test]
+    fn test_verify_multi_proof_rejects_length_mismatch() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        // leaves.len() + proof.len() must equal proof_flags.len() + 1
+        assert!(!crate::utils::verify_multi_proof(&[[1u8; 32], [2u8; 32]], &[], &[], [0u8; 32]));
+    }
+
+    #[test]
+    fn test_parse_taker_data_roundtrip() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&2u16.to_le_bytes()); // count
+        bytes.extend_from_slice(&0u64.to_le_bytes()); // idx 0
+        bytes.extend_from_slice(&[1u8; 32]); // secret_hash 0
+        bytes.extend_from_slice(&1u64.to_le_bytes()); // idx 1
+        bytes.extend_from_slice(&[2u8; 32]); // secret_hash 1
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // flags_len
+        bytes.push(1u8); // flags: [true]
+        bytes.extend_from_slice(&[3u8; 32]); // one sibling proof element (unused since flags all leaf-sourced here, but exercises the tail parsing)
+
+        let parsed = crate::utils::parse_taker_data(&bytes).expect("well-formed taker data");
+        assert_eq!(parsed.indices, vec![0, 1]);
+        assert_eq!(parsed.secret_hashes, vec![[1u8; 32], [2u8; 32]]);
+        assert_eq!(parsed.proof_flags, vec![true]);
+        assert_eq!(parsed.proof, vec![[3u8; 32]]);
+    }
+
+    fn single_leaf_extra_data(idx: u64, secret_hash: [u8; 32]) -> Vec<u8> {
+        let mut extra_data = Vec::new();
+        extra_data.extend_from_slice(&1u16.to_le_bytes());
+        extra_data.extend_from_slice(&idx.to_le_bytes());
+        extra_data.extend_from_slice(&secret_hash);
+        extra_data.extend_from_slice(&0u16.to_le_bytes()); // no flags
+        // no trailing proof bytes
+        extra_data
+    }
+
+    #[test]
+    fn test_taker_interaction_validates_and_stores_batch() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MerkleStorageInvalidator::new(accounts(1));
+
+        let secret_hash = [5u8; 32];
+        let leaf = crate::utils::hash_taker_leaf(3, secret_hash);
+        let extra_data = single_leaf_extra_data(3, secret_hash);
+
+        contract
+            .taker_interaction(
+                create_test_order(),
+                vec![0u8; 4],
+                [9u8; 32],
+                accounts(2),
+                100,
+                100,
+                0,
+                extra_data,
+                leaf, // hashlock_info: a single-leaf tree's root is the leaf itself
+                3,
+            )
+            .expect("multiproof batch should validate");
+
+        let key = create_validation_key(&[9u8; 32], &extract_root(&leaf));
+        let stored = contract.get_last_validated(key).unwrap();
+        assert_eq!(stored.leaf, secret_hash);
+        assert_eq!(stored.index, 4);
+    }
+
+    #[test]
+    fn test_taker_interaction_rejects_index_past_max() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MerkleStorageInvalidator::new(accounts(1));
+
+        let secret_hash = [5u8; 32];
+        let leaf = crate::utils::hash_taker_leaf(3, secret_hash);
+        let extra_data = single_leaf_extra_data(3, secret_hash);
+
+        let result = contract.taker_interaction(
+            create_test_order(),
+            vec![0u8; 4],
+            [9u8; 32],
+            accounts(2),
+            100,
+            100,
+            0,
+            extra_data,
+            leaf,
+            2, // max_index below the submitted idx of 3
+        );
+
+        assert_eq!(result, Err(InvalidatorError::InvalidProof));
+    }
+
+    #[test]
+    fn test_taker_interaction_rejects_replayed_index() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MerkleStorageInvalidator::new(accounts(1));
+
+        let secret_hash = [5u8; 32];
+        let leaf = crate::utils::hash_taker_leaf(3, secret_hash);
+
+        contract
+            .taker_interaction(
+                create_test_order(),
+                vec![0u8; 4],
+                [9u8; 32],
+                accounts(2),
+                100,
+                100,
+                0,
+                single_leaf_extra_data(3, secret_hash),
+                leaf,
+                10,
+            )
+            .expect("first batch should validate");
+
+        // Replaying the same index (3) again against the same order/root
+        // must be rejected: the prior call advanced `last_validated` past it.
+        let result = contract.taker_interaction(
+            create_test_order(),
+            vec![0u8; 4],
+            [9u8; 32],
+            accounts(2),
+            100,
+            100,
+            0,
+            single_leaf_extra_data(3, secret_hash),
+            leaf,
+            10,
+        );
+
+        assert_eq!(result, Err(InvalidatorError::InvalidProof));
+    }
+
+    #[test]
+    fn test_validate_proof_accepts_leaf_with_empty_proof() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MerkleStorageInvalidator::new(accounts(1));
+
+        // A single-leaf tree: its root is the leaf itself, so an empty
+        // sibling path is a valid proof.
+        let secret_hash = [5u8; 32];
+        let root = crate::utils::hash_taker_leaf(0, secret_hash);
+
+        contract
+            .validate_proof([9u8; 32], root, vec![], 0, secret_hash)
+            .expect("single-leaf proof should validate");
+
+        let stored = contract.last_validated_for_order([9u8; 32], root).unwrap();
+        assert_eq!(stored.leaf, root);
+        assert_eq!(stored.index, 1);
+    }
+
+    #[test]
+    fn test_validate_proof_rejects_wrong_sibling() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MerkleStorageInvalidator::new(accounts(1));
+
+        let leaf0 = crate::utils::hash_taker_leaf(0, [5u8; 32]);
+        let leaf1 = crate::utils::hash_taker_leaf(1, [6u8; 32]);
+        let mut data = Vec::new();
+        data.extend_from_slice(&leaf0);
+        data.extend_from_slice(&leaf1);
+        let root: [u8; 32] = near_sdk::env::keccak256(&data).try_into().unwrap();
+
+        // Index 0 pairs with `leaf1` as its sibling; submitting the wrong
+        // sibling should fail to reconstruct `root`.
+        let result = contract.validate_proof([9u8; 32], root, vec![[0u8; 32]], 0, [5u8; 32]);
+        assert_eq!(result, Err(InvalidatorError::InvalidProof));
+    }
+
+    #[test]
+    fn test_validate_proof_rejects_replayed_index() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MerkleStorageInvalidator::new(accounts(1));
+
+        let secret_hash = [5u8; 32];
+        let root = crate::utils::hash_taker_leaf(0, secret_hash);
+
+        contract
+            .validate_proof([9u8; 32], root, vec![], 0, secret_hash)
+            .expect("first reveal should validate");
+
+        // `last_validated` already advanced past index 0, so replaying it
+        // is rejected even though the proof itself is still valid.
+        let result = contract.validate_proof([9u8; 32], root, vec![], 0, secret_hash);
+        assert_eq!(result, Err(InvalidatorError::InvalidProof));
+    }
+}
\ No newline at end of file
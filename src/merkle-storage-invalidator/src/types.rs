@@ -85,20 +85,30 @@ impl Default for ValidationData {
     }
 }
 
-/// Taker data for Merkle proof validation
+/// Taker data for Merkle multiproof validation. A single `taker_interaction`
+/// call can validate several partial-fill secrets against one root by sharing
+/// interior nodes instead of submitting a full sibling path per secret.
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
 pub struct TakerData {
-    pub idx: u64,
-    pub secret_hash: [u8; 32],
+    /// Leaf indices being validated together, in ascending tree-index order.
+    pub indices: Vec<u64>,
+    /// Secret hashes, paired 1:1 with `indices`; each is hashed with its index
+    /// into a leaf by `hash_taker_leaf`.
+    pub secret_hashes: Vec<[u8; 32]>,
+    /// Sibling hashes not re-derivable from `secret_hashes`, shared across the batch.
     pub proof: Vec<[u8; 32]>,
+    /// Per `verify_multi_proof`: for each interior step, whether the second
+    /// operand comes from a pending leaf/computed hash rather than `proof`.
+    pub proof_flags: Vec<bool>,
 }
 
 impl Default for TakerData {
     fn default() -> Self {
         Self {
-            idx: 0,
-            secret_hash: [0u8; 32],
+            indices: Vec::new(),
+            secret_hashes: Vec::new(),
             proof: Vec::new(),
+            proof_flags: Vec::new(),
         }
     }
 }
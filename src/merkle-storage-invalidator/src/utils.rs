@@ -53,24 +53,174 @@ pub fn extract_post_interaction_data(extension: &[u8]) -> Result<&[u8], Invalida
     Ok(extension)
 }
 
-/// Parse taker data from extra data
+/// Verify many leaves against one Merkle root in a single pass, sharing
+/// interior nodes instead of requiring a separate sibling path per leaf.
+///
+/// `leaves` must be supplied in ascending tree-index order, the same
+/// convention `process_merkle_proof`/`validate_merkle_proof` use for a single
+/// leaf. `proof_flags` has one entry per interior step: `true` means the
+/// second operand of that step is itself a pending leaf/computed hash,
+/// `false` means it comes from `proof`. Malformed inputs that violate the
+/// `leaves.len() + proof.len() == proof_flags.len() + 1` length invariant are
+/// rejected rather than panicking.
+pub fn verify_multi_proof(
+    leaves: &[[u8; 32]],
+    proof: &[[u8; 32]],
+    proof_flags: &[bool],
+    root: [u8; 32],
+) -> bool {
+    let total = proof_flags.len();
+    if leaves.len() + proof.len() != total + 1 {
+        return false;
+    }
+
+    if total == 0 {
+        let computed_root = if !leaves.is_empty() {
+            leaves[0]
+        } else if !proof.is_empty() {
+            proof[0]
+        } else {
+            return false;
+        };
+        return computed_root == root;
+    }
+
+    let mut hashes: Vec<[u8; 32]> = Vec::with_capacity(total);
+    let mut leaf_pos = 0usize;
+    let mut hash_pos = 0usize;
+    let mut proof_pos = 0usize;
+
+    for i in 0..total {
+        let a = if leaf_pos < leaves.len() {
+            leaf_pos += 1;
+            leaves[leaf_pos - 1]
+        } else if hash_pos < hashes.len() {
+            hash_pos += 1;
+            hashes[hash_pos - 1]
+        } else {
+            return false;
+        };
+
+        let b = if proof_flags[i] {
+            if leaf_pos < leaves.len() {
+                leaf_pos += 1;
+                leaves[leaf_pos - 1]
+            } else if hash_pos < hashes.len() {
+                hash_pos += 1;
+                hashes[hash_pos - 1]
+            } else {
+                return false;
+            }
+        } else if proof_pos < proof.len() {
+            proof_pos += 1;
+            proof[proof_pos - 1]
+        } else {
+            return false;
+        };
+
+        let mut data = Vec::with_capacity(64);
+        data.extend_from_slice(&a);
+        data.extend_from_slice(&b);
+        hashes.push(near_sdk::env::keccak256(&data).try_into().unwrap());
+    }
+
+    hashes[total - 1] == root
+}
+
+/// Per-leaf hash binding a secret to its tree position:
+/// `keccak256(index_le || secret_hash)`. Hashing the index into the leaf
+/// (rather than using the raw secret hash as the leaf) stops a secret
+/// revealed for one index being replayed to satisfy a different index's leaf
+/// in the same tree.
+pub fn hash_taker_leaf(index: u64, secret_hash: [u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(40);
+    data.extend_from_slice(&index.to_le_bytes());
+    data.extend_from_slice(&secret_hash);
+    near_sdk::env::keccak256(&data).try_into().unwrap()
+}
+
+/// Verify that `taker_data` reconstructs `root`, the way 1inch Fusion+ does
+/// for `N+1`-secret partial fills: each `(index, secret_hash)` pair is first
+/// hashed into a leaf via `hash_taker_leaf`, then the batch is folded against
+/// `proof`/`proof_flags` via `verify_multi_proof`. Every index must fall in
+/// `[0, max_index]` (the `N` an `allow_multiple_fills` order committed to),
+/// and indices must be strictly increasing within the batch so the same
+/// secret index can't be submitted twice in one call.
+pub fn verify_merkle_proof(
+    taker_data: &TakerData,
+    root: [u8; 32],
+    max_index: u64,
+) -> Result<(), InvalidatorError> {
+    if taker_data.indices.is_empty() || taker_data.indices.len() != taker_data.secret_hashes.len() {
+        return Err(InvalidatorError::InvalidProof);
+    }
+    if taker_data.indices.iter().any(|idx| *idx > max_index) {
+        return Err(InvalidatorError::InvalidProof);
+    }
+    if taker_data.indices.windows(2).any(|pair| pair[1] <= pair[0]) {
+        return Err(InvalidatorError::InvalidProof);
+    }
+
+    let leaves: Vec<[u8; 32]> = taker_data
+        .indices
+        .iter()
+        .zip(taker_data.secret_hashes.iter())
+        .map(|(idx, secret_hash)| hash_taker_leaf(*idx, *secret_hash))
+        .collect();
+
+    if verify_multi_proof(&leaves, &taker_data.proof, &taker_data.proof_flags, root) {
+        Ok(())
+    } else {
+        Err(InvalidatorError::InvalidProof)
+    }
+}
+
+/// Parse taker data from extra data.
+///
+/// Wire format (little-endian), simplified for this NEAR port:
+/// - `count: u16` - number of `(idx, secret_hash)` leaves validated together
+/// - `count * 40` bytes - the `(idx: u64, secret_hash: [u8; 32])` pairs, in
+///   ascending tree-index order
+/// - `flags_len: u16` - number of `verify_multi_proof` proof flags
+/// - `flags_len` bytes - one `0`/`1` byte per proof flag
+/// - remaining bytes - sibling hashes for `verify_multi_proof`, 32 bytes each
 pub fn parse_taker_data(extra_data: &[u8]) -> Result<TakerData, InvalidatorError> {
-    if extra_data.len() < 40 { // Minimum size for idx + secret_hash
+    if extra_data.len() < 2 {
         return Err(InvalidatorError::InvalidExtraData);
     }
-    
-    // Extract index (first 8 bytes)
-    let idx = u64::from_le_bytes(extra_data[0..8].try_into().unwrap());
-    
-    // Extract secret hash (next 32 bytes)
-    let mut secret_hash = [0u8; 32];
-    secret_hash.copy_from_slice(&extra_data[8..40]);
-    
+
+    let count = u16::from_le_bytes(extra_data[0..2].try_into().unwrap()) as usize;
+    let mut offset = 2;
+    let entries_len = count * 40;
+    if extra_data.len() < offset + entries_len + 2 {
+        return Err(InvalidatorError::InvalidExtraData);
+    }
+
+    let mut indices = Vec::with_capacity(count);
+    let mut secret_hashes = Vec::with_capacity(count);
+    for i in 0..count {
+        let start = offset + i * 40;
+        let idx = u64::from_le_bytes(extra_data[start..start + 8].try_into().unwrap());
+        let mut secret_hash = [0u8; 32];
+        secret_hash.copy_from_slice(&extra_data[start + 8..start + 40]);
+        indices.push(idx);
+        secret_hashes.push(secret_hash);
+    }
+    offset += entries_len;
+
+    let flags_len = u16::from_le_bytes(extra_data[offset..offset + 2].try_into().unwrap()) as usize;
+    offset += 2;
+    if extra_data.len() < offset + flags_len {
+        return Err(InvalidatorError::InvalidExtraData);
+    }
+    let proof_flags: Vec<bool> = extra_data[offset..offset + flags_len].iter().map(|b| *b != 0).collect();
+    offset += flags_len;
+
     // Extract proof (remaining bytes)
-    let proof_data = &extra_data[40..];
+    let proof_data = &extra_data[offset..];
     let proof_elements = proof_data.len() / 32;
     let mut proof = Vec::new();
-    
+
     for i in 0..proof_elements {
         let start = i * 32;
         let end = start + 32;
@@ -80,11 +230,12 @@ pub fn parse_taker_data(extra_data: &[u8]) -> Result<TakerData, InvalidatorError
             proof.push(element);
         }
     }
-    
+
     Ok(TakerData {
-        idx,
-        secret_hash,
+        indices,
+        secret_hashes,
         proof,
+        proof_flags,
     })
 }
 